@@ -6,6 +6,7 @@ fn main() {
         "kbs/journal-titles.kb",
         "kbs/report-numbers.kb",
         "kbs/collaborations.kb",
+        "kbs/publishers.kb",
     ] {
         println!("cargo::rerun-if-changed={kb}");
     }
@@ -23,6 +24,7 @@ fn kb_hash() -> u64 {
         "kbs/journal-titles.kb",
         "kbs/report-numbers.kb",
         "kbs/collaborations.kb",
+        "kbs/publishers.kb",
     ] {
         if let Ok(contents) = std::fs::read_to_string(kb) {
             contents.hash(&mut hasher);
@@ -0,0 +1,367 @@
+//! Streaming batch extraction for large corpora: one NDJSON line per input,
+//! written and flushed as soon as it is done, so a killed run leaves valid
+//! (if partial) output. The CLI runs every document through the same
+//! pipeline (`references_from_zoned`, `references_from_raw`).
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use pdfium_render::prelude::Pdfium;
+use serde::Serialize;
+
+use crate::citations;
+use crate::collect::{self, CollectOptions};
+use crate::doi::{self, DoiCache, EnrichOptions};
+use crate::error::RefextractError;
+use crate::layout::LayoutConfig;
+use crate::parse::ParseOptions;
+use crate::pdf::{self, ExtractOptions};
+use crate::stats::{self, Stats};
+use crate::types::{Block, ParsedReference, RawReference, ZonedBlock};
+use crate::zones;
+use crate::SequenceIssue;
+
+/// One input's outcome: its references, or why it failed.
+#[derive(Debug, Serialize)]
+pub struct BatchResult {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<Vec<ParsedReference>>,
+    /// `check_marker_sequence` findings, as warning text.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub marker_issues: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn new<E: Display>(file: String, extraction: Result<Extraction, E>) -> Self {
+        match extraction {
+            Ok(Extraction { references, marker_issues, .. }) => BatchResult {
+                file,
+                references: Some(references),
+                marker_issues: marker_issues.iter().map(ToString::to_string).collect(),
+                error: None,
+            },
+            Err(e) => BatchResult { file, references: None, marker_issues: Vec::new(), error: Some(e.to_string()) },
+        }
+    }
+}
+
+/// One document's references and what went wrong reading them.
+#[derive(Debug, Clone, Default)]
+pub struct Extraction {
+    pub references: Vec<ParsedReference>,
+    pub marker_issues: Vec<SequenceIssue>,
+    /// `cap_references` warnings.
+    pub warnings: Vec<String>,
+    /// Counts for `--stats`, taken before `only_with_doi`/`only_with_arxiv`.
+    pub stats: Stats,
+}
+
+impl From<Vec<ParsedReference>> for Extraction {
+    fn from(references: Vec<ParsedReference>) -> Self {
+        let stats = stats::summarize(&references);
+        Extraction { references, marker_issues: Vec::new(), warnings: Vec::new(), stats }
+    }
+}
+
+/// Settings for every stage of `references_from_pdf`.
+#[derive(Debug, Clone, Default)]
+pub struct BatchOptions<'a> {
+    pub extract: ExtractOptions,
+    pub layout: LayoutConfig,
+    pub collect: CollectOptions,
+    pub parse: ParseOptions<'a>,
+    pub refs: RefOptions,
+}
+
+/// What happens to the references around parsing; each field is the
+/// command-line switch of the same name.
+#[derive(Debug, Clone, Default)]
+pub struct RefOptions {
+    pub no_subrefs: bool,
+    pub cited_on_pages: bool,
+    pub exclude_footnotes_without_markers: bool,
+    pub title_from_italics: bool,
+    pub max_refs: Option<usize>,
+    pub first_author_only: bool,
+    pub min_confidence: Option<f32>,
+    pub dedup: bool,
+    pub sort: bool,
+    pub arxiv_prefix: bool,
+    pub no_raw: bool,
+    pub only_with_doi: bool,
+    pub only_with_arxiv: bool,
+}
+
+/// DOI enrichment for `references_from_raw`.
+pub struct DoiLookup<'c> {
+    pub cache: &'c DoiCache,
+    pub enrich: EnrichOptions,
+    /// Also fill arXiv IDs and DOIs from each other (`doi::cross_link`).
+    pub cross_link: bool,
+}
+
+/// Extract, zone, collect and parse the references of one PDF.
+pub fn references_from_pdf(
+    pdfium: &Pdfium,
+    path: &Path,
+    opts: &BatchOptions,
+) -> Result<Extraction, RefextractError> {
+    let pages = pdf::extract_chars(pdfium, path, &opts.extract)?;
+    Ok(references_from_zoned(&zones::zone_pages(&pages, &opts.layout), opts, None))
+}
+
+/// Run zoning, collection and parsing on blocks from another PDF text
+/// extractor (pdfalto, a commercial tool), bypassing `pdf` and `layout`.
+/// `pages[i]` holds page `i + 1`'s blocks; every page is `page_height`
+/// points tall. `opts.extract` is not used.
+///
+/// Blocks are built the way `layout` builds them:
+/// - coordinates are PDF points with the origin at the bottom left, so `y`
+///   grows up the page;
+/// - a `Word`'s `x`/`y` is its left edge and baseline, and `font_size` its
+///   size in points; set `is_superscript` on raised footnote and citation
+///   markers, and `is_italic`/`font_name` where known (bold font names
+///   mark headings);
+/// - a `Line`'s words run left to right on one baseline `y`, between
+///   `x_start` and `x_end`;
+/// - a `Block` is one paragraph or reference entry, lines top to bottom:
+///   `x` is its left edge, `y` the top line's baseline, `height` the span
+///   down to the last line plus one font size;
+/// - each page's blocks are in reading order, the left column before the
+///   right.
+pub fn references_from_blocks(pages: Vec<Vec<Block>>, page_height: f32, opts: &BatchOptions) -> Extraction {
+    references_from_zoned(&zones::zone_blocks(pages, page_height, &opts.layout), opts, None)
+}
+
+/// Collect and parse the references of zoned pages (`references_from_raw`).
+pub fn references_from_zoned(zoned: &[Vec<ZonedBlock>], opts: &BatchOptions, doi: Option<&DoiLookup>) -> Extraction {
+    let (raw_refs, cited_pages) = collect_raw_references(zoned, opts);
+    references_from_raw(raw_refs, &cited_pages, zoned.len(), opts, doi)
+}
+
+/// Collected references, one per entry, and with `opts.refs.cited_on_pages`
+/// the body pages citing each marker.
+pub fn collect_raw_references(
+    zoned: &[Vec<ZonedBlock>],
+    opts: &BatchOptions,
+) -> (Vec<RawReference>, HashMap<String, Vec<usize>>) {
+    let mut refs = collect::collect_references_with(zoned, &opts.collect);
+    let mut cited_pages = HashMap::new();
+    if opts.refs.cited_on_pages {
+        cited_pages = citations::cited_pages(zoned, &opts.collect.ref_headings, &refs);
+    }
+    if opts.refs.exclude_footnotes_without_markers {
+        collect::drop_weak_footnotes(&mut refs);
+    }
+    if opts.refs.title_from_italics {
+        collect::attach_italic_runs(&mut refs, zoned);
+    }
+    (refs, cited_pages)
+}
+
+/// Parse collected references and finish them: linking, filtering,
+/// ordering and, given `doi`, DOI lookup. `pages` is the document's page
+/// count for `cap_references`, 0 for text input.
+pub fn references_from_raw(
+    raw_refs: Vec<RawReference>,
+    cited_pages: &HashMap<String, Vec<usize>>,
+    pages: usize,
+    opts: &BatchOptions,
+    doi: Option<&DoiLookup>,
+) -> Extraction {
+    let refs = &opts.refs;
+    let raw_refs = crate::split_semicolon_subrefs(raw_refs);
+    let mut parsed = if refs.no_subrefs {
+        crate::parse_primary_references(&raw_refs, &opts.parse)
+    } else {
+        crate::parse_all_references(&raw_refs, &opts.parse)
+    };
+    // Before any filtering, which would leave holes of its own.
+    let marker_issues = crate::check_marker_sequence(&parsed);
+    crate::resolve_ibid_journals(&mut parsed);
+    crate::resolve_same_authors(&mut parsed);
+    citations::attach_cited_pages(&mut parsed, cited_pages);
+    let warnings = crate::cap_references(&mut parsed, pages, refs.max_refs);
+    if refs.first_author_only {
+        crate::first_author_only(&mut parsed);
+    }
+    // Filter before DOI lookup so low-quality entries cost no requests.
+    if let Some(min) = refs.min_confidence {
+        parsed.retain(|r| r.confidence >= min);
+    }
+    if refs.dedup {
+        parsed = crate::dedup_references(parsed);
+    }
+    if refs.sort {
+        crate::sort_by_marker(&mut parsed);
+    }
+    let mut stats = stats::summarize(&parsed);
+    if let Some(lookup) = doi {
+        doi::enrich_dois(&mut parsed, lookup.cache, &lookup.enrich);
+        if lookup.cross_link {
+            doi::cross_link(&mut parsed, lookup.cache, &lookup.enrich);
+        }
+    }
+    // After lookup: DOI cache keys use the bare ID.
+    if refs.arxiv_prefix {
+        crate::prefix_arxiv_ids(&mut parsed);
+    }
+    if refs.no_raw {
+        crate::drop_raw_refs(&mut parsed);
+    }
+    stats.doi = parsed.iter().filter(|r| r.doi.is_some()).count();
+    stats.marker_issues = marker_issues.clone();
+    // Last, so DOIs found by lookup count; the stats still count everything.
+    crate::retain_linked(&mut parsed, refs.only_with_doi, refs.only_with_arxiv);
+    Extraction { references: parsed, marker_issues, warnings, stats }
+}
+
+/// Process PDFs one after another, writing a `BatchResult` line to `out`
+/// for each. A file that fails becomes an `error` line; only a failed
+/// write stops the stream.
+pub fn process_batch<I, W>(pdfium: &Pdfium, inputs: I, opts: &BatchOptions, out: W) -> io::Result<()>
+where
+    I: IntoIterator<Item = PathBuf>,
+    W: Write,
+{
+    stream_results(inputs, out, |path| references_from_pdf(pdfium, path, opts))
+}
+
+/// `process_batch` with any per-file extraction, e.g. for inputs that are
+/// not PDFs.
+pub fn stream_results<I, W, F, E>(inputs: I, mut out: W, mut extract: F) -> io::Result<()>
+where
+    I: IntoIterator<Item = PathBuf>,
+    W: Write,
+    F: FnMut(&Path) -> Result<Extraction, E>,
+    E: Display,
+{
+    for path in inputs {
+        let result = BatchResult::new(path.display().to_string(), extract(&path));
+        write_result(&mut out, &result)?;
+    }
+    Ok(())
+}
+
+/// Write `result` as one NDJSON line and flush it.
+pub fn write_result<W: Write>(mut out: W, result: &BatchResult) -> io::Result<()> {
+    serde_json::to_writer(&mut out, result)?;
+    out.write_all(b"\n")?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Line, Word};
+
+    #[test]
+    fn every_input_gets_its_own_line() {
+        let inputs = ["a.txt", "missing.txt", "b.txt"].map(PathBuf::from);
+        let mut out = Vec::new();
+        stream_results(inputs, &mut out, |path| {
+            if path.ends_with("missing.txt") {
+                return Err(format!("{}: no such file", path.display()));
+            }
+            Ok(crate::parse_one("Phys. Rev. D 72, 052002 (2005)").into())
+        })
+        .unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["file"], "a.txt");
+        assert_eq!(lines[0]["references"][0]["journal_volume"], "72");
+        assert_eq!(lines[1]["error"], "missing.txt: no such file");
+        assert!(lines[1].get("references").is_none());
+        assert!(lines[0].get("marker_issues").is_none());
+        assert_eq!(lines[2]["file"], "b.txt");
+    }
+
+    #[test]
+    fn raw_references_go_through_the_cli_pipeline() {
+        let raw = || {
+            crate::text_raw_references(&[
+                "[1] A. Author, Phys. Rev. D 72, 052002 (2005), Phys. Lett. B 716, 1 (2012)".to_string(),
+                "[3] B. Author, doi:10.1103/PhysRevLett.19.1264".to_string(),
+            ])
+        };
+        let opts = BatchOptions::default();
+        let all = references_from_raw(raw(), &HashMap::new(), 0, &opts, None);
+        assert_eq!(all.references.len(), 3);
+        assert_eq!(all.marker_issues, [SequenceIssue::Missing { section: 0, from: 2, to: 2 }]);
+        assert_eq!(all.stats.marker_issues, all.marker_issues);
+
+        let refs = RefOptions { no_subrefs: true, only_with_doi: true, ..RefOptions::default() };
+        let opts = BatchOptions { refs, ..BatchOptions::default() };
+        let linked = references_from_raw(raw(), &HashMap::new(), 0, &opts, None);
+        let markers: Vec<_> = linked.references.iter().map(|r| r.linemarker.as_deref()).collect();
+        assert_eq!(markers, [Some("3")]);
+        assert_eq!(linked.stats.references, 2);
+    }
+
+    #[test]
+    fn blocks_from_another_extractor() {
+        let word = |text: &str, x: f32, y: f32, font_size: f32| Word {
+            text: text.into(),
+            x,
+            y,
+            width: 5.0 * text.len() as f32,
+            height: font_size,
+            font_size,
+            is_superscript: false,
+            is_italic: false,
+            font_name: if text == "References" { "Times-Bold" } else { "Times-Roman" }.into(),
+        };
+        let block = |lines: &[&str], y: f32, font_size: f32| {
+            let lines: Vec<Line> = lines
+                .iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    let line_y = y - i as f32 * (font_size + 2.0);
+                    let mut x = 72.0;
+                    let words: Vec<Word> = text
+                        .split(' ')
+                        .map(|w| {
+                            let word = word(w, x, line_y, font_size);
+                            x += word.width + 3.0;
+                            word
+                        })
+                        .collect();
+                    Line { words, y: line_y, x_start: 72.0, x_end: x - 3.0, font_size, font_name: "Times-Roman".into() }
+                })
+                .collect();
+            let width = lines.iter().map(|l| l.x_end - 72.0).fold(0.0, f32::max);
+            let height = y - lines.last().unwrap().y + font_size;
+            Block { lines, x: 72.0, y, width, height, font_size }
+        };
+        let body = "We measure the mass of the boson and compare it with the prediction of the model.";
+        let pages = vec![
+            vec![block(&[body; 12], 700.0, 10.0)],
+            vec![
+                block(&[body; 4], 700.0, 10.0),
+                block(&["References"], 600.0, 10.0),
+                block(&["[1] A. Author, Phys. Rev. D 72, 052002 (2005)."], 580.0, 9.0),
+                block(&["[2] B. Author, Phys. Lett. B 716, 1 (2012)."], 560.0, 9.0),
+                block(&["[3] C. Author, Nucl. Phys. B 360, 145 (1991)."], 540.0, 9.0),
+                block(&["2"], 40.0, 10.0),
+            ],
+        ];
+        let extraction = references_from_blocks(pages, 792.0, &BatchOptions::default());
+        assert!(extraction.marker_issues.is_empty());
+        let found: Vec<_> = extraction
+            .references
+            .iter()
+            .map(|r| (r.linemarker.as_deref(), r.journal_volume.as_deref(), r.found_on_page))
+            .collect();
+        assert_eq!(found, [(Some("1"), Some("72"), 2), (Some("2"), Some("716"), 2), (Some("3"), Some("360"), 2)]);
+    }
+}
@@ -0,0 +1,182 @@
+//! LaTeX `.bbl` input: references already split by `\bibitem`.
+
+use crate::types::{RawReference, ReferenceSource};
+
+/// Split a `.bbl` file into one reference per `\bibitem[label]{key}`.
+/// The marker is the bracket label when there is one, else the key.
+/// Text before the first `\bibitem` and after `\end{thebibliography}` is
+/// ignored; LaTeX markup is stripped with `strip_latex`.
+pub fn raw_references(bbl: &str) -> Vec<RawReference> {
+    let body = bbl.split("\\end{thebibliography}").next().unwrap_or(bbl);
+    let mut pieces = body.split("\\bibitem");
+    pieces.next(); // preamble
+    pieces
+        .filter_map(|item| {
+            let (label, rest) = take_delimited(item.trim_start(), '[', ']');
+            let (key, text) = take_delimited(rest.trim_start(), '{', '}');
+            let text = crate::tokenizer::normalize_dashes(&strip_latex(text));
+            if text.is_empty() {
+                return None;
+            }
+            let label = label.map(strip_latex).filter(|l| !l.is_empty());
+            let linemarker = label.or_else(|| key.map(|k| k.trim().to_string()));
+            Some(RawReference {
+                text,
+                linemarker,
+                from_marker: None,
+                source: ReferenceSource::ReferenceSection,
+                page_num: 0,
+                section: 0,
+                italic_runs: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Split off a leading `open ... close` group, honoring nested braces.
+/// Returns the group's contents (None if `text` doesn't start with `open`)
+/// and the text after it.
+fn take_delimited(text: &str, open: char, close: char) -> (Option<&str>, &str) {
+    if !text.starts_with(open) {
+        return (None, text);
+    }
+    let (mut depth, mut braces) = (0, 0);
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if c == open && braces == 0 => depth += 1,
+            _ if c == close && braces == 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return (Some(&text[1..i]), &text[i + 1..]);
+                }
+            }
+            '{' => braces += 1,
+            '}' => braces -= 1,
+            _ => {}
+        }
+    }
+    (None, text)
+}
+
+/// Reduce LaTeX markup to plain text: `\emph{A}`, `\textit{A}` and other
+/// formatting commands keep their argument, `~` is a space, `\&` is `&`,
+/// braces, `$` and comments are dropped. Accents are dropped too, leaving
+/// the base letter (`Schr\"odinger` -> `Schrodinger`). `\href{url}{text}`
+/// keeps the text and `\doi{x}` becomes `doi:x`.
+pub fn strip_latex(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let name: String = chars[i + 1..].iter().take_while(|c| c.is_ascii_alphabetic()).collect();
+                i += 1 + name.len();
+                if name.is_empty() {
+                    match chars.get(i) {
+                        Some(&c @ ('&' | '%' | '_' | '$' | '#' | '{' | '}')) => out.push(c),
+                        Some(' ' | '\\' | ',' | ';') => out.push(' '),
+                        _ => {} // accent: the letter that follows is kept
+                    }
+                    i += 1;
+                    continue;
+                }
+                // Spaces after a control word are not output in LaTeX.
+                while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+                    i += 1;
+                }
+                match name.as_str() {
+                    "href" | "BibitemShut" | "bibinfo" | "bibfield" => i = skip_group(&chars, i),
+                    "doi" => out.push_str("doi:"),
+                    "penalty" => {
+                        while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                            i += 1;
+                        }
+                    }
+                    "newblock" | "BibitemOpen" | "quad" | "qquad" => out.push(' '),
+                    _ => {}
+                }
+            }
+            '%' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            '~' => {
+                out.push(' ');
+                i += 1;
+            }
+            '{' | '}' | '$' => i += 1,
+            c => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Index just past the braced group starting at `i`, or `i` if there is none.
+fn skip_group(chars: &[char], i: usize) -> usize {
+    if chars.get(i) != Some(&'{') {
+        return i;
+    }
+    let mut depth = 0;
+    for (j, &c) in chars.iter().enumerate().skip(i) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return j + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    chars.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BBL: &str = r"\begin{thebibliography}{10}
+\providecommand{\url}[1]{\texttt{#1}}
+
+\bibitem{Weinberg:1967tq}
+S.~Weinberg, \emph{A Model of Leptons}, Phys. Rev. Lett. \textbf{19}, 1264
+  (1967).
+
+\bibitem[{Aad et~al.(2012)}]{Aad:2012tfa}
+G.~Aad \textit{et~al.} (ATLAS), Phys. Lett. B \textbf{716}, 1 (2012),
+  \href{https://arxiv.org/abs/1207.7214}{arXiv:1207.7214} % Higgs \& co.
+
+\end{thebibliography}
+";
+
+    #[test]
+    fn bibitems_become_references() {
+        let refs = raw_references(BBL);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].linemarker.as_deref(), Some("Weinberg:1967tq"));
+        assert_eq!(refs[0].text, "S. Weinberg, A Model of Leptons, Phys. Rev. Lett. 19, 1264 (1967).");
+        assert_eq!(refs[1].linemarker.as_deref(), Some("Aad et al.(2012)"));
+        assert_eq!(refs[1].text, "G. Aad et al. (ATLAS), Phys. Lett. B 716, 1 (2012), arXiv:1207.7214");
+
+        let parsed = crate::parse_all_references(&refs, &Default::default());
+        assert_eq!(parsed[0].journal_title.as_deref(), Some("Phys. Rev. Lett."));
+        assert_eq!(parsed[0].journal_volume.as_deref(), Some("19"));
+        assert_eq!(parsed[1].journal_page.as_deref(), Some("1"));
+        assert_eq!(parsed[1].arxiv_id.as_deref(), Some("1207.7214"));
+    }
+
+    #[test]
+    fn latex_markup_is_stripped() {
+        assert_eq!(strip_latex(r#"Schr\"odinger \& Dirac"#), "Schrodinger & Dirac");
+        assert_eq!(strip_latex(r"\newblock {\em Nucl. Phys.} {\bf B417}"), "Nucl. Phys. B417");
+        assert_eq!(strip_latex(r"\doi{10.1103/PhysRevD.72.052002}"), "doi:10.1103/PhysRevD.72.052002");
+        assert_eq!(strip_latex(r"95\%\penalty0 CL, $\sqrt{s}=13$~TeV"), "95% CL, s=13 TeV");
+    }
+}
@@ -0,0 +1,221 @@
+//! In-text citations: the body pages that cite each reference entry, read
+//! from bracketed markers ("[12]", "[3, 7]", "[12–14]") and superscript
+//! numbers in the body text (`--cited-on-pages`).
+
+use std::collections::{BTreeSet, HashMap};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::types::{ParsedReference, RawReference, ReferenceSource, Word, ZoneKind, ZonedBlock};
+use crate::zones;
+
+/// "[12]", "[3, 7]", "[12–14, 20]".
+static BRACKET_CITATION_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[(\d{1,4}(?:\s*[,–-]\s*\d{1,4})*)\]").unwrap());
+
+/// A range wider than this ("[10-500]") is not a citation.
+const MAX_RANGE: usize = 100;
+
+/// Pages citing each marker, in ascending order. Only body text before the
+/// bibliography is read, so its own "[1]" entry markers don't count: it
+/// starts at the first reference heading, or at the first entry `refs`
+/// collected there when no heading was found. `ref_headings` are extra
+/// heading titles, as in `CollectOptions`.
+pub fn cited_pages(
+    zoned_pages: &[Vec<ZonedBlock>],
+    ref_headings: &[String],
+    refs: &[RawReference],
+) -> HashMap<String, Vec<usize>> {
+    let headings: Vec<String> = ref_headings.iter().map(|h| zones::normalize_heading(h)).collect();
+    let start = bibliography_start(refs);
+    let mut pages: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    'pages: for zb in zoned_pages.iter().flatten() {
+        if zb.zone != ZoneKind::Body {
+            continue;
+        }
+        for line in &zb.block.lines {
+            if zones::is_reference_heading_line(&line.text(), &headings)
+                || zones::is_bold_heading_line(line, &headings)
+            {
+                break 'pages;
+            }
+            if let Some((page, opening)) = &start
+                && (zb.page_num > *page || (zb.page_num == *page && line.text().contains(opening.as_str())))
+            {
+                break 'pages;
+            }
+            let mut markers = Vec::new();
+            for caps in BRACKET_CITATION_RE.captures_iter(&line.text()) {
+                markers.extend(expand_markers(&caps[1]));
+            }
+            for (i, word) in line.words.iter().enumerate() {
+                if !word.is_superscript || !is_citation_superscript(&line.words, i) {
+                    continue;
+                }
+                let numbers = word.text.trim_matches(|c: char| "()[],.;:".contains(c));
+                if numbers.chars().all(|c| c.is_ascii_digit() || ",–-".contains(c)) {
+                    markers.extend(expand_markers(numbers));
+                }
+            }
+            for marker in markers {
+                pages.entry(marker.to_string()).or_default().insert(zb.page_num);
+            }
+        }
+    }
+    pages.into_iter().map(|(marker, pages)| (marker, pages.into_iter().collect())).collect()
+}
+
+/// Page and opening words of the first bibliography entry collected.
+fn bibliography_start(refs: &[RawReference]) -> Option<(usize, String)> {
+    let first = refs.iter().find(|r| r.source == ReferenceSource::ReferenceSection)?;
+    let opening = first.text.split_whitespace().take(2).collect::<Vec<_>>().join(" ");
+    (!opening.is_empty()).then_some((first.page_num, opening))
+}
+
+/// Superscript `words[i]` is a citation when it sits right after a word
+/// or closing punctuation ("decays⁴", "model,¹²"). An exponent ("10¹²")
+/// or the unit of a quantity ("5 cm²") is not.
+fn is_citation_superscript(words: &[Word], i: usize) -> bool {
+    let Some(prev) = i.checked_sub(1).map(|p| &words[p]) else {
+        return false;
+    };
+    let word = &words[i];
+    let touching = word.x - (prev.x + prev.width) < word.font_size * 0.5;
+    let after_word = prev.text.ends_with(|c: char| c.is_alphabetic() || ")].,;:'\"”’".contains(c));
+    let has_digit = |w: &Word| w.text.contains(|c: char| c.is_ascii_digit());
+    let unit_of_quantity = i >= 2 && has_digit(&words[i - 2]);
+    touching && after_word && !has_digit(prev) && !unit_of_quantity
+}
+
+/// The numbers in "3, 7, 12–14": 3, 7, 12, 13, 14. An open-ended range
+/// ("12-") gives nothing.
+fn expand_markers(list: &str) -> Vec<usize> {
+    let mut numbers = Vec::new();
+    for item in list.split(',') {
+        let bounds: Option<Vec<usize>> = item.split(['–', '-']).map(|n| n.trim().parse().ok()).collect();
+        match bounds.as_deref() {
+            Some(&[n]) => numbers.push(n),
+            Some(&[start, end]) if start <= end && end - start <= MAX_RANGE => numbers.extend(start..=end),
+            _ => {}
+        }
+    }
+    numbers
+}
+
+/// Fill `cited_on_pages` from `cited_pages`, matching on `linemarker`.
+/// Only the first reference section is matched, since markers restart in
+/// each one.
+pub fn attach_cited_pages(refs: &mut [ParsedReference], pages: &HashMap<String, Vec<usize>>) {
+    for r in refs.iter_mut().filter(|r| r.section == 0) {
+        if let Some(pages) = r.linemarker.as_ref().and_then(|m| pages.get(m)) {
+            r.cited_on_pages = pages.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Block, Line};
+
+    /// Words left to right, a space apart; superscripts touch the word
+    /// before them.
+    fn line(words: &[(&str, bool)]) -> Line {
+        let mut x = 0.0;
+        let words = words
+            .iter()
+            .map(|&(text, is_superscript)| {
+                if !is_superscript && x > 0.0 {
+                    x += 3.0;
+                }
+                let width = 5.0 * text.chars().count() as f32;
+                let word = Word {
+                    text: text.into(),
+                    x,
+                    y: 0.0,
+                    width,
+                    height: 10.0,
+                    font_size: if is_superscript { 7.0 } else { 10.0 },
+                    is_superscript,
+                    is_italic: false,
+                    font_name: "Times-Roman".into(),
+                };
+                x += width;
+                word
+            })
+            .collect();
+        Line { words, y: 100.0, x_start: 0.0, x_end: 300.0, font_size: 10.0, font_name: "Times-Roman".into() }
+    }
+
+    fn body(page_num: usize, lines: Vec<Line>) -> ZonedBlock {
+        let block = Block { lines, x: 0.0, y: 100.0, width: 300.0, height: 12.0, font_size: 10.0 };
+        ZonedBlock { block, zone: ZoneKind::Body, page_num }
+    }
+
+    #[test]
+    fn body_citations_are_mapped_to_pages() {
+        let plain = |text: &str| line(&text.split(' ').map(|w| (w, false)).collect::<Vec<_>>());
+        let zoned = vec![
+            vec![body(1, vec![plain("as shown in [1, 3–5] and [2].")])],
+            vec![
+                body(2, vec![plain("see also [2] and [10-500]"), line(&[("decays", false), ("4,6", true)])]),
+                body(2, vec![plain("References")]),
+                body(2, vec![plain("[1] A. Author, Phys. Rev. D 72, 052002 (2005)")]),
+            ],
+            vec![body(3, vec![plain("[7] B. Author, Phys. Lett. B 716, 1 (2012)")])],
+        ];
+        let pages = cited_pages(&zoned, &[], &[]);
+        assert_eq!(pages["1"], [1]);
+        assert_eq!(pages["2"], [1, 2]);
+        assert_eq!(pages["4"], [1, 2]);
+        assert_eq!(pages["6"], [2]);
+        assert!(!pages.contains_key("7"));
+        assert!(!pages.contains_key("300"));
+
+        let mut refs = crate::parse_reference_text(&["[2] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string()]);
+        attach_cited_pages(&mut refs, &pages);
+        assert_eq!(refs[0].cited_on_pages, [1, 2]);
+    }
+
+    #[test]
+    fn exponents_units_and_open_ranges_are_no_citations() {
+        let zoned = vec![vec![body(
+            1,
+            vec![
+                line(&[("a", false), ("rate", false), ("of", false), ("10", false), ("12", true)]),
+                line(&[("an", false), ("area", false), ("of", false), ("5", false), ("cm", false), ("2", true)]),
+                line(&[("decays", false), ("12-", true), ("and", false), ("model,", false), ("3", true)]),
+            ],
+        )]];
+        let pages = cited_pages(&zoned, &[], &[]);
+        assert_eq!(pages.keys().collect::<Vec<_>>(), ["3"]);
+        assert!(expand_markers("12-").is_empty());
+        assert_eq!(expand_markers("3, 12-, 5-6"), [3, 5, 6]);
+    }
+
+    #[test]
+    fn bibliography_without_a_heading_is_not_read() {
+        let plain = |text: &str| line(&text.split(' ').map(|w| (w, false)).collect::<Vec<_>>());
+        let zoned = vec![
+            vec![
+                body(2, vec![plain("as shown in [1].")]),
+                body(2, vec![plain("[1] A. Author, Phys. Rev. D 72, 052002 (2005), see [2]")]),
+            ],
+            vec![body(3, vec![plain("[2] B. Author, Phys. Lett. B 716, 1 (2012), and [1]")])],
+        ];
+        let refs = [RawReference {
+            text: "A. Author, Phys. Rev. D 72, 052002 (2005), see [2]".into(),
+            linemarker: Some("1".into()),
+            from_marker: None,
+            source: ReferenceSource::ReferenceSection,
+            page_num: 2,
+            section: 0,
+            italic_runs: Vec::new(),
+        }];
+        let pages = cited_pages(&zoned, &[], &refs);
+        assert_eq!(pages.keys().collect::<Vec<_>>(), ["1"]);
+        assert_eq!(pages["1"], [2]);
+        assert_eq!(cited_pages(&zoned, &[], &[]).len(), 2);
+    }
+}
@@ -2,31 +2,59 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::markers::{
-    collect_refs_by_markers, count_markers_in_block, count_markers_in_text, has_any_marker,
-    has_citation_content, score_citation_block, split_into_references,
+    BlockFeatures, collect_refs_by_markers, count_markers_in_text, page_features,
+    split_into_references, with_superscript_line_markers,
 };
-use crate::types::{RawReference, ReferenceSource, ZoneKind, ZonedBlock};
-use crate::zones;
+use crate::types::{RawReference, ReferenceSource, TokenKind, ZoneKind, ZonedBlock};
+use crate::{tokenizer, zones};
+
+/// Collection switches set from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+    /// Reference section titles to accept besides the built-in ones, e.g.
+    /// "Works Cited" (`--ref-heading`). Followed by citations like any
+    /// other heading, or it doesn't count.
+    pub ref_headings: Vec<String>,
+}
 
 /// Collect all references from zoned blocks across all pages.
 pub fn collect_references(zoned_pages: &[Vec<ZonedBlock>]) -> Vec<RawReference> {
-    let mut refs = collect_reference_section(zoned_pages);
+    collect_references_with(zoned_pages, &CollectOptions::default())
+}
+
+/// `collect_references` with non-default options.
+pub fn collect_references_with(zoned_pages: &[Vec<ZonedBlock>], opts: &CollectOptions) -> Vec<RawReference> {
+    let headings: Vec<String> = opts.ref_headings.iter().map(|h| zones::normalize_heading(h)).collect();
+    let marked = with_superscript_line_markers(zoned_pages);
+    let zoned_pages = marked.as_deref().unwrap_or(zoned_pages);
+    let features = page_features(zoned_pages);
+    let mut refs = collect_reference_section(zoned_pages, &features, &headings);
     let footnote_refs = collect_footnote_refs(zoned_pages);
     dedup_and_merge(&mut refs, footnote_refs);
     refs
 }
 
 /// Find the reference section and extract individual references.
+/// `features` are the `page_features` of `zoned_pages`; `extra_headings`
+/// are accepted heading titles, normalized.
 fn collect_reference_section(
     zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
+    extra_headings: &[String],
 ) -> Vec<RawReference> {
-    let headings = find_all_reference_headings(zoned_pages);
+    let headings = find_all_reference_headings(zoned_pages, features, extra_headings);
     if !headings.is_empty() {
-        let mut all_blocks = Vec::new();
-        for loc in &headings {
-            all_blocks.extend(gather_ref_blocks(zoned_pages, loc));
+        // Split each section on its own: numbering restarts after every
+        // heading, and an entry never continues into the next section.
+        let mut heading_refs = Vec::new();
+        for (section, loc) in headings.iter().enumerate() {
+            let blocks = gather_ref_blocks(zoned_pages, features, loc, extra_headings);
+            let mut refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+            for r in &mut refs {
+                r.section = section;
+            }
+            heading_refs.extend(refs);
         }
-        let heading_refs = split_into_references(&all_blocks, ReferenceSource::ReferenceSection);
         // If heading-based collection yielded few refs, the heading may be
         // a false positive (e.g., TOC entry "References" on page 4 of a 97-page
         // paper). Try the fallback marker scan and use whichever found more.
@@ -50,13 +78,17 @@ struct RefHeadingLoc {
     line_idx: Option<usize>,
 }
 
-fn find_all_reference_headings(zoned_pages: &[Vec<ZonedBlock>]) -> Vec<RefHeadingLoc> {
+fn find_all_reference_headings(
+    zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
+    extra: &[String],
+) -> Vec<RefHeadingLoc> {
     let mut headings = Vec::new();
     // First try: standalone heading blocks, verified by following reference markers.
     for (page_idx, page_blocks) in zoned_pages.iter().enumerate() {
         for (block_idx, zb) in page_blocks.iter().enumerate() {
-            if zones::is_reference_heading(&zb.block)
-                && has_refs_after(zoned_pages, page_idx, block_idx)
+            if zones::is_reference_heading(&zb.block, extra)
+                && has_refs_after(zoned_pages, features, page_idx, block_idx)
             {
                 headings.push(RefHeadingLoc {
                     page_idx,
@@ -73,8 +105,8 @@ fn find_all_reference_headings(zoned_pages: &[Vec<ZonedBlock>]) -> Vec<RefHeadin
     for (page_idx, page_blocks) in zoned_pages.iter().enumerate() {
         for (block_idx, zb) in page_blocks.iter().enumerate() {
             for (line_idx, line) in zb.block.lines.iter().enumerate() {
-                if zones::is_reference_heading_line(&line.text())
-                    && has_refs_after(zoned_pages, page_idx, block_idx)
+                if (zones::is_reference_heading_line(&line.text(), extra) || zones::is_bold_heading_line(line, extra))
+                    && has_refs_after(zoned_pages, features, page_idx, block_idx)
                 {
                     headings.push(RefHeadingLoc {
                         page_idx,
@@ -91,17 +123,19 @@ fn find_all_reference_headings(zoned_pages: &[Vec<ZonedBlock>]) -> Vec<RefHeadin
 /// Verify a heading by checking if blocks after it contain citation-like content.
 fn has_refs_after(
     zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
     page_idx: usize,
     block_idx: usize,
 ) -> bool {
     let mut checked = 0;
     let mut citation_score = 0;
     // Check remaining blocks on the heading page.
-    for zb in &zoned_pages[page_idx][block_idx + 1..] {
+    let rest = zoned_pages[page_idx].iter().zip(&features[page_idx]).skip(block_idx + 1);
+    for (zb, f) in rest {
         if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
             continue;
         }
-        citation_score += score_citation_block(&zb.block);
+        citation_score += f.citation_score;
         if citation_score >= 4 {
             return true;
         }
@@ -113,13 +147,13 @@ fn has_refs_after(
     // Check up to 3 subsequent pages (handles appendix pages between
     // heading and continuation of references).
     let end = (page_idx + 4).min(zoned_pages.len());
-    for next_page in &zoned_pages[page_idx + 1..end] {
+    for (next_page, page_features) in zoned_pages[page_idx + 1..end].iter().zip(&features[page_idx + 1..end]) {
         let mut page_checked = 0;
-        for zb in next_page {
+        for (zb, f) in next_page.iter().zip(page_features) {
             if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
                 continue;
             }
-            citation_score += score_citation_block(&zb.block);
+            citation_score += f.citation_score;
             if citation_score >= 4 {
                 return true;
             }
@@ -134,7 +168,9 @@ fn has_refs_after(
 
 fn gather_ref_blocks(
     zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
     loc: &RefHeadingLoc,
+    extra_headings: &[String],
 ) -> Vec<(String, usize)> {
     let mut ref_blocks = Vec::new();
 
@@ -150,13 +186,19 @@ fn gather_ref_blocks(
     };
 
     for zb in &zoned_pages[loc.page_idx][first_full_block..] {
-        if zb.zone != ZoneKind::Header && zb.zone != ZoneKind::PageNumber {
-            ref_blocks.push((zb.block.text(), zb.page_num));
+        if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
+            continue;
         }
+        // A second section starting on the same page is gathered from its
+        // own heading.
+        if is_standalone_ref_heading(&zb.block, extra_headings) {
+            return ref_blocks;
+        }
+        ref_blocks.push((zb.block.text(), zb.page_num));
     }
 
-    let has_markers = detect_marker_format(&ref_blocks, zoned_pages, loc.page_idx);
-    gather_subsequent_pages(zoned_pages, loc.page_idx, &mut ref_blocks, has_markers);
+    let has_markers = detect_marker_format(&ref_blocks, zoned_pages, features, loc.page_idx);
+    gather_subsequent_pages(zoned_pages, features, loc.page_idx, &mut ref_blocks, has_markers, extra_headings);
     ref_blocks
 }
 
@@ -164,22 +206,18 @@ fn gather_ref_blocks(
 fn detect_marker_format(
     ref_blocks: &[(String, usize)],
     zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
     heading_page: usize,
 ) -> bool {
     if ref_blocks.iter().any(|(text, _)| count_markers_in_text(text) > 0) {
         return true;
     }
-    if heading_page + 1 < zoned_pages.len() {
-        for zb in &zoned_pages[heading_page + 1] {
-            if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
-                continue;
-            }
-            if count_markers_in_block(&zb.block) > 0 {
-                return true;
-            }
-        }
-    }
-    false
+    let next_page = heading_page + 1;
+    next_page < zoned_pages.len()
+        && zoned_pages[next_page]
+            .iter()
+            .zip(&features[next_page])
+            .any(|(zb, f)| zb.zone != ZoneKind::Header && zb.zone != ZoneKind::PageNumber && f.marker_lines > 0)
 }
 
 fn collect_lines_after(zb: &ZonedBlock, heading_line_idx: usize) -> String {
@@ -192,39 +230,37 @@ fn collect_lines_after(zb: &ZonedBlock, heading_line_idx: usize) -> String {
 
 fn gather_subsequent_pages(
     zoned_pages: &[Vec<ZonedBlock>],
+    features: &[Vec<BlockFeatures>],
     start_page: usize,
     ref_blocks: &mut Vec<(String, usize)>,
     use_markers: bool,
+    extra_headings: &[String],
 ) {
     let mut pages_without_refs = 0;
-    for page_blocks in &zoned_pages[start_page + 1..] {
+    for (page_blocks, page_features) in zoned_pages.iter().zip(features).skip(start_page + 1) {
         let mut page_has_refs = false;
         let mut page_blocks_buf = Vec::new();
         let mut page_citation_lines = 0;
         let mut page_total_lines = 0;
-        let mut saw_heading = false;
-        for zb in page_blocks {
+        let mut heading_at = None;
+        for (zb, f) in page_blocks.iter().zip(page_features) {
             if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
                 continue;
             }
-            if is_standalone_ref_heading(&zb.block) {
+            if is_standalone_ref_heading(&zb.block, extra_headings) {
                 // Don't stop immediately — the heading might be a running
                 // header (e.g., "References" at top of an appendix page).
                 // Only stop if the page also has reference content.
-                saw_heading = true;
+                heading_at.get_or_insert(page_blocks_buf.len());
                 continue;
             }
             if use_markers {
-                if has_any_marker(&zb.block) {
+                if f.marker_lines > 0 {
                     page_has_refs = true;
                 }
             } else {
-                for line in &zb.block.lines {
-                    page_total_lines += 1;
-                    if has_citation_content(&line.text()) {
-                        page_citation_lines += 1;
-                    }
-                }
+                page_total_lines += zb.block.lines.len();
+                page_citation_lines += f.citation_lines;
             }
             page_blocks_buf.push((zb.block.text(), zb.page_num));
         }
@@ -235,9 +271,11 @@ fn gather_subsequent_pages(
             page_has_refs = true;
         }
         // A standalone heading on a page WITH ref content means a real
-        // second ref section — stop before it (multi-chapter documents).
+        // second ref section — stop before it (multi-chapter documents);
+        // that section is gathered from its own heading.
         // A heading on a page WITHOUT ref content is a running header — skip it.
-        if saw_heading && page_has_refs {
+        if let Some(at) = heading_at && page_has_refs {
+            page_blocks_buf.truncate(at);
             ref_blocks.extend(page_blocks_buf);
             return;
         }
@@ -255,8 +293,8 @@ fn gather_subsequent_pages(
 }
 
 /// A standalone reference heading (short block, not heading + content).
-fn is_standalone_ref_heading(block: &crate::types::Block) -> bool {
-    zones::is_reference_heading(block) && block.lines.len() <= 2
+fn is_standalone_ref_heading(block: &crate::types::Block, extra_headings: &[String]) -> bool {
+    zones::is_reference_heading(block, extra_headings) && block.lines.len() <= 2
 }
 
 /// Collect references from footnote zones.
@@ -268,7 +306,7 @@ fn collect_footnote_refs(
         let footnote_blocks: Vec<(String, usize)> = page_blocks
             .iter()
             .filter(|zb| zb.zone == ZoneKind::Footnote)
-            .map(|zb| (zb.block.text(), zb.page_num))
+            .map(|zb| (strip_footnote_symbol(&zb.block.text()), zb.page_num))
             .collect();
         if !footnote_blocks.is_empty() {
             let page_refs =
@@ -279,6 +317,69 @@ fn collect_footnote_refs(
     refs
 }
 
+/// Drop footnote references that have neither an identifier nor a journal
+/// name (`--exclude-footnotes-without-markers`). `collect_footnote_refs`
+/// accepts a bare year, which lets remarks like "see the text (2005)" through.
+pub fn drop_weak_footnotes(refs: &mut Vec<RawReference>) {
+    refs.retain(|r| r.source != ReferenceSource::Footnote || has_identifier_or_journal(&r.text));
+}
+
+fn has_identifier_or_journal(text: &str) -> bool {
+    tokenizer::tokenize(text).iter().any(|t| {
+        matches!(t.kind, TokenKind::Doi | TokenKind::ArxivId | TokenKind::Hdl | TokenKind::JournalName)
+    })
+}
+
+/// "† See A. Author, ..." -> "See A. Author, ...". Numeric markers are left
+/// for the regular line-marker handling.
+fn strip_footnote_symbol(text: &str) -> String {
+    text.trim_start().trim_start_matches(zones::FOOTNOTE_SYMBOLS).trim_start().to_string()
+}
+
+/// Fill `italic_runs` from the italic words of the blocks on the reference's
+/// page and the next one (references continue across page breaks). Runs are
+/// matched ignoring spacing, punctuation and case, because line joining and
+/// de-hyphenation change those, and stored as the covered slice of the
+/// reference text.
+pub fn attach_italic_runs(refs: &mut [RawReference], zoned_pages: &[Vec<ZonedBlock>]) {
+    let runs: Vec<(usize, String)> = zoned_pages
+        .iter()
+        .flatten()
+        .filter(|zb| zb.zone != ZoneKind::Header && zb.zone != ZoneKind::PageNumber)
+        .flat_map(|zb| zb.block.italic_runs().into_iter().map(|run| (zb.page_num, run)))
+        .collect();
+    for r in refs {
+        for (page, run) in &runs {
+            if *page != r.page_num && *page != r.page_num + 1 {
+                continue;
+            }
+            if let Some(found) = find_loosely(&r.text, run)
+                && !r.italic_runs.iter().any(|existing| existing == found)
+            {
+                r.italic_runs.push(found.to_string());
+            }
+        }
+    }
+}
+
+/// Find `needle` in `haystack` comparing alphanumeric characters only,
+/// case-insensitively. Returns the slice from the first to the last
+/// matched character.
+fn find_loosely<'a>(haystack: &'a str, needle: &str) -> Option<&'a str> {
+    let key: Vec<char> = needle.chars().filter(|c| c.is_alphanumeric()).collect();
+    let hay: Vec<(usize, char)> = haystack.char_indices().filter(|(_, c)| c.is_alphanumeric()).collect();
+    if key.is_empty() || key.len() > hay.len() {
+        return None;
+    }
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+    let start = hay
+        .windows(key.len())
+        .position(|w| w.iter().zip(&key).all(|(&(_, a), &b)| same(a, b)))?;
+    let (from, _) = hay[start];
+    let (last, ch) = hay[start + key.len() - 1];
+    Some(&haystack[from..last + ch.len_utf8()])
+}
+
 fn is_citation_like(r: &RawReference) -> bool {
     let t = &r.text;
     has_year_pattern(t) || t.contains("arXiv") || t.contains("doi") || t.contains("DOI")
@@ -310,9 +411,261 @@ fn refs_overlap(a: &str, b: &str) -> bool {
     a_norm == b_norm
 }
 
-fn normalize_for_dedup(text: &str) -> String {
+/// Lowercased alphanumerics only, so punctuation and spacing differences
+/// don't hide duplicates.
+pub(crate) fn normalize_for_dedup(text: &str) -> String {
     text.chars()
         .filter(|c| c.is_alphanumeric())
         .flat_map(|c| c.to_lowercase())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Block, Line, Word};
+
+    fn word(text: &str, is_italic: bool) -> Word {
+        Word {
+            text: text.to_string(),
+            x: 0.0,
+            y: 0.0,
+            width: 10.0,
+            height: 10.0,
+            font_size: 10.0,
+            is_superscript: false,
+            is_italic,
+            font_name: if is_italic { "Times-Italic" } else { "Times-Roman" }.into(),
+        }
+    }
+
+    #[test]
+    fn italic_title_between_authors_and_journal() {
+        let words = vec![
+            word("[1]", false),
+            word("S.", false),
+            word("Weinberg,", false),
+            word("A", true),
+            word("Model", true),
+            word("of", true),
+            word("Lep-", true),
+        ];
+        let first = Line { words, y: 100.0, x_start: 0.0, x_end: 300.0, font_size: 10.0, font_name: "Times-Roman".into() };
+        let words = vec![
+            word("tons,", true),
+            word("Phys.", true),
+            word("Rev.", true),
+            word("Lett.", true),
+            word("19,", false),
+            word("1264", false),
+            word("(1967)", false),
+        ];
+        let second = Line { words, y: 88.0, ..first.clone() };
+        let block = Block { lines: vec![first, second], x: 0.0, y: 100.0, width: 300.0, height: 22.0, font_size: 10.0 };
+        let zoned = vec![vec![ZonedBlock { block, zone: ZoneKind::Body, page_num: 3 }]];
+
+        let mut refs = vec![RawReference {
+            text: "S. Weinberg, A Model of Leptons, Phys. Rev. Lett. 19, 1264 (1967)".into(),
+            linemarker: Some("1".into()),
+            from_marker: None,
+            source: ReferenceSource::ReferenceSection,
+            page_num: 3,
+            section: 0,
+            italic_runs: Vec::new(),
+        }];
+        attach_italic_runs(&mut refs, &zoned);
+        assert_eq!(refs[0].italic_runs, ["A Model of Leptons, Phys. Rev. Lett"]);
+
+        let tokens = crate::tokenizer::tokenize(&refs[0].text);
+        let parsed = crate::parse::parse_references(&refs[0], &tokens).remove(0);
+        assert_eq!(parsed.title.as_deref(), Some("A Model of Leptons"));
+        assert_eq!(parsed.authors.as_deref(), Some("S. Weinberg"));
+        assert_eq!(parsed.journal_title.as_deref(), Some("Phys. Rev. Lett."));
+
+        // Without italics the title words run into the author list
+        refs[0].italic_runs.clear();
+        let parsed = crate::parse::parse_references(&refs[0], &tokens).remove(0);
+        assert_eq!(parsed.title, None);
+    }
+
+    #[test]
+    fn footnote_with_symbol_marker() {
+        let config = crate::layout::LayoutConfig::default();
+        let body_words: Vec<Word> = "We follow the usual conventions for the coupling constants."
+            .split(' ')
+            .map(|w| word(w, false))
+            .collect();
+        let body_line = Line { words: body_words, y: 600.0, x_start: 72.0, x_end: 500.0, font_size: 10.0, font_name: "Times-Roman".into() };
+        let body = Block { lines: vec![body_line], x: 72.0, y: 600.0, width: 428.0, height: 12.0, font_size: 10.0 };
+        let note_words: Vec<Word> = "* See S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967)."
+            .split(' ')
+            .map(|w| Word { font_size: 8.0, ..word(w, false) })
+            .collect();
+        let note_line = Line { words: note_words, y: 80.0, x_start: 72.0, x_end: 400.0, font_size: 8.0, font_name: "Times-Roman".into() };
+        let note = Block { lines: vec![note_line], x: 72.0, y: 80.0, width: 328.0, height: 10.0, font_size: 8.0 };
+
+        let zoned = vec![zones::classify_page(&[body, note], 1, 792.0, None, 10.0, &config)];
+        assert_eq!(zoned[0][1].zone, ZoneKind::Footnote);
+        let refs = collect_references(&zoned);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].source, ReferenceSource::Footnote);
+        assert_eq!(refs[0].text, "See S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967).");
+    }
+
+    fn text_block(lines: &[&str], y: f32) -> Block {
+        let lines: Vec<Line> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let words = text.split(' ').map(|w| word(w, false)).collect();
+                Line { words, y: y - 12.0 * i as f32, x_start: 72.0, x_end: 500.0, font_size: 10.0, font_name: "Times-Roman".into() }
+            })
+            .collect();
+        let height = 12.0 * lines.len() as f32;
+        Block { lines, x: 72.0, y, width: 428.0, height, font_size: 10.0 }
+    }
+
+    #[test]
+    fn restarted_numbering_in_a_second_reference_section() {
+        let page = |page_num: usize, entries: &[&str]| -> Vec<ZonedBlock> {
+            [text_block(&["References"], 700.0), text_block(entries, 680.0)]
+                .into_iter()
+                .map(|block| ZonedBlock { block, zone: ZoneKind::Body, page_num })
+                .collect()
+        };
+        let zoned = vec![
+            page(1, &[
+                "[1] A. Author, Phys. Rev. D 72, 052002 (2005)",
+                "[2] B. Other, Nucl. Phys. B 417, 181 (1994)",
+                "[3] C. Third, Phys. Lett. B 716, 1 (2012)",
+            ]),
+            page(2, &[
+                "[1] D. Fourth, JHEP 05, 026 (2006); ibid. 07, 012 (2007)",
+                "[2] E. Fifth, ibid. 74, 2 (2007)",
+                "[3] F. Sixth, Eur. Phys. J. C 72, 1 (2012)",
+            ]),
+        ];
+        let refs = collect_references(&zoned);
+        let ids: Vec<(Option<&str>, usize)> = refs.iter().map(|r| (r.linemarker.as_deref(), r.section)).collect();
+        assert_eq!(
+            ids,
+            [(Some("1"), 0), (Some("2"), 0), (Some("3"), 0), (Some("1"), 1), (Some("2"), 1), (Some("3"), 1)]
+        );
+
+        let mut parsed = crate::parse_all_references(&crate::split_semicolon_subrefs(refs), &Default::default());
+        crate::resolve_ibid_journals(&mut parsed);
+        let journals: Vec<Option<&str>> = parsed.iter().map(|r| r.journal_title.as_deref()).collect();
+        // The ibid in section 1's [1] resolves within its own entry; the one in
+        // section 1's [2] must not pick up "Nucl. Phys. B" from section 0's [2].
+        assert_eq!(journals[4], Some("J. High Energy Phys."));
+        assert_eq!(journals[5], Some("ibid"));
+    }
+
+    #[test]
+    fn second_reference_section_on_the_heading_page() {
+        let blocks = [
+            text_block(&["References"], 700.0),
+            text_block(&["[1] A. Author, Phys. Rev. D 72, 052002 (2005)", "[2] B. Other, Nucl. Phys. B 417, 181 (1994)"], 680.0),
+            text_block(&["References"], 400.0),
+            text_block(&["[1] C. Third, Phys. Lett. B 716, 1 (2012)", "[2] D. Fourth, JHEP 05, 026 (2006)"], 380.0),
+        ];
+        let zoned = vec![blocks.into_iter().map(|block| ZonedBlock { block, zone: ZoneKind::Body, page_num: 1 }).collect()];
+        let refs = collect_references(&zoned);
+        let ids: Vec<(Option<&str>, usize)> = refs.iter().map(|r| (r.linemarker.as_deref(), r.section)).collect();
+        assert_eq!(ids, [(Some("1"), 0), (Some("2"), 0), (Some("1"), 1), (Some("2"), 1)]);
+    }
+
+    #[test]
+    fn custom_heading_is_found_only_when_given() {
+        let body = |block, page_num| ZonedBlock { block, zone: ZoneKind::Body, page_num };
+        let zoned = vec![
+            vec![body(text_block(&["We compare with the measurements of Smith (2005) and Jones (2007)."], 700.0), 1)],
+            vec![
+                body(text_block(&["Works Cited"], 700.0), 2),
+                body(
+                    text_block(
+                        &[
+                            "Jones, A. 2007, Nucl. Phys. B 417, 181",
+                            "Smith, J. 2005, Phys. Rev. D 72, 052002",
+                            "Weinberg, S. 1967, Phys. Rev. Lett. 19, 1264",
+                            "Witten, E. 1981, Nucl. Phys. B 188, 513",
+                        ],
+                        680.0,
+                    ),
+                    2,
+                ),
+            ],
+        ];
+        let opts = CollectOptions { ref_headings: vec!["Works cited".into()] };
+        let refs = collect_references_with(&zoned, &opts);
+        assert!(refs[0].text.starts_with("Jones, A. 2007, Nucl. Phys. B 417, 181"), "{refs:?}");
+        assert!(refs.last().unwrap().text.ends_with("Nucl. Phys. B 188, 513"));
+        assert!(refs.iter().all(|r| r.source == ReferenceSource::ReferenceSection));
+        assert!(collect_references(&zoned).is_empty());
+    }
+
+    #[test]
+    fn year_only_footnotes_are_dropped_when_strict() {
+        let note = |text: &str| ZonedBlock { block: text_block(&[text], 80.0), zone: ZoneKind::Footnote, page_num: 4 };
+        let zoned = vec![vec![
+            note("1. For details see the discussion in the text (2005)."),
+            note("2. S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967)."),
+            note("3. Updated in 2021, arXiv:2101.12345."),
+        ]];
+        let mut refs = collect_references(&zoned);
+        assert_eq!(refs.len(), 3);
+        drop_weak_footnotes(&mut refs);
+        let kept: Vec<Option<&str>> = refs.iter().map(|r| r.linemarker.as_deref()).collect();
+        assert_eq!(kept, [Some("2"), Some("3")]);
+    }
+
+    #[test]
+    fn each_line_is_scanned_once_in_a_long_bibliography() {
+        let body = |block, page_num| ZonedBlock { block, zone: ZoneKind::Body, page_num };
+        let entries: Vec<String> =
+            (1..=2000).map(|n| format!("[{n}] A. Author{n}, Phys. Rev. D {n}, 052002 (2005)")).collect();
+        let mut zoned: Vec<Vec<ZonedBlock>> = vec![vec![body(text_block(&["References"], 700.0), 1)]];
+        for (i, page) in entries.chunks(40).enumerate() {
+            let blocks = page.chunks(10).enumerate().map(|(j, lines)| {
+                let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+                body(text_block(&lines, 680.0 - 130.0 * j as f32), i + 1)
+            });
+            if i == 0 {
+                zoned[0].extend(blocks);
+            } else {
+                zoned.push(blocks.collect());
+            }
+        }
+        let lines: usize = zoned.iter().flatten().map(|zb| zb.block.lines.len()).sum();
+
+        crate::markers::FEATURE_LINE_SCANS.with(|n| n.set(0));
+        let refs = collect_references(&zoned);
+        assert_eq!(refs.len(), 2000);
+        assert_eq!(refs[1999].linemarker.as_deref(), Some("2000"));
+        assert_eq!(crate::markers::FEATURE_LINE_SCANS.with(|n| n.get()), lines);
+    }
+
+    #[test]
+    fn reference_wraps_into_the_next_column() {
+        let column = |x: f32, y: f32, lines: &[&str]| {
+            let block = Block { x, width: 230.0, ..text_block(lines, y) };
+            ZonedBlock { block, zone: ZoneKind::Body, page_num: 7 }
+        };
+        let left = column(50.0, 700.0, &[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005).",
+            "[2] B. Author, Nucl. Phys. B 417, 181 (1994).",
+            "[3] C. Author, Phys. Lett. B 716, 1 (2012);",
+            "C. Author, Phys. Rev.",
+        ]);
+        let head = column(320.0, 700.0, &["Lett. 19, 1264 (1967)."]);
+        let right = column(320.0, 688.0, &[
+            "[4] D. Author, JHEP 05, 026 (2006).",
+            "[5] E. Author, Nature 248, 30 (1974).",
+            "[6] F. Author, Phys. Rev. D 7, 2333 (1973).",
+        ]);
+        let refs = collect_references(&[vec![left, head, right]]);
+        let entry: Vec<&str> = refs.iter().filter(|r| r.linemarker.as_deref() == Some("3")).map(|r| r.text.as_str()).collect();
+        assert_eq!(entry.join("; "), "C. Author, Phys. Lett. B 716, 1 (2012); C. Author, Phys. Rev. Lett. 19, 1264 (1967).");
+        assert_eq!(refs.last().unwrap().linemarker.as_deref(), Some("6"));
+    }
+}
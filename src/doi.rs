@@ -1,15 +1,48 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
 use rusqlite::{params, Connection};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::kb;
 use crate::types::ParsedReference;
 
+/// SQLite-backed DOI lookup cache. The connection sits behind a `Mutex` so
+/// concurrent lookup workers can share one cache while HTTP runs in parallel.
+///
+/// Cache keys are part of the on-disk format and must stay stable across
+/// versions so that shared cache files keep working:
+/// - `j:<journal>|v:<volume>|p:<page>` for journal citations
+/// - `arxiv:<id>` for arXiv IDs
+/// - either of the above behind `openalex:` for `Resolver::OpenAlex` answers
+/// - either of the above, or `doi:<doi>`, behind `s2:` for
+///   `Resolver::SemanticScholar` answers
+/// - `inspire:doi:<doi>` and `inspire:arxiv:<id>` for INSPIRE answers
+///   (`--cross-link`)
+/// - `short:<shortDOI>` for doi.org handle answers (`resolve_short_dois`)
+///
+/// Semantic Scholar rows also store the work's `title` (`--enrich-titles`),
+/// INSPIRE rows its `arxiv_id`.
+/// A row with a NULL `doi` is a negative hit (lookup found nothing).
+/// Negative hits expire after `negative_ttl_secs` so lookups that failed
+/// during an outage get retried; positive hits never expire, DOIs are
+/// immutable.
 pub struct DoiCache {
-    conn: Connection,
+    conn: Mutex<Connection>,
+    negative_ttl_secs: i64,
 }
 
+/// Default lifetime of a negative hit.
+pub const DEFAULT_NEGATIVE_TTL_DAYS: u64 = 30;
+
+pub const SECS_PER_DAY: i64 = 86_400;
+
 #[derive(Deserialize)]
 struct CrossRefResponse {
     message: CrossRefMessage,
@@ -26,8 +59,200 @@ struct CrossRefItem {
     doi: String,
 }
 
+#[derive(Deserialize)]
+struct OpenAlexResponse {
+    results: Vec<OpenAlexWork>,
+}
+
+#[derive(Deserialize)]
+struct OpenAlexWork {
+    /// Full `https://doi.org/...` URL, or null for works without a DOI.
+    doi: Option<String>,
+    primary_location: Option<OpenAlexLocation>,
+}
+
+#[derive(Deserialize)]
+struct OpenAlexLocation {
+    source: Option<OpenAlexSource>,
+}
+
+#[derive(Deserialize)]
+struct OpenAlexSource {
+    /// Full journal name, e.g. "Physical Review D".
+    display_name: String,
+}
+
+/// `paper/search` answers wrap papers in `data`; `paper/<id>` answers are
+/// the paper itself.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum S2Response {
+    Search { data: Vec<S2Paper> },
+    Paper(S2Paper),
+}
+
+#[derive(Deserialize)]
+struct S2Paper {
+    #[serde(rename = "externalIds")]
+    external_ids: Option<S2ExternalIds>,
+    title: Option<String>,
+    venue: Option<String>,
+    journal: Option<S2Journal>,
+}
+
+#[derive(Deserialize)]
+struct S2Journal {
+    name: Option<String>,
+    volume: Option<String>,
+    pages: Option<String>,
+}
+
+impl S2Paper {
+    /// Whether the paper is the expected citation: same journal (by name
+    /// or venue), volume and first page.
+    fn matches(&self, expected: &Expected) -> bool {
+        let Some(journal) = &self.journal else { return false };
+        let name = journal.name.as_deref().or(self.venue.as_deref());
+        let digits = |s: &str| s.trim().trim_start_matches(|c: char| c.is_ascii_alphabetic()).to_string();
+        let first_page = |s: &str| s.split(['-', '–']).next().unwrap_or(s).trim().to_string();
+        name.is_some_and(|n| expected.is_journal(n))
+            && journal.volume.as_deref().is_some_and(|v| digits(v) == digits(&expected.volume))
+            && journal.pages.as_deref().is_some_and(|p| first_page(p) == first_page(&expected.page))
+    }
+}
+
+#[derive(Deserialize)]
+struct S2ExternalIds {
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+}
+
+/// doi.org handle record; a shortDOI's `HS_ALIAS` value is its full DOI.
+#[derive(Deserialize)]
+struct HandleResponse {
+    #[serde(default)]
+    values: Vec<HandleValue>,
+}
+
+#[derive(Deserialize)]
+struct HandleValue {
+    #[serde(rename = "type")]
+    kind: String,
+    data: HandleData,
+}
+
+#[derive(Deserialize)]
+struct HandleData {
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct InspireResponse {
+    hits: InspireHits,
+}
+
+#[derive(Deserialize)]
+struct InspireHits {
+    hits: Vec<InspireHit>,
+}
+
+#[derive(Deserialize)]
+struct InspireHit {
+    metadata: InspireMetadata,
+}
+
+#[derive(Deserialize)]
+struct InspireMetadata {
+    #[serde(default)]
+    arxiv_eprints: Vec<InspireValue>,
+    #[serde(default)]
+    dois: Vec<InspireValue>,
+}
+
+#[derive(Deserialize)]
+struct InspireValue {
+    value: String,
+}
+
+/// One cache row in the `--dump-cache`/`--load-cache` NDJSON exchange format.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    doi: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arxiv_id: Option<String>,
+    created_at: i64,
+}
+
+/// Row counts reported by `--cache-stats`.
+pub struct CacheStats {
+    pub total: usize,
+    pub positive: usize,
+    pub negative: usize,
+    /// Unix time of the oldest row, if any.
+    pub oldest_created_at: Option<i64>,
+}
+
+/// Service queried for missing DOIs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Resolver {
+    /// CrossRef bibliographic search
+    #[default]
+    #[value(name = "crossref")]
+    CrossRef,
+    /// OpenAlex works filter; generous rate limits for large batches
+    #[value(name = "openalex")]
+    OpenAlex,
+    /// Semantic Scholar; can also fill in missing titles (`--enrich-titles`)
+    #[value(name = "s2")]
+    SemanticScholar,
+}
+
+/// Settings for DOI enrichment.
+pub struct EnrichOptions {
+    /// Number of concurrent lookup workers (capped at `MAX_CONCURRENCY`).
+    pub concurrency: usize,
+    /// Only consult the cache; never query the resolver.
+    pub offline: bool,
+    pub resolver: Resolver,
+    /// Extra attempts after a 429 or 5xx answer, with exponential backoff.
+    pub retries: u32,
+    /// Also fill missing titles. Only `Resolver::SemanticScholar` returns
+    /// titles; references that already have a DOI are looked up by it.
+    pub enrich_titles: bool,
+}
+
+/// Default for `EnrichOptions::retries`: waits of 1s, 2s and 4s.
+pub const DEFAULT_RETRIES: u32 = 3;
+/// First backoff wait; each retry doubles it.
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+/// Cap on any single wait, including a server's `Retry-After`, so one
+/// lookup adds at most `retries * MAX_BACKOFF`.
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// What a lookup found. Only Semantic Scholar reports a title, and it may
+/// know the title of a work without a DOI; only INSPIRE reports arXiv IDs.
+#[derive(Debug, Default, PartialEq)]
+struct Work {
+    doi: Option<String>,
+    title: Option<String>,
+    arxiv_id: Option<String>,
+}
+
+impl Work {
+    fn doi(doi: String) -> Self {
+        Work { doi: Some(doi), ..Work::default() }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.doi.is_none() && self.title.is_none() && self.arxiv_id.is_none()
+    }
+}
+
 enum LookupOutcome {
-    Found(String),
+    Found(Work),
     NotFound,
     Skipped, // transient error, don't cache
 }
@@ -38,83 +263,364 @@ impl DoiCache {
             .context("Could not determine cache directory")?
             .join("refextract");
         std::fs::create_dir_all(&cache_dir)?;
-        let db_path = cache_dir.join("doi_cache.db");
-        let conn = Connection::open(&db_path)?;
+        Self::open_at(&cache_dir.join("doi_cache.db"))
+    }
+
+    /// Open (or create) a cache database at an explicit path.
+    pub fn open_at(db_path: &Path) -> Result<Self> {
+        let conn = Connection::open(db_path)
+            .with_context(|| format!("Failed to open DOI cache: {}", db_path.display()))?;
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS doi_cache (
                 key TEXT PRIMARY KEY,
                 doi TEXT,
+                title TEXT,
+                arxiv_id TEXT,
                 created_at INTEGER NOT NULL
             )",
         )?;
-        Ok(Self { conn })
+        // Caches written before titles or arXiv IDs were stored lack the column.
+        for column in ["title", "arxiv_id"] {
+            if conn.prepare(&format!("SELECT {column} FROM doi_cache LIMIT 0")).is_err() {
+                conn.execute_batch(&format!("ALTER TABLE doi_cache ADD COLUMN {column} TEXT"))?;
+            }
+        }
+        Ok(Self {
+            conn: Mutex::new(conn),
+            negative_ttl_secs: DEFAULT_NEGATIVE_TTL_DAYS as i64 * SECS_PER_DAY,
+        })
+    }
+
+    /// Set how many days a negative hit is trusted before it is retried.
+    pub fn with_negative_ttl_days(mut self, days: u64) -> Self {
+        self.negative_ttl_secs = days as i64 * SECS_PER_DAY;
+        self
+    }
+
+    /// None = not cached (or an expired negative hit as of `now`),
+    /// Some(None) = negative hit, Some(Some(doi)) = cached DOI.
+    pub fn get(&self, key: &str, now: i64) -> Result<Option<Option<String>>> {
+        Ok(self.get_work(key, now)?.map(|work| work.doi))
     }
 
-    /// None = not cached, Some(None) = negative hit, Some(Some(doi)) = cached DOI.
-    pub fn get(&self, key: &str) -> Result<Option<Option<String>>> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT doi FROM doi_cache WHERE key = ?1")?;
+    /// Like `get`, with the stored title and arXiv ID. Rows without a DOI
+    /// expire like negative hits even when they have a title or arXiv ID.
+    fn get_work(&self, key: &str, now: i64) -> Result<Option<Work>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT doi, title, arxiv_id, created_at FROM doi_cache WHERE key = ?1")?;
         let mut rows = stmt.query(params![key])?;
-        match rows.next()? {
-            Some(row) => Ok(Some(row.get(0)?)),
-            None => Ok(None),
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        let work = Work { doi: row.get(0)?, title: row.get(1)?, arxiv_id: row.get(2)? };
+        let created_at: i64 = row.get(3)?;
+        if work.doi.is_none() && now - created_at > self.negative_ttl_secs {
+            return Ok(None);
         }
+        Ok(Some(work))
     }
 
     pub fn put(&self, key: &str, doi: Option<&str>) -> Result<()> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-        self.conn.execute(
-            "INSERT OR REPLACE INTO doi_cache (key, doi, created_at) VALUES (?1, ?2, ?3)",
-            params![key, doi, now],
+        self.put_work(key, &Work { doi: doi.map(str::to_string), ..Work::default() })
+    }
+
+    fn put_work(&self, key: &str, work: &Work) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO doi_cache (key, doi, title, arxiv_id, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key, work.doi, work.title, work.arxiv_id, unix_now()],
         )?;
         Ok(())
     }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        let conn = self.conn.lock().unwrap();
+        let (total, positive, oldest): (i64, i64, Option<i64>) = conn.query_row(
+            "SELECT COUNT(*), COUNT(doi), MIN(created_at) FROM doi_cache",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(CacheStats {
+            total: total as usize,
+            positive: positive as usize,
+            negative: (total - positive) as usize,
+            oldest_created_at: oldest,
+        })
+    }
+
+    /// Delete negative hits older than `max_age_secs`. Returns the row count.
+    pub fn purge_negative(&self, max_age_secs: i64) -> Result<usize> {
+        let cutoff = unix_now() - max_age_secs;
+        let deleted = self.conn.lock().unwrap().execute(
+            "DELETE FROM doi_cache WHERE doi IS NULL AND created_at < ?1",
+            params![cutoff],
+        )?;
+        Ok(deleted)
+    }
+
+    /// Write every cache row as one JSON object per line. Returns the row count.
+    pub fn dump(&self, out: &mut impl Write) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT key, doi, title, arxiv_id, created_at FROM doi_cache ORDER BY key")?;
+        let entries = stmt.query_map([], |row| {
+            Ok(CacheEntry {
+                key: row.get(0)?,
+                doi: row.get(1)?,
+                title: row.get(2)?,
+                arxiv_id: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?;
+        let mut count = 0;
+        for entry in entries {
+            writeln!(out, "{}", serde_json::to_string(&entry?)?)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Import rows written by `dump`, replacing existing rows with the same key.
+    /// Returns the row count.
+    pub fn load(&self, input: impl BufRead) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut count = 0;
+        for (i, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CacheEntry = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid cache entry on line {}", i + 1))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO doi_cache (key, doi, title, arxiv_id, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![entry.key, entry.doi, entry.title, entry.arxiv_id, entry.created_at],
+            )?;
+            count += 1;
+        }
+        tx.commit()?;
+        Ok(count)
+    }
 }
 
-fn query_crossref(terms: &str) -> LookupOutcome {
-    let url = format!(
-        "https://api.crossref.org/works?query.bibliographic={}&rows=1&select=DOI&mailto=adeiana@gmail.com",
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+const MAILTO: &str = "adeiana@gmail.com";
+
+fn crossref_url(terms: &str) -> String {
+    format!(
+        "https://api.crossref.org/works?query.bibliographic={}&rows=1&select=DOI&mailto={MAILTO}",
         terms.replace(' ', "+")
-    );
-    let resp = match ureq::get(&url).call() {
+    )
+}
+
+fn openalex_url(filter: &str) -> String {
+    format!("https://api.openalex.org/works?filter={filter}&select=doi,primary_location&per-page=10&mailto={MAILTO}")
+}
+
+/// `path` is `paper/<id>` or `paper/search?query=...`; searches also ask
+/// for the venue and numeration the hit is checked against.
+fn s2_url(path: &str) -> String {
+    let fields = if path.contains('?') { "&fields=externalIds,title,venue,journal" } else { "?fields=externalIds,title" };
+    format!("https://api.semanticscholar.org/graph/v1/{path}{fields}")
+}
+
+/// `q` is an INSPIRE search, e.g. `doi 10.1103/PhysRevD.72.052002`.
+fn inspire_url(q: &str) -> String {
+    format!(
+        "https://inspirehep.net/api/literature?q={}&size=1&fields=arxiv_eprints,dois",
+        q.replace(' ', "%20")
+    )
+}
+
+/// doi.org handle API record of `handle`, e.g. a shortDOI "10/abcde".
+fn handle_url(handle: &str) -> String {
+    format!("https://doi.org/api/handles/{handle}")
+}
+
+/// Error statuses come back as responses so `Retry-After` can be read.
+static AGENT: Lazy<ureq::Agent> = Lazy::new(|| {
+    ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .new_agent()
+});
+
+/// Result of one HTTP attempt.
+enum Attempt {
+    Done(LookupOutcome),
+    /// Rate-limited or server error; the server's `Retry-After`, if any.
+    Retry(Option<Duration>),
+}
+
+/// Reads a successful answer body. The second argument is the query's
+/// `expect`, the citation a hit must match.
+type ReadBody = fn(&str, Option<&Expected>) -> LookupOutcome;
+
+/// Query `url`, retrying 429 and 5xx answers up to `retries` times.
+/// Network errors are not retried: they usually mean no connectivity.
+fn query(deserialize: ReadBody, q: &LookupQuery, retries: u32) -> LookupOutcome {
+    let mut attempt = 0;
+    loop {
+        match query_once(deserialize, q) {
+            Attempt::Done(outcome) => return outcome,
+            Attempt::Retry(retry_after) if attempt < retries => {
+                thread::sleep(backoff_delay(attempt, retry_after));
+                attempt += 1;
+            }
+            Attempt::Retry(_) => return LookupOutcome::Skipped,
+        }
+    }
+}
+
+fn query_once(deserialize: ReadBody, q: &LookupQuery) -> Attempt {
+    let resp = match AGENT.get(&q.url).call() {
         Ok(resp) => resp,
-        Err(_) => return LookupOutcome::Skipped,
+        Err(_) => return Attempt::Done(LookupOutcome::Skipped),
     };
-    if resp.status() == 429 {
-        return LookupOutcome::Skipped;
+    let status = resp.status();
+    if status == 429 || status.is_server_error() {
+        let retry_after = resp
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_retry_after);
+        return Attempt::Retry(retry_after);
     }
-    if resp.status() != 200 {
-        return LookupOutcome::NotFound;
+    if status != 200 {
+        return Attempt::Done(LookupOutcome::NotFound);
     }
     let body = match resp.into_body().read_to_string() {
         Ok(b) => b,
-        Err(_) => return LookupOutcome::Skipped,
+        Err(_) => return Attempt::Done(LookupOutcome::Skipped),
     };
-    deserialize_crossref(&body)
+    Attempt::Done(deserialize(&body, q.expect.as_ref()))
 }
 
-fn deserialize_crossref(body: &str) -> LookupOutcome {
+fn deserializer(resolver: Resolver) -> ReadBody {
+    match resolver {
+        Resolver::CrossRef => deserialize_crossref,
+        Resolver::OpenAlex => deserialize_openalex,
+        Resolver::SemanticScholar => deserialize_s2,
+    }
+}
+
+/// Wait before retry number `attempt` (0-based): the server's `Retry-After`
+/// if given, else `BASE_BACKOFF * 2^attempt`; never more than `MAX_BACKOFF`.
+fn backoff_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    let exponential = BASE_BACKOFF.saturating_mul(2u32.saturating_pow(attempt));
+    retry_after.unwrap_or(exponential).min(MAX_BACKOFF)
+}
+
+/// `Retry-After` in delay-seconds form; the HTTP-date form is ignored.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+fn deserialize_crossref(body: &str, _expect: Option<&Expected>) -> LookupOutcome {
     match serde_json::from_str::<CrossRefResponse>(body) {
         Ok(data) => match data.message.items.into_iter().next() {
-            Some(item) => LookupOutcome::Found(item.doi),
+            Some(item) => LookupOutcome::Found(Work::doi(item.doi)),
+            None => LookupOutcome::NotFound,
+        },
+        Err(_) => LookupOutcome::NotFound,
+    }
+}
+
+/// The first hit with a DOI; with an `expect`, the first one published in
+/// its journal, since volume, page and year alone match papers in many.
+fn deserialize_openalex(body: &str, expect: Option<&Expected>) -> LookupOutcome {
+    let in_source = |w: &OpenAlexWork| {
+        expect.is_none_or(|expected| {
+            w.primary_location
+                .as_ref()
+                .and_then(|l| l.source.as_ref())
+                .is_some_and(|s| expected.is_journal(&s.display_name))
+        })
+    };
+    match serde_json::from_str::<OpenAlexResponse>(body) {
+        Ok(data) => match data.results.into_iter().filter(in_source).find_map(|w| w.doi) {
+            Some(doi) => {
+                let bare = doi.strip_prefix("https://doi.org/").unwrap_or(&doi);
+                LookupOutcome::Found(Work::doi(bare.to_string()))
+            }
             None => LookupOutcome::NotFound,
         },
         Err(_) => LookupOutcome::NotFound,
     }
 }
 
-fn lookup_cached_or_fetch(cache: &DoiCache, key: &str, terms: &str) -> Option<String> {
-    if let Ok(Some(cached)) = cache.get(key) {
-        return cached;
+/// A search hit only counts when it is the expected citation: the search
+/// is free text and returns its best guess.
+fn deserialize_s2(body: &str, expect: Option<&Expected>) -> LookupOutcome {
+    let paper = match serde_json::from_str::<S2Response>(body) {
+        Ok(S2Response::Search { data }) => {
+            data.into_iter().next().filter(|p| expect.is_none_or(|expected| p.matches(expected)))
+        }
+        Ok(S2Response::Paper(paper)) => Some(paper),
+        Err(_) => None,
+    };
+    let work = paper.map_or_else(Work::default, |p| Work {
+        doi: p.external_ids.and_then(|ids| ids.doi),
+        title: p.title.filter(|t| !t.trim().is_empty()),
+        arxiv_id: None,
+    });
+    if work.is_empty() { LookupOutcome::NotFound } else { LookupOutcome::Found(work) }
+}
+
+/// First DOI and first arXiv e-print of the top hit.
+fn deserialize_inspire(body: &str, _expect: Option<&Expected>) -> LookupOutcome {
+    let metadata = match serde_json::from_str::<InspireResponse>(body) {
+        Ok(data) => data.hits.hits.into_iter().next().map(|hit| hit.metadata),
+        Err(_) => None,
+    };
+    let work = metadata.map_or_else(Work::default, |m| Work {
+        doi: m.dois.into_iter().next().map(|d| d.value),
+        title: None,
+        arxiv_id: m.arxiv_eprints.into_iter().next().map(|e| e.value),
+    });
+    if work.is_empty() { LookupOutcome::NotFound } else { LookupOutcome::Found(work) }
+}
+
+/// The full DOI a shortDOI stands for, from its `HS_ALIAS` value.
+fn deserialize_handle(body: &str, _expect: Option<&Expected>) -> LookupOutcome {
+    let alias = serde_json::from_str::<HandleResponse>(body).ok().and_then(|data| {
+        data.values
+            .into_iter()
+            .filter(|v| v.kind == "HS_ALIAS")
+            .find_map(|v| v.data.value.as_str().map(str::to_string))
+    });
+    match alias.filter(|doi| doi_registrant(doi).is_some()) {
+        Some(doi) => LookupOutcome::Found(Work::doi(doi)),
+        None => LookupOutcome::NotFound,
     }
-    match query_crossref(terms) {
-        LookupOutcome::Found(doi) => {
-            let _ = cache.put(key, Some(&doi));
-            Some(doi)
+}
+
+fn lookup_cached_or_fetch(
+    cache: &DoiCache,
+    q: &LookupQuery,
+    deserialize: ReadBody,
+    opts: &EnrichOptions,
+) -> Option<Work> {
+    if let Ok(Some(cached)) = cache.get_work(&q.key, unix_now()) {
+        return (!cached.is_empty()).then_some(cached);
+    }
+    if opts.offline {
+        return None;
+    }
+    let key = q.key.as_str();
+    match query(deserialize, q, opts.retries) {
+        LookupOutcome::Found(work) => {
+            let _ = cache.put_work(key, &work);
+            Some(work)
         }
         LookupOutcome::NotFound => {
             let _ = cache.put(key, None);
@@ -124,49 +630,588 @@ fn lookup_cached_or_fetch(cache: &DoiCache, key: &str, terms: &str) -> Option<St
     }
 }
 
-pub fn enrich_dois(refs: &mut [ParsedReference], cache: &DoiCache) {
-    let total = refs.iter().filter(|r| r.doi.is_none()).count();
-    let mut done = 0;
-    for r in refs.iter_mut() {
-        if r.doi.is_some() {
-            continue;
+/// Upper bound on concurrent resolver requests, to stay polite to the API.
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// A cache key plus the resolver request URL for one lookup attempt.
+struct LookupQuery {
+    key: String,
+    url: String,
+    /// Citation a hit must match, for resolvers that search on numeration
+    /// or free text.
+    expect: Option<Expected>,
+}
+
+/// The journal citation a search was built from (KB journal name).
+struct Expected {
+    journal: String,
+    volume: String,
+    page: String,
+}
+
+impl Expected {
+    fn from_ref(r: &ParsedReference) -> Option<Self> {
+        Some(Expected {
+            journal: r.journal_title.clone()?,
+            volume: r.journal_volume.clone()?,
+            page: r.journal_page.clone()?,
+        })
+    }
+
+    /// Whether `name` (a resolver's journal name) is this journal.
+    fn is_journal(&self, name: &str) -> bool {
+        kb::match_journal_name(name, 0).is_some_and(|(_, matched)| matched == self.journal)
+    }
+}
+
+/// Fill missing DOIs using up to `opts.concurrency` parallel lookup workers
+/// (see `run_lookups`). Results are written back by index, leaving the
+/// order of `refs` unchanged. In offline mode only the cache is consulted.
+/// With `opts.enrich_titles`, missing titles are filled the same way.
+/// shortDOIs are resolved first (`resolve_short_dois`); only references
+/// still without a DOI are searched.
+pub fn enrich_dois(refs: &mut [ParsedReference], cache: &DoiCache, opts: &EnrichOptions) {
+    resolve_short_dois(refs, cache, opts);
+    let wants_title = |r: &ParsedReference| {
+        opts.enrich_titles && opts.resolver == Resolver::SemanticScholar && r.title.is_none()
+    };
+    let pending: Vec<(usize, Vec<LookupQuery>)> = refs
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.doi.is_none() || wants_title(r))
+        .map(|(i, r)| (i, lookup_queries(r, opts.resolver)))
+        .collect();
+    let found = run_lookups(&pending, cache, deserializer(opts.resolver), opts, "Looking up DOIs");
+    for (idx, work) in found {
+        let r = &mut refs[idx];
+        if let Some(title) = work.title && wants_title(r) {
+            r.title = Some(title);
+        }
+        if let Some(doi) = work.doi && r.doi.is_none() {
+            r.doi_prefix = doi_registrant(&doi).map(str::to_string);
+            r.doi = Some(doi);
+        }
+    }
+}
+
+/// Fill `doi` from `short_doi` by asking doi.org which DOI the shortDOI
+/// aliases. Runs like `enrich_dois`; `short_doi` is kept either way.
+pub fn resolve_short_dois(refs: &mut [ParsedReference], cache: &DoiCache, opts: &EnrichOptions) {
+    let pending: Vec<(usize, Vec<LookupQuery>)> = refs
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.doi.is_none())
+        .filter_map(|(i, r)| Some((i, vec![short_doi_query(r.short_doi.as_deref()?)])))
+        .collect();
+    let found = run_lookups(&pending, cache, deserialize_handle, opts, "Resolving shortDOIs");
+    for (idx, work) in found {
+        let r = &mut refs[idx];
+        if let Some(doi) = work.doi {
+            r.doi_prefix = doi_registrant(&doi).map(str::to_string);
+            r.doi = Some(doi);
+        }
+    }
+}
+
+fn short_doi_query(short_doi: &str) -> LookupQuery {
+    LookupQuery { key: format!("short:{short_doi}"), url: handle_url(short_doi), expect: None }
+}
+
+/// Fill a missing arXiv ID from the DOI, or a missing DOI from the arXiv
+/// ID, by asking INSPIRE (`--cross-link`). Runs like `enrich_dois`: same
+/// workers, same cache, cache only in offline mode.
+pub fn cross_link(refs: &mut [ParsedReference], cache: &DoiCache, opts: &EnrichOptions) {
+    let pending: Vec<(usize, Vec<LookupQuery>)> = refs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| Some((i, vec![cross_link_query(r)?])))
+        .collect();
+    let found = run_lookups(&pending, cache, deserialize_inspire, opts, "Cross-linking");
+    for (idx, work) in found {
+        let r = &mut refs[idx];
+        if let Some(arxiv_id) = work.arxiv_id && r.arxiv_id.is_none() {
+            r.arxiv_id = Some(arxiv_id);
         }
-        done += 1;
-        eprint!("\rLooking up DOIs: {done}/{total}");
-        if try_journal_lookup(r, cache) {
-            continue;
+        if let Some(doi) = work.doi && r.doi.is_none() {
+            r.doi_prefix = doi_registrant(&doi).map(str::to_string);
+            r.doi = Some(doi);
         }
-        try_arxiv_lookup(r, cache);
     }
+}
+
+/// The INSPIRE lookup for a reference with exactly one of DOI and arXiv ID.
+fn cross_link_query(r: &ParsedReference) -> Option<LookupQuery> {
+    match (&r.doi, &r.arxiv_id) {
+        (Some(doi), None) => Some(LookupQuery {
+            key: format!("inspire:doi:{doi}"),
+            url: inspire_url(&format!("doi {doi}")),
+            expect: None,
+        }),
+        (None, Some(arxiv_id)) => Some(LookupQuery {
+            key: format!("inspire:arxiv:{arxiv_id}"),
+            url: inspire_url(&format!("arxiv {arxiv_id}")),
+            expect: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Run each reference's queries until one finds something, on up to
+/// `opts.concurrency` workers. Each worker claims the next pending
+/// reference, so at most `concurrency` requests are in flight. Returns
+/// what was found, by reference index.
+fn run_lookups(
+    pending: &[(usize, Vec<LookupQuery>)],
+    cache: &DoiCache,
+    deserialize: ReadBody,
+    opts: &EnrichOptions,
+    progress: &str,
+) -> Vec<(usize, Work)> {
+    let total = pending.len();
+    let workers = opts.concurrency.clamp(1, MAX_CONCURRENCY).min(total.max(1));
+    let next = AtomicUsize::new(0);
+    let done = Mutex::new(0usize);
+
+    let found: Vec<(usize, Work)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut found = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some((ref_idx, queries)) = pending.get(idx) else {
+                            break;
+                        };
+                        let work = queries
+                            .iter()
+                            .find_map(|q| lookup_cached_or_fetch(cache, q, deserialize, opts));
+                        report_progress(&done, total, progress);
+                        if let Some(work) = work {
+                            found.push((*ref_idx, work));
+                        }
+                    }
+                    found
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
     if total > 0 {
         eprintln!();
     }
+    found
 }
 
-fn try_journal_lookup(r: &mut ParsedReference, cache: &DoiCache) -> bool {
-    let (Some(journal), Some(volume), Some(page)) =
+/// Registrant code of a DOI, the part before the first slash:
+/// "10.1103" for "10.1103/PhysRevD.72.052002". None unless the DOI is
+/// "10." plus dot-separated digits, a slash and a non-empty suffix.
+pub fn doi_registrant(doi: &str) -> Option<&str> {
+    let (prefix, suffix) = doi.split_once('/')?;
+    let code = prefix.strip_prefix("10.")?;
+    let well_formed = !suffix.trim().is_empty()
+        && code.split('.').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    well_formed.then_some(prefix)
+}
+
+/// A shortDOI ("10/abcde"), the doi.org shortener's alias of a full DOI.
+/// The parser keeps it in `short_doi` for `resolve_short_dois`.
+pub fn is_short_doi(doi: &str) -> bool {
+    doi.strip_prefix("10/")
+        .is_some_and(|code| !code.is_empty() && code.bytes().all(|b| b.is_ascii_alphanumeric()))
+}
+
+/// Print the progress counter. Incrementing and printing under one lock
+/// keeps the counter monotonic on stderr even with concurrent workers.
+fn report_progress(done: &Mutex<usize>, total: usize, label: &str) {
+    let mut done = done.lock().unwrap();
+    *done += 1;
+    eprint!("\r{label}: {}/{total}", *done);
+}
+
+/// Lookups to try for a reference, in order: journal citation, then arXiv ID.
+/// Key formats are documented on `DoiCache` and must not change.
+fn lookup_queries(r: &ParsedReference, resolver: Resolver) -> Vec<LookupQuery> {
+    match resolver {
+        Resolver::CrossRef => crossref_queries(r),
+        Resolver::OpenAlex => openalex_queries(r),
+        Resolver::SemanticScholar => s2_queries(r),
+    }
+}
+
+fn crossref_queries(r: &ParsedReference) -> Vec<LookupQuery> {
+    let mut queries = Vec::new();
+    if let (Some(journal), Some(volume), Some(page)) =
         (&r.journal_title, &r.journal_volume, &r.journal_page)
-    else {
-        return false;
-    };
-    let key = format!("j:{journal}|v:{volume}|p:{page}");
-    let terms = format!("{journal} {volume} {page}");
-    if let Some(doi) = lookup_cached_or_fetch(cache, &key, &terms) {
-        r.doi = Some(doi);
-        return true;
+    {
+        queries.push(LookupQuery {
+            key: format!("j:{journal}|v:{volume}|p:{page}"),
+            url: crossref_url(&format!("{journal} {volume} {page}")),
+            expect: None,
+        });
     }
-    false
+    if let Some(arxiv_id) = &r.arxiv_id {
+        queries.push(LookupQuery {
+            key: format!("arxiv:{arxiv_id}"),
+            url: crossref_url(&format!("arXiv {arxiv_id}")),
+            expect: None,
+        });
+    }
+    queries
 }
 
-fn try_arxiv_lookup(r: &mut ParsedReference, cache: &DoiCache) -> bool {
-    let Some(arxiv_id) = &r.arxiv_id else {
-        return false;
-    };
-    let key = format!("arxiv:{arxiv_id}");
-    let terms = format!("arXiv {arxiv_id}");
-    if let Some(doi) = lookup_cached_or_fetch(cache, &key, &terms) {
-        r.doi = Some(doi);
-        return true;
+/// OpenAlex can't search journal abbreviations, so a journal citation is
+/// matched on volume, first page and year, and a hit only counts when its
+/// source is the same KB journal. Without a year, or for a journal the KB
+/// doesn't know, it would be too ambiguous and is skipped. arXiv IDs go
+/// through the arXiv DOI, which OpenAlex maps to the published version
+/// when it knows one.
+fn openalex_queries(r: &ParsedReference) -> Vec<LookupQuery> {
+    let mut queries = Vec::new();
+    if let (Some(journal), Some(volume), Some(page), Some(year)) =
+        (&r.journal_title, &r.journal_volume, &r.journal_page, &r.journal_year)
+        && kb::match_journal_name(journal, 0).is_some_and(|(_, name)| &name == journal)
+    {
+        let volume = volume.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+        let first_page = page.split('-').next().unwrap_or(page);
+        queries.push(LookupQuery {
+            key: format!("openalex:j:{journal}|v:{volume}|p:{page}"),
+            url: openalex_url(&format!(
+                "biblio.volume:{volume},biblio.first_page:{first_page},publication_year:{year}"
+            )),
+            expect: Expected::from_ref(r),
+        });
+    }
+    if let Some(arxiv_id) = &r.arxiv_id {
+        queries.push(LookupQuery {
+            key: format!("openalex:arxiv:{arxiv_id}"),
+            url: openalex_url(&format!("doi:10.48550/arxiv.{arxiv_id}")),
+            expect: None,
+        });
+    }
+    queries
+}
+
+/// A reference that already has a DOI (it is only pending for its title)
+/// is looked up by that DOI. The arXiv ID goes before the journal search,
+/// which is only a best guess.
+fn s2_queries(r: &ParsedReference) -> Vec<LookupQuery> {
+    if let Some(doi) = &r.doi {
+        return vec![LookupQuery {
+            key: format!("s2:doi:{doi}"),
+            url: s2_url(&format!("paper/DOI:{doi}")),
+            expect: None,
+        }];
+    }
+    let mut queries = Vec::new();
+    if let Some(arxiv_id) = &r.arxiv_id {
+        queries.push(LookupQuery {
+            key: format!("s2:arxiv:{arxiv_id}"),
+            url: s2_url(&format!("paper/arXiv:{arxiv_id}")),
+            expect: None,
+        });
     }
-    false
+    if let (Some(journal), Some(volume), Some(page)) =
+        (&r.journal_title, &r.journal_volume, &r.journal_page)
+    {
+        let terms = format!("{journal} {volume} {page}").replace(' ', "+");
+        queries.push(LookupQuery {
+            key: format!("s2:j:{journal}|v:{volume}|p:{page}"),
+            url: s2_url(&format!("paper/search?query={terms}&limit=1")),
+            expect: Expected::from_ref(r),
+        });
+    }
+    queries
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str) -> (DoiCache, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!(
+            "refextract-test-{name}-{}.db",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        (DoiCache::open_at(&path).unwrap(), path)
+    }
+
+    #[test]
+    fn dump_load_round_trip() {
+        let (src, src_path) = temp_cache("dump-src");
+        src.put("j:Phys. Rev. D|v:72|p:052002", Some("10.1103/PhysRevD.72.052002")).unwrap();
+        src.put("arxiv:hep-ph/0202058", None).unwrap();
+        let mut buf = Vec::new();
+        assert_eq!(src.dump(&mut buf).unwrap(), 2);
+
+        let (dst, dst_path) = temp_cache("dump-dst");
+        assert_eq!(dst.load(buf.as_slice()).unwrap(), 2);
+        assert_eq!(
+            dst.get("j:Phys. Rev. D|v:72|p:052002", unix_now()).unwrap(),
+            Some(Some("10.1103/PhysRevD.72.052002".to_string()))
+        );
+        assert_eq!(dst.get("arxiv:hep-ph/0202058", unix_now()).unwrap(), Some(None));
+        let _ = std::fs::remove_file(src_path);
+        let _ = std::fs::remove_file(dst_path);
+    }
+
+    #[test]
+    fn stats_and_purge_negative() {
+        let (cache, path) = temp_cache("stats");
+        cache.put("arxiv:1001.0785", Some("10.1000/hit")).unwrap();
+        cache.put("arxiv:9999.9999", None).unwrap();
+        cache.conn.lock().unwrap().execute(
+            "INSERT INTO doi_cache (key, doi, created_at) VALUES ('arxiv:old', NULL, 100)",
+            [],
+        ).unwrap();
+        let stats = cache.stats().unwrap();
+        assert_eq!((stats.total, stats.positive, stats.negative), (3, 1, 2));
+        assert_eq!(stats.oldest_created_at, Some(100));
+
+        assert_eq!(cache.purge_negative(86_400).unwrap(), 1);
+        assert_eq!(cache.get("arxiv:old", unix_now()).unwrap(), None);
+        assert_eq!(cache.get("arxiv:9999.9999", unix_now()).unwrap(), Some(None), "recent negative kept");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn stale_negative_hit_is_a_miss() {
+        let (cache, path) = temp_cache("ttl");
+        let cache = cache.with_negative_ttl_days(30);
+        let now = unix_now();
+        let stale = now - 31 * SECS_PER_DAY;
+        {
+            let conn = cache.conn.lock().unwrap();
+            for (key, doi) in [("arxiv:miss", None), ("arxiv:hit", Some("10.1000/old"))] {
+                conn.execute(
+                    "INSERT INTO doi_cache (key, doi, created_at) VALUES (?1, ?2, ?3)",
+                    params![key, doi, stale],
+                ).unwrap();
+            }
+        }
+        assert_eq!(cache.get("arxiv:miss", now).unwrap(), None, "stale negative must be retried");
+        assert_eq!(cache.get("arxiv:miss", stale + 1).unwrap(), Some(None));
+        assert_eq!(
+            cache.get("arxiv:hit", now).unwrap(),
+            Some(Some("10.1000/old".to_string())),
+            "positive hits never expire"
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn offline_lookup_uses_cache_only() {
+        let (cache, path) = temp_cache("offline");
+        cache.put("arxiv:1001.0785", Some("10.1000/cached")).unwrap();
+        let query = |key: &str| LookupQuery { key: key.to_string(), url: String::new(), expect: None };
+        let opts = EnrichOptions {
+            concurrency: 1,
+            offline: true,
+            resolver: Resolver::CrossRef,
+            retries: DEFAULT_RETRIES,
+            enrich_titles: false,
+        };
+        let hit = lookup_cached_or_fetch(&cache, &query("arxiv:1001.0785"), deserialize_crossref, &opts);
+        assert_eq!(hit, Some(Work::doi("10.1000/cached".to_string())));
+        let miss = lookup_cached_or_fetch(&cache, &query("arxiv:9999.9999"), deserialize_crossref, &opts);
+        assert!(miss.is_none());
+        assert_eq!(cache.get("arxiv:9999.9999", unix_now()).unwrap(), None, "offline miss must not be cached");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn openalex_response_and_keys() {
+        let body = r#"{"meta":{"count":1},"results":[{"doi":"https://doi.org/10.1103/PhysRevD.72.052002"}]}"#;
+        assert!(matches!(deserialize_openalex(body, None), LookupOutcome::Found(w) if w == Work::doi("10.1103/PhysRevD.72.052002".into())));
+        assert!(matches!(deserialize_openalex(r#"{"results":[{"doi":null}]}"#, None), LookupOutcome::NotFound));
+        assert!(matches!(deserialize_openalex(r#"{"results":[]}"#, None), LookupOutcome::NotFound));
+
+        let r = &crate::parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), arXiv:1001.0785".to_string(),
+        ])[0];
+        let keys: Vec<String> = lookup_queries(r, Resolver::OpenAlex).into_iter().map(|q| q.key).collect();
+        assert_eq!(keys, ["openalex:j:Phys. Rev. D|v:72|p:052002", "openalex:arxiv:1001.0785"]);
+        let queries = lookup_queries(r, Resolver::CrossRef);
+        assert_eq!(queries[0].key, "j:Phys. Rev. D|v:72|p:052002", "CrossRef keys unchanged");
+        assert!(lookup_queries(r, Resolver::OpenAlex)[0]
+            .url
+            .contains("filter=biblio.volume:72,biblio.first_page:052002,publication_year:2005"));
+    }
+
+    #[test]
+    fn openalex_hits_from_other_journals_are_rejected() {
+        let hit = |doi: &str, source: &str| {
+            format!(r#"{{"doi":"https://doi.org/{doi}","primary_location":{{"source":{{"display_name":"{source}"}}}}}}"#)
+        };
+        let other = hit("10.1016/j.physletb.2005.01.001", "Physics Letters B");
+        let body = format!(r#"{{"results":[{other}]}}"#);
+        let expected = Expected { journal: "Phys. Rev. D".into(), volume: "72".into(), page: "052002".into() };
+        assert!(matches!(deserialize_openalex(&body, Some(&expected)), LookupOutcome::NotFound));
+        let right = hit("10.1103/PhysRevD.72.052002", "Physical Review D");
+        let body = format!(r#"{{"results":[{other},{right},{{"doi":null,"primary_location":null}}]}}"#);
+        let LookupOutcome::Found(work) = deserialize_openalex(&body, Some(&expected)) else { panic!("hit not found") };
+        assert_eq!(work.doi.as_deref(), Some("10.1103/PhysRevD.72.052002"));
+
+        let r = &crate::parse_reference_text(&["[1] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string()])[0];
+        let expected = lookup_queries(r, Resolver::OpenAlex).remove(0).expect.unwrap();
+        assert_eq!((expected.journal.as_str(), expected.volume.as_str()), ("Phys. Rev. D", "72"));
+    }
+
+    #[test]
+    fn semantic_scholar_responses_keys_and_titles() {
+        let paper = r#"{"paperId":"abc","externalIds":{"ArXiv":"1207.7214","DOI":"10.1016/j.physletb.2012.08.020"},"title":"Observation of a new particle"}"#;
+        let LookupOutcome::Found(work) = deserialize_s2(paper, None) else { panic!("paper not found") };
+        assert_eq!(work.doi.as_deref(), Some("10.1016/j.physletb.2012.08.020"));
+        assert_eq!(work.title.as_deref(), Some("Observation of a new particle"));
+        let search = r#"{"total":1,"offset":0,"data":[{"paperId":"x","externalIds":{"ArXiv":"1001.0785"},"title":"Only a title"}]}"#;
+        assert!(matches!(deserialize_s2(search, None), LookupOutcome::Found(Work { doi: None, title: Some(_), .. })));
+        assert!(matches!(deserialize_s2(r#"{"total":0,"offset":0,"data":[]}"#, None), LookupOutcome::NotFound));
+        assert!(matches!(deserialize_s2(r#"{"error":"Paper not found"}"#, None), LookupOutcome::NotFound));
+        // A search hit must be the citation searched for
+        let expected = Expected { journal: "Phys. Rev. D".into(), volume: "72".into(), page: "052002".into() };
+        let hit = |name: &str, volume: &str| {
+            format!(
+                r#"{{"data":[{{"externalIds":{{"DOI":"10.1/x"}},"title":"T","venue":"","journal":{{"name":"{name}","volume":"{volume}","pages":"052002"}}}}]}}"#
+            )
+        };
+        assert!(matches!(deserialize_s2(&hit("Physical Review D", "72"), Some(&expected)), LookupOutcome::Found(_)));
+        assert!(matches!(deserialize_s2(&hit("Physical Review D", "73"), Some(&expected)), LookupOutcome::NotFound));
+        assert!(matches!(deserialize_s2(&hit("Physics Letters B", "72"), Some(&expected)), LookupOutcome::NotFound));
+        assert!(matches!(deserialize_s2(search, Some(&expected)), LookupOutcome::NotFound));
+
+        let mut refs = crate::parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), arXiv:1001.0785".to_string(),
+            "[2] B. Other, doi:10.1103/PhysRevD.72.052002".to_string(),
+        ]);
+        let keys: Vec<String> = lookup_queries(&refs[0], Resolver::SemanticScholar).into_iter().map(|q| q.key).collect();
+        assert_eq!(keys, ["s2:arxiv:1001.0785", "s2:j:Phys. Rev. D|v:72|p:052002"]);
+        let q = &lookup_queries(&refs[1], Resolver::SemanticScholar)[0];
+        assert_eq!(q.key, "s2:doi:10.1103/PhysRevD.72.052002");
+        assert!(q.url.ends_with("/paper/DOI:10.1103/PhysRevD.72.052002?fields=externalIds,title"));
+
+        // Titles come from the cache in offline mode, and only with enrich_titles
+        let (cache, path) = temp_cache("s2");
+        let work = |doi: Option<&str>, title: &str| Work { doi: doi.map(str::to_string), title: Some(title.to_string()), arxiv_id: None };
+        cache.put_work("s2:j:Phys. Rev. D|v:72|p:052002", &work(Some("10.1000/x"), "First title")).unwrap();
+        cache.put_work("s2:doi:10.1103/PhysRevD.72.052002", &work(None, "Second title")).unwrap();
+        let mut opts = EnrichOptions {
+            concurrency: 1,
+            offline: true,
+            resolver: Resolver::SemanticScholar,
+            retries: 0,
+            enrich_titles: false,
+        };
+        let mut plain = refs.clone();
+        enrich_dois(&mut plain, &cache, &opts);
+        assert_eq!(plain[0].doi.as_deref(), Some("10.1000/x"));
+        assert_eq!(plain[0].title, None);
+        opts.enrich_titles = true;
+        enrich_dois(&mut refs, &cache, &opts);
+        assert_eq!(refs[0].title.as_deref(), Some("First title"));
+        assert_eq!(refs[1].title.as_deref(), Some("Second title"));
+        assert_eq!(refs[1].doi.as_deref(), Some("10.1103/PhysRevD.72.052002"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn inspire_responses_cross_link_doi_and_arxiv() {
+        let body = r#"{"hits":{"hits":[{"id":"1124337","metadata":{"arxiv_eprints":[{"categories":["hep-ex"],"value":"1207.7214"}],"dois":[{"source":"Elsevier","value":"10.1016/j.physletb.2012.08.020"}]}}],"total":1},"links":{}}"#;
+        let LookupOutcome::Found(work) = deserialize_inspire(body, None) else { panic!("hit not found") };
+        assert_eq!(work.arxiv_id.as_deref(), Some("1207.7214"));
+        assert_eq!(work.doi.as_deref(), Some("10.1016/j.physletb.2012.08.020"));
+        let no_arxiv = r#"{"hits":{"hits":[{"metadata":{"dois":[{"value":"10.1103/PhysRev.159.1251"}]}}],"total":1}}"#;
+        assert!(matches!(deserialize_inspire(no_arxiv, None), LookupOutcome::Found(Work { arxiv_id: None, .. })));
+        assert!(matches!(deserialize_inspire(r#"{"hits":{"hits":[],"total":0}}"#, None), LookupOutcome::NotFound));
+
+        let mut refs = crate::parse_reference_text(&[
+            "[1] G. Aad et al., doi:10.1016/j.physletb.2012.08.020".to_string(),
+            "[2] S. Chatrchyan et al., arXiv:1207.7235".to_string(),
+            "[3] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string(),
+        ]);
+        let keys: Vec<Option<String>> = refs.iter().map(|r| cross_link_query(r).map(|q| q.key)).collect();
+        assert_eq!(
+            keys,
+            [Some("inspire:doi:10.1016/j.physletb.2012.08.020".into()), Some("inspire:arxiv:1207.7235".into()), None]
+        );
+        assert!(cross_link_query(&refs[1]).unwrap().url.contains("q=arxiv%201207.7235&"));
+
+        let (cache, path) = temp_cache("inspire");
+        let work = |doi: &str, arxiv_id: &str| Work { doi: Some(doi.into()), title: None, arxiv_id: Some(arxiv_id.into()) };
+        cache.put_work("inspire:doi:10.1016/j.physletb.2012.08.020", &work("10.1016/j.physletb.2012.08.020", "1207.7214")).unwrap();
+        cache.put_work("inspire:arxiv:1207.7235", &work("10.1016/j.physletb.2012.08.021", "1207.7235")).unwrap();
+        let opts = EnrichOptions {
+            concurrency: 1,
+            offline: true,
+            resolver: Resolver::CrossRef,
+            retries: 0,
+            enrich_titles: false,
+        };
+        cross_link(&mut refs, &cache, &opts);
+        assert_eq!(refs[0].arxiv_id.as_deref(), Some("1207.7214"));
+        assert_eq!(refs[1].doi.as_deref(), Some("10.1016/j.physletb.2012.08.021"));
+        assert_eq!(refs[1].doi_prefix.as_deref(), Some("10.1016"));
+        assert_eq!((refs[2].doi.as_deref(), refs[2].arxiv_id.as_deref()), (None, None));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn registrant_of_well_formed_and_malformed_dois() {
+        assert_eq!(doi_registrant("10.1103/PhysRevD.72.052002"), Some("10.1103"));
+        assert_eq!(doi_registrant("10.1016/0550-3213(94)90501-0"), Some("10.1016"));
+        assert_eq!(doi_registrant("10.1000.10/123456"), Some("10.1000.10"));
+        for bad in ["10.1103", "10.1103/", "11.1103/abc", "10./abc", "10.11a3/abc", "PhysRevD.72/052002"] {
+            assert_eq!(doi_registrant(bad), None, "{bad}");
+        }
+        let r = crate::parse_reference_text(&["A. Author, doi:10.1088/1126-6708/2006/05/026".to_string()]);
+        assert_eq!(r[0].doi_prefix.as_deref(), Some("10.1088"));
+        assert!(is_short_doi("10/abcde"));
+        assert!(!is_short_doi("10.1103/PhysRevD.72.052002") && !is_short_doi("10/"));
+    }
+
+    #[test]
+    fn short_dois_resolve_through_their_alias() {
+        let body = r#"{"responseCode":1,"handle":"10/bxfr","values":[
+            {"index":1,"type":"URL","data":{"format":"string","value":"https://doi.org/10.1103/PhysRevD.72.052002"}},
+            {"index":2,"type":"HS_ALIAS","data":{"format":"string","value":"10.1103/PhysRevD.72.052002"}}]}"#;
+        let LookupOutcome::Found(work) = deserialize_handle(body, None) else { panic!("alias not found") };
+        assert_eq!(work.doi.as_deref(), Some("10.1103/PhysRevD.72.052002"));
+        assert!(matches!(deserialize_handle(r#"{"responseCode":100}"#, None), LookupOutcome::NotFound));
+
+        let mut refs = crate::parse_reference_text(&[
+            "[1] A. Author, Title, doi:10/bxfr.".to_string(),
+            "[2] B. Author, Title, doi:10/zzzz.".to_string(),
+        ]);
+        assert_eq!((refs[0].doi.as_deref(), refs[0].short_doi.as_deref()), (None, Some("10/bxfr")));
+        let (cache, path) = temp_cache("short");
+        cache.put_work("short:10/bxfr", &Work::doi("10.1103/PhysRevD.72.052002".into())).unwrap();
+        let opts = EnrichOptions {
+            concurrency: 1,
+            offline: true,
+            resolver: Resolver::CrossRef,
+            retries: 0,
+            enrich_titles: false,
+        };
+        enrich_dois(&mut refs, &cache, &opts);
+        assert_eq!(refs[0].doi.as_deref(), Some("10.1103/PhysRevD.72.052002"));
+        assert_eq!((refs[0].doi_prefix.as_deref(), refs[0].short_doi.as_deref()), (Some("10.1103"), Some("10/bxfr")));
+        assert_eq!((refs[1].doi.as_deref(), refs[1].short_doi.as_deref()), (None, Some("10/zzzz")));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn backoff_doubles_and_is_capped() {
+        let secs = |attempt, retry_after| backoff_delay(attempt, retry_after).as_secs();
+        assert_eq!([secs(0, None), secs(1, None), secs(2, None)], [1, 2, 4]);
+        assert_eq!(secs(5, None), MAX_BACKOFF.as_secs());
+        assert_eq!(secs(40, None), MAX_BACKOFF.as_secs(), "no overflow");
+        assert_eq!(secs(0, Some(Duration::from_secs(3))), 3, "Retry-After wins");
+        assert_eq!(secs(0, Some(Duration::from_secs(3600))), MAX_BACKOFF.as_secs());
+
+        assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
+
@@ -0,0 +1,57 @@
+//! Errors returned by the PDF pipeline, so callers can tell a missing
+//! pdfium library from a damaged PDF or one without any text.
+
+use pdfium_render::prelude::PdfiumError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RefextractError {
+    /// The pdfium library could not be found or loaded.
+    #[error("{0}")]
+    PdfiumBinding(String),
+    /// The PDF could not be opened.
+    #[error("{}", load_message(.label, .reason))]
+    PdfLoad { label: String, reason: PdfLoadReason },
+    /// pdfium failed to read the text of a page (1-based).
+    #[error("Failed to load text for page {page}")]
+    TextExtraction {
+        page: usize,
+        #[source]
+        source: PdfiumError,
+    },
+    /// A `--pages` selection past the end of the document.
+    #[error("Page range {start}-{end} is out of bounds: document has {page_count} pages")]
+    PageRange { start: usize, end: usize, page_count: usize },
+    /// No selected page has any text, e.g. a scan without OCR.
+    #[error("{label} has no text layer; it may be a scan (try --ocr-fallback)")]
+    NoText { label: String },
+}
+
+/// Why a PDF failed to open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PdfLoadReason {
+    NotFound,
+    /// Encrypted, and no password was given.
+    NeedsPassword,
+    WrongPassword,
+    UnsupportedSecurity,
+    Corrupt,
+    Unreadable,
+    /// Any other pdfium error, as pdfium words it.
+    Other(String),
+}
+
+fn load_message(label: &str, reason: &PdfLoadReason) -> String {
+    match reason {
+        PdfLoadReason::NotFound => format!("Failed to load PDF: {label}: no such file"),
+        PdfLoadReason::NeedsPassword => {
+            format!("Failed to load PDF: {label} is encrypted and needs a password (--password)")
+        }
+        PdfLoadReason::WrongPassword => format!("Failed to load PDF: {label}: wrong password"),
+        PdfLoadReason::UnsupportedSecurity => {
+            format!("Failed to load PDF: {label}: its security handler is not supported")
+        }
+        PdfLoadReason::Corrupt => format!("Failed to load PDF: {label} is corrupt or not a PDF"),
+        PdfLoadReason::Unreadable => format!("Failed to load PDF: {label} could not be read"),
+        PdfLoadReason::Other(e) => format!("Failed to load PDF: {label}: {e}"),
+    }
+}
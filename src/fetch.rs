@@ -0,0 +1,83 @@
+//! Remote (`http(s)://`) and gzip-compressed inputs, read into memory.
+
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+
+/// Downloads larger than this are refused.
+pub const MAX_DOWNLOAD_BYTES: u64 = 200 * 1024 * 1024;
+/// Cap on decompressed size, so a gzip bomb can't exhaust memory.
+pub const MAX_GUNZIP_BYTES: u64 = 1024 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// True for `http://` and `https://` arguments.
+pub fn is_url(file: &Path) -> bool {
+    file.to_str()
+        .is_some_and(|s| s.starts_with("http://") || s.starts_with("https://"))
+}
+
+/// True for `.gz` inputs (`paper.pdf.gz`, `refs.txt.gz`).
+pub fn is_gzip(file: &Path) -> bool {
+    file.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Fetch a URL into memory, failing on HTTP errors, timeouts and bodies
+/// over `MAX_DOWNLOAD_BYTES`.
+pub fn download(url: &str) -> Result<Vec<u8>> {
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(DOWNLOAD_TIMEOUT))
+        .build()
+        .new_agent();
+    let resp = agent
+        .get(url)
+        .call()
+        .with_context(|| format!("Failed to download {url}"))?;
+    resp.into_body()
+        .with_config()
+        .limit(MAX_DOWNLOAD_BYTES)
+        .read_to_vec()
+        .with_context(|| format!("Failed to read response body from {url}"))
+}
+
+/// Decompress a gzip buffer. `label` names the source in error messages.
+pub fn gunzip(bytes: &[u8], label: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    GzDecoder::new(bytes)
+        .take(MAX_GUNZIP_BYTES + 1)
+        .read_to_end(&mut out)
+        .with_context(|| format!("Failed to decompress {label}"))?;
+    if out.len() as u64 > MAX_GUNZIP_BYTES {
+        anyhow::bail!("{label} decompresses to more than {MAX_GUNZIP_BYTES} bytes");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    #[test]
+    fn gunzip_round_trip() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"%PDF-1.4 not really").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(gunzip(&compressed, "x.pdf.gz").unwrap(), b"%PDF-1.4 not really");
+        let err = gunzip(b"plain bytes", "x.pdf.gz").unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to decompress x.pdf.gz"));
+    }
+
+    #[test]
+    fn input_kinds() {
+        assert!(is_url(Path::new("https://arxiv.org/pdf/1001.0785")));
+        assert!(is_url(Path::new("http://example.org/a.pdf")));
+        assert!(!is_url(Path::new("paper.pdf")));
+        assert!(is_gzip(Path::new("paper.pdf.gz")));
+        assert!(!is_gzip(Path::new("paper.pdf")));
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -14,8 +15,10 @@ struct TrieNode {
 
 struct TrieLeaf {
     standardized: String,
-    /// Matches the numeration part that follows the prefix: `[\s\-/]*(?:alt1|alt2|…)`.
-    numeration_re: Regex,
+    /// One regex per numeration that may follow the prefix, each
+    /// `^[\s\-/]*(?:num)` plus an optional version/revision tail. Shared by
+    /// all prefixes declared under the same numerations.
+    numerations: Arc<[Regex]>,
 }
 
 pub struct ReportNumberTrie {
@@ -24,7 +27,12 @@ pub struct ReportNumberTrie {
 
 pub struct ReportNumberMatch {
     pub matched: String,
+    /// Standardized prefix: "CERN-TH".
     pub standardized: String,
+    /// Standardized prefix plus the numeration: "CERN-TH-2000-123".
+    pub number: String,
+    /// Byte offset of `matched` in the searched text.
+    pub start: usize,
 }
 
 impl TrieNode {
@@ -49,6 +57,25 @@ impl ReportNumberTrie {
         None
     }
 
+    /// Every non-overlapping report number in `text`, left to right:
+    /// "SLAC-PUB-8587, CERN-TH-2000-123" has two.
+    pub fn find_all_matches(&self, text: &str) -> Vec<ReportNumberMatch> {
+        let bytes = text.as_bytes();
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start < bytes.len() {
+            if (start == 0 || !bytes[start - 1].is_ascii_alphanumeric())
+                && let Some(m) = self.try_match_at(text, start)
+            {
+                start += m.matched.len().max(1);
+                matches.push(m);
+                continue;
+            }
+            start += 1;
+        }
+        matches
+    }
+
     fn try_match_at(&self, text: &str, start: usize) -> Option<ReportNumberMatch> {
         let bytes = text.as_bytes();
         let mut node = &self.root;
@@ -100,15 +127,22 @@ fn try_leaves(
 ) -> Option<ReportNumberMatch> {
     let suffix = &text[pos..];
     for leaf in leaves {
-        if let Some(m) = leaf.numeration_re.find(suffix) {
-            // Only accept match anchored at position 0 in suffix.
-            if m.start() == 0 {
-                let matched = text[start..pos + m.end()].to_string();
-                return Some(ReportNumberMatch {
-                    matched,
-                    standardized: leaf.standardized.clone(),
-                });
-            }
+        // Longest numeration wins, so "2018-123" isn't cut short at "2018"
+        // by a shorter pattern listed first.
+        let end = leaf
+            .numerations
+            .iter()
+            .filter_map(|re| re.find(suffix))
+            .map(|m| m.end())
+            .max();
+        if let Some(end) = end {
+            let numeration = suffix[..end].trim_start_matches([' ', '\t', '-', '/']);
+            return Some(ReportNumberMatch {
+                matched: text[start..pos + end].to_string(),
+                standardized: leaf.standardized.clone(),
+                number: format!("{}-{numeration}", leaf.standardized),
+                start,
+            });
         }
     }
     None
@@ -117,7 +151,8 @@ fn try_leaves(
 /// Build the report-number trie from KB text.
 pub fn build_report_trie(kb_text: &str) -> ReportNumberTrie {
     let mut root = TrieNode::new();
-    let mut current_numerations: Vec<String> = Vec::new();
+    let mut current_numerations: Vec<Regex> = Vec::new();
+    let mut shared: Arc<[Regex]> = Arc::new([]);
 
     for line in kb_text.lines() {
         let line = line.trim();
@@ -126,28 +161,33 @@ pub fn build_report_trie(kb_text: &str) -> ReportNumberTrie {
         }
         if line.starts_with('<') && line.ends_with('>') {
             let inner = &line[1..line.len() - 1];
-            if let Some(regex_str) = numeration_to_regex(inner) {
-                current_numerations.push(regex_str);
+            if let Some(re) = numeration_to_regex(inner).and_then(|r| compile_numeration(&r)) {
+                current_numerations.push(re);
+                shared = current_numerations.clone().into();
             }
             continue;
         }
         if let Some((prefix, standardized)) = line.split_once("---") {
-            insert_into_trie(
-                &mut root,
-                prefix.trim(),
-                standardized.trim(),
-                &current_numerations,
-            );
+            insert_into_trie(&mut root, prefix.trim(), standardized.trim(), &shared);
         }
     }
     ReportNumberTrie { root }
 }
 
+/// Anchor a numeration to the start of the text after the prefix, with an
+/// optional version ("-v2") or revision (" Rev. 2") tail.
+fn compile_numeration(numeration: &str) -> Option<Regex> {
+    Regex::new(&format!(
+        r"(?i)^[\s\-/]*(?:{numeration})(?:-?v\d+\b|\s*Rev\.?\s*\d+\b)?"
+    ))
+    .ok()
+}
+
 fn insert_into_trie(
     root: &mut TrieNode,
     prefix: &str,
     standardized: &str,
-    numerations: &[String],
+    numerations: &Arc<[Regex]>,
 ) {
     if numerations.is_empty() {
         return;
@@ -165,15 +205,10 @@ fn insert_into_trie(
         node = node.children.entry(byte).or_insert_with(|| Box::new(TrieNode::new()));
     }
 
-    // Build numeration regex anchored to start of remaining text.
-    let num_alt = numerations.join("|");
-    let pattern = format!(r"(?i)^[\s\-/]*(?:{num_alt})");
-    if let Ok(re) = Regex::new(&pattern) {
-        node.leaves.push(TrieLeaf {
-            standardized: standardized.to_string(),
-            numeration_re: re,
-        });
-    }
+    node.leaves.push(TrieLeaf {
+        standardized: standardized.to_string(),
+        numerations: Arc::clone(numerations),
+    });
 }
 
 /// Compiled report-number trie (replaces sequential REPORT_NUMBERS scan).
@@ -187,6 +222,7 @@ const _KB_HASH: &str = env!("KB_HASH");
 static JOURNAL_TITLES_KB: &str = include_str!("../kbs/journal-titles.kb");
 static REPORT_NUMBERS_KB: &str = include_str!("../kbs/report-numbers.kb");
 static COLLABORATIONS_KB: &str = include_str!("../kbs/collaborations.kb");
+static PUBLISHERS_KB: &str = include_str!("../kbs/publishers.kb");
 
 /// Journal title mapping: normalized full name → abbreviated name.
 /// Keys are normalized (dots stripped, whitespace collapsed, uppercased)
@@ -204,7 +240,7 @@ pub static JOURNAL_TITLES: Lazy<Vec<(String, String)>> = Lazy::new(|| {
             Some((normalize_abbrev(full.trim()), abbrev.trim().to_string()))
         })
         .collect();
-    entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.0.len()));
     entries
 });
 
@@ -223,25 +259,25 @@ pub static JOURNAL_ABBREVS: Lazy<Vec<(String, String)>> = Lazy::new(|| {
             let abbrev = abbrev.trim();
             // Normalize: "Phys. Rev. D" → "PHYS REV D" for matching
             let normalized = normalize_abbrev(abbrev);
-            // Skip short abbreviations — too many false positives
-            // e.g., "EN" matches "Witten,", "PR" matches "er," in author names
-            // Require at least 3 chars (e.g., "PoS", "JHEP", "JCAP" ok, "EN" "PR" not)
-            if normalized.len() < 3 || !seen.insert(normalized.clone()) {
+            // Short forms ("PR", "NC") are only accepted before numeration;
+            // see `is_journal_boundary`.
+            if !seen.insert(normalized.clone()) {
                 return None;
             }
             Some((normalized, abbrev.to_string()))
         })
         .collect();
-    entries.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.0.len()));
     entries
 });
 
 /// Normalize an abbreviated journal name for matching.
 /// "Phys. Rev. D" → "PHYS REV D"
 /// "Phys.Rev.D" → "PHYS REV D"  (dots act as word separators)
+/// "Pis’ma Zh. Éksp." → "PIS MA ZH EKSP"  (as transliterated in the KB)
 fn normalize_abbrev(s: &str) -> String {
     s.chars()
-        .map(|c| if c == '.' || c == ':' { ' ' } else { c })
+        .map(|c| if is_abbrev_separator(c) { ' ' } else { fold_accent(c) })
         .collect::<String>()
         .split_whitespace()
         .collect::<Vec<_>>()
@@ -249,9 +285,120 @@ fn normalize_abbrev(s: &str) -> String {
         .to_uppercase()
 }
 
-/// Collaboration name mapping: uppercase name → standardized name.
-pub static COLLABORATIONS: Lazy<HashMap<String, String>> = Lazy::new(|| {
-    COLLABORATIONS_KB
+/// Word separators in journal names: whitespace, dots, colons, dashes
+/// ("Sov. Phys.–JETP") and apostrophes, which the KB writes as spaces.
+fn is_abbrev_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '.' | ':' | '-' | '–' | '—' | '\'' | '’' | '‘')
+}
+
+/// Strip the accent from a Latin letter, so "Éksp." matches "EKSP".
+fn fold_accent(c: char) -> char {
+    const FOLDS: &[(&str, char)] = &[
+        ("àáâãä", 'a'),
+        ("ÀÁÂÃÄ", 'A'),
+        ("èéêë", 'e'),
+        ("ÈÉÊË", 'E'),
+        ("ìíîï", 'i'),
+        ("ÌÍÎÏ", 'I'),
+        ("òóôõö", 'o'),
+        ("ÒÓÔÕÖ", 'O'),
+        ("ùúûü", 'u'),
+        ("ÙÚÛÜ", 'U'),
+    ];
+    if c.is_ascii() {
+        return c;
+    }
+    FOLDS.iter().find(|(from, _)| from.contains(c)).map_or(c, |&(_, to)| to)
+}
+
+/// The baked-in collaboration names.
+pub static COLLABORATIONS: Lazy<CollaborationKb> = Lazy::new(|| {
+    let mut kb = CollaborationKb { entries: Vec::new() };
+    kb.extend_from_kb(COLLABORATIONS_KB);
+    kb
+});
+
+/// Most words in a collaboration name; the baked-in KB's run to six.
+pub(crate) const MAX_COLLABORATION_WORDS: usize = 8;
+
+/// Collaboration name mapping: normalized name (see `normalize_words`) →
+/// standardized name, sorted by key length descending so the longest name
+/// wins. Built once from the baked-in KB plus any `--collaborations-kb`.
+#[derive(Debug, Clone)]
+pub struct CollaborationKb {
+    entries: Vec<(String, String)>,
+}
+
+impl CollaborationKb {
+    /// The baked-in KB with the `name---standardized` lines of `kb_text`
+    /// added; a user entry replaces a baked-in one with the same name.
+    pub fn with_extra(kb_text: &str) -> Self {
+        let mut kb = COLLABORATIONS.clone();
+        kb.extend_from_kb(kb_text);
+        kb
+    }
+
+    /// Standardized name of the collaboration `text` names, all of it:
+    /// "ATLAS Collaboration" but not "G. Aad, ATLAS Collaboration".
+    pub fn get(&self, text: &str) -> Option<&str> {
+        let name = normalize_words(text);
+        self.entries.iter().find(|(n, _)| *n == name).map(|(_, standardized)| standardized.as_str())
+    }
+
+    /// Byte span and standardized name of the longest run of whole words
+    /// in `text` naming a collaboration; brackets and punctuation at either
+    /// end stay outside the span.
+    pub fn find(&self, text: &str) -> Option<(usize, usize, &str)> {
+        let words: Vec<(usize, usize)> = text
+            .split_whitespace()
+            .map(|w| {
+                let start = w.as_ptr() as usize - text.as_ptr() as usize;
+                (start, start + w.len())
+            })
+            .collect();
+        let mut best: Option<(usize, usize, &str)> = None;
+        for first in 0..words.len() {
+            for last in first..words.len().min(first + MAX_COLLABORATION_WORDS) {
+                let (start, end) = (words[first].0, words[last].1);
+                if best.is_some_and(|(s, e, _)| end - start <= e - s) {
+                    continue;
+                }
+                if let Some(name) = self.get(&text[start..end]) {
+                    best = Some((start, end, name));
+                }
+            }
+        }
+        let (start, end, name) = best?;
+        let span = &text[start..end];
+        let trimmed = span.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let start = start + span.len() - trimmed.len();
+        let end = start + trimmed.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+        Some((start, end, name))
+    }
+
+    fn extend_from_kb(&mut self, kb_text: &str) {
+        for line in kb_text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, standardized)) = line.split_once("---") else { continue };
+            let name = normalize_words(name.trim());
+            if name.is_empty() {
+                continue;
+            }
+            self.entries.retain(|(n, _)| *n != name);
+            self.entries.push((name, standardized.trim().to_string()));
+        }
+        self.entries.sort_by_key(|e| std::cmp::Reverse(e.0.len()));
+    }
+}
+
+/// Book publisher mapping: normalized name → standardized name.
+/// Keys use `normalize_words` and are sorted longest first, so
+/// "John Wiley & Sons" wins over "Wiley".
+pub static PUBLISHERS: Lazy<Vec<(String, String)>> = Lazy::new(|| {
+    let mut entries: Vec<(String, String)> = PUBLISHERS_KB
         .lines()
         .filter_map(|line| {
             let line = line.trim();
@@ -259,11 +406,25 @@ pub static COLLABORATIONS: Lazy<HashMap<String, String>> = Lazy::new(|| {
                 return None;
             }
             let (name, standardized) = line.split_once("---")?;
-            Some((name.trim().to_uppercase(), standardized.trim().to_string()))
+            Some((normalize_words(name.trim()), standardized.trim().to_string()))
         })
-        .collect()
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.0.len()));
+    entries
 });
 
+/// Uppercase, turn dots and list punctuation into spaces, collapse whitespace.
+/// "Cambridge Univ. Press, (1995)" → "CAMBRIDGE UNIV PRESS 1995"
+fn normalize_words(s: &str) -> String {
+    s.chars()
+        .map(|c| if matches!(c, '.' | ',' | ';' | ':' | '(' | ')' | '[' | ']') { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
 /// A report number pattern: institute prefix + compiled regex for numeration.
 #[allow(dead_code)]
 pub struct ReportNumberPattern {
@@ -466,7 +627,8 @@ fn emit_literal(ch: char, out: &mut String) -> usize {
 
 /// Try to match a journal name at the given byte position in text.
 /// Returns (matched_byte_length, abbreviated_name) if found.
-/// Tries both full names and abbreviated forms.
+/// Tries both full names and abbreviated forms, and takes in a
+/// parenthesized supplement series: "Nucl. Phys. B (Proc. Suppl.) 140".
 pub fn match_journal_name(text: &str, pos: usize) -> Option<(usize, String)> {
     if !text.is_char_boundary(pos) {
         return None;
@@ -477,8 +639,23 @@ pub fn match_journal_name(text: &str, pos: usize) -> Option<(usize, String)> {
         return None;
     }
     let suffix = &text[pos..];
-    match_full_journal(suffix)
-        .or_else(|| match_abbrev_journal(suffix))
+    let (len, abbrev) = match_full_journal(suffix).or_else(|| match_abbrev_journal(suffix))?;
+    Some(absorb_series(suffix, len).unwrap_or((len, abbrev)))
+}
+
+/// A series in parentheses right after a journal name: "(Proc. Suppl.)".
+static SERIES_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*\(((?:Proc\.?\s*)?Suppl\.?)\)").unwrap());
+
+/// When a journal match of `len` bytes is followed by a series such as
+/// "(Proc. Suppl.)", match the name again with the parentheses dropped
+/// ("Nucl. Phys. B Proc. Suppl."). If the KB knows that journal, return
+/// it with the length extended over the closing parenthesis.
+fn absorb_series(suffix: &str, len: usize) -> Option<(usize, String)> {
+    let caps = SERIES_RE.captures(&suffix[len..])?;
+    let joined = format!("{} {}", &suffix[..len], &caps[1]);
+    let (joined_len, abbrev) = match_full_journal(&joined).or_else(|| match_abbrev_journal(&joined))?;
+    (joined_len == joined.len()).then(|| (len + caps[0].len(), abbrev))
 }
 
 fn match_full_journal(suffix: &str) -> Option<(usize, String)> {
@@ -526,7 +703,19 @@ fn match_abbrev_journal(suffix: &str) -> Option<(usize, String)> {
 /// a trailing period was consumed (abbreviation end like "Lett.74"),
 /// or the match ends with a section letter directly followed by a digit
 /// (e.g., "Chin. Phys. C40" — section letter "C" + volume "40").
+///
+/// Two-letter forms like "PR" or "NC" are also initials, name parts and
+/// state codes ("PR Smith", "Pittsburgh PA 15213", "SC 2019"), so they
+/// only count when a volume and a page follow: "PR 123, 45",
+/// "NP B12 (1970) 3".
 fn is_journal_boundary(suffix: &str, match_len: usize) -> bool {
+    let letters = suffix[..match_len.min(suffix.len())]
+        .bytes()
+        .filter(u8::is_ascii_alphanumeric)
+        .count();
+    if letters <= 2 && !numeration_follows(&suffix[match_len.min(suffix.len())..]) {
+        return false;
+    }
     if match_len >= suffix.len() {
         return true;
     }
@@ -550,31 +739,44 @@ fn is_journal_boundary(suffix: &str, match_len: usize) -> bool {
     false
 }
 
-/// Find how many bytes in the original string correspond to N normalized chars.
-/// Normalized form treats dots, colons, and whitespace as word separators,
-/// collapsing consecutive separators to a single space.
+/// Volume (optionally with a section letter), an optional year in
+/// parentheses, then a page.
+static VOLUME_PAGE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*[A-Z]?(\d+)(?:\s*\(\d{4}\))?(?:\s*[,:]\s*|\s+)(?:pp?\.\s*)?[A-Z]?\d").unwrap()
+});
+
+/// True when `rest` starts with a volume and a page. A volume that looks
+/// like a year or a ZIP code doesn't count.
+fn numeration_follows(rest: &str) -> bool {
+    let Some(volume) = VOLUME_PAGE_RE.captures(rest).map(|caps| caps[1].to_string()) else {
+        return false;
+    };
+    let year_like = volume.len() == 4 && matches!(&volume[..2], "18" | "19" | "20");
+    volume.len() <= 4 && !year_like
+}
+
+/// Find how many bytes in the original string correspond to N bytes of its
+/// `normalize_abbrev` form, which collapses runs of separators to a single
+/// space and may change a char's UTF-8 length (accents, uppercasing).
 fn find_original_byte_len(original: &str, norm_len: usize) -> usize {
     let mut norm_pos = 0;
-    let mut orig_pos = 0;
-    let bytes = original.as_bytes();
+    let mut chars = original.char_indices().peekable();
 
-    while orig_pos < bytes.len() && norm_pos < norm_len {
-        let ch = bytes[orig_pos];
-        // Dots, colons, and whitespace are word separators; normalize to single space
-        if ch == b'.' || ch == b':' || ch == b' ' || ch == b'\t' {
+    while norm_pos < norm_len
+        && let Some(&(_, ch)) = chars.peek()
+    {
+        if is_abbrev_separator(ch) {
             if norm_pos > 0 {
                 norm_pos += 1;
             }
-            while orig_pos < bytes.len()
-                && matches!(bytes[orig_pos], b'.' | b':' | b' ' | b'\t')
-            {
-                orig_pos += 1;
-            }
+            while chars.next_if(|&(_, c)| is_abbrev_separator(c)).is_some() {}
             continue;
         }
-        norm_pos += 1;
-        orig_pos += 1;
+        norm_pos += fold_accent(ch).to_uppercase().map(char::len_utf8).sum::<usize>();
+        chars.next();
     }
+    let mut orig_pos = chars.peek().map_or(original.len(), |&(i, _)| i);
+    let bytes = original.as_bytes();
     // Skip trailing dots only (not spaces — space is the word boundary)
     while orig_pos < bytes.len() && bytes[orig_pos] == b'.' {
         orig_pos += 1;
@@ -582,26 +784,43 @@ fn find_original_byte_len(original: &str, norm_len: usize) -> usize {
     orig_pos
 }
 
-/// Try to match a collaboration name in the text.
-pub fn match_collaboration(text: &str) -> Option<String> {
-    let upper = text.to_uppercase();
-    COLLABORATIONS
+/// Try to match a collaboration name in the text (whole words only, so
+/// "CMS" doesn't fire inside "ECMS").
+pub fn match_collaboration(kb: &CollaborationKb, text: &str) -> Option<String> {
+    let padded = format!(" {} ", normalize_words(text));
+    kb.entries
+        .iter()
+        .find(|(name, _)| padded.contains(&format!(" {name} ")))
+        .map(|(_, standardized)| standardized.clone())
+}
+
+/// Try to match a book publisher in the text (whole words only).
+pub fn match_publisher(text: &str) -> Option<String> {
+    let padded = format!(" {} ", normalize_words(text));
+    PUBLISHERS
         .iter()
-        .find(|(name, _)| upper.contains(name.as_str()))
+        .find(|(name, _)| padded.contains(&format!(" {name} ")))
         .map(|(_, standardized)| standardized.clone())
 }
 
-/// Try to match a report number in the text.
-/// Returns (matched_text, standardized_prefix).
-pub fn match_report_number(text: &str) -> Option<(String, String)> {
-    REPORT_NUMBER_TRIE
-        .find_match(text)
-        .map(|m| (m.matched, m.standardized))
+/// Whether the text begins with a book publisher name.
+pub fn starts_with_publisher(text: &str) -> bool {
+    let padded = format!("{} ", normalize_words(text));
+    PUBLISHERS
+        .iter()
+        .any(|(name, _)| padded.starts_with(&format!("{name} ")))
+}
+
+/// Every report number in the text, left to right.
+pub fn match_report_numbers(text: &str) -> Vec<ReportNumberMatch> {
+    REPORT_NUMBER_TRIE.find_all_matches(text)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse::ParseOptions;
+    use crate::types::{RawReference, ReferenceSource};
 
     fn trie() -> ReportNumberTrie {
         build_report_trie(REPORT_NUMBERS_KB)
@@ -655,4 +874,88 @@ mod tests {
         let m = m.expect("should match FERMILAB  PUB with double space");
         assert_eq!(m.standardized, "FERMILAB-Pub");
     }
+
+    #[test]
+    fn version_and_revision_suffixes() {
+        let t = trie();
+        let m = t.find_match("ATLAS, CERN-EP-2018-123-v2, submitted").expect("versioned");
+        assert_eq!(m.matched, "CERN-EP-2018-123-v2");
+        let m = t.find_match("SLAC-PUB-8587 Rev. 2 (2001)").expect("revised");
+        assert_eq!(m.matched, "SLAC-PUB-8587 Rev. 2");
+        // A following journal is not swallowed
+        let m = t.find_match("SLAC-PUB-8587 Rev. Mod. Phys. 72, 1").expect("plain");
+        assert_eq!(m.matched, "SLAC-PUB-8587");
+        let m = t.find_match("SLAC-PUB-8587 version 2").expect("plain");
+        assert_eq!(m.matched, "SLAC-PUB-8587");
+    }
+
+    #[test]
+    fn every_report_number_is_found() {
+        let text = "SLAC-PUB-8587, CERN-TH-2000-123 (2000)";
+        let found: Vec<(usize, String)> =
+            trie().find_all_matches(text).into_iter().map(|m| (m.start, m.matched)).collect();
+        assert_eq!(found, [(0, "SLAC-PUB-8587".to_string()), (15, "CERN-TH-2000-123".to_string())]);
+
+        let r = &crate::parse_one(&format!("A. Author, {text}"))[0];
+        assert_eq!(r.report_number.as_deref(), Some("SLAC-PUB-8587"));
+        assert_eq!(r.report_numbers, ["SLAC-PUB-8587", "CERN-TH-2000-123"]);
+        // Separators after the prefix are standardized, the numeration kept
+        let r = &crate::parse_one("A. Author, CERN-TH/2000-124, CERN TH 2000-125")[0];
+        assert_eq!(r.report_numbers, ["CERN-TH-2000-124", "CERN-TH-2000-125"]);
+    }
+
+    #[test]
+    fn two_letter_journals_need_numeration() {
+        assert_eq!(match_journal_name("PR 123, 45 (1961)", 0), Some((2, "Phys. Rev.".to_string())));
+        assert_eq!(match_journal_name("NC 14, 951", 0), Some((2, "Nuovo Cimento".to_string())));
+        assert_eq!(match_journal_name("NP B12 (1970) 3", 0).map(|m| m.1).as_deref(), Some("Nucl. Phys. B"));
+        assert_eq!(match_journal_name("PR Smith, Title", 0), None);
+        assert_eq!(match_journal_name("NC, 1999", 0), None);
+        assert_eq!(match_journal_name("PR", 0), None);
+        // A volume alone, a year or a ZIP code is no citation
+        assert_eq!(match_journal_name("PT 2", 0), None);
+        assert_eq!(match_journal_name("SC 2019, Denver", 0), None);
+        assert_eq!(match_journal_name("PA 15213, USA", 0), None);
+        assert_eq!(match_journal_name("E. Witten, Nucl", 5), None);
+    }
+
+    #[test]
+    fn parenthesized_supplement_joins_the_journal() {
+        let text = "Nucl. Phys. B (Proc. Suppl.) 140, 835 (2005)";
+        assert_eq!(
+            match_journal_name(text, 0),
+            Some(("Nucl. Phys. B (Proc. Suppl.)".len(), "Nucl. Phys. B, Proc. Suppl.".to_string()))
+        );
+        // Unknown series stay outside the match.
+        assert_eq!(match_journal_name("Phys. Rev. D (Suppl.) 72, 1", 0).map(|m| m.0), Some("Phys. Rev. D".len()));
+
+        let refs = crate::parse_one(&format!("A. Author, {text}"));
+        assert_eq!(refs[0].journal_title.as_deref(), Some("Nucl. Phys. B, Proc. Suppl."));
+        assert_eq!(refs[0].journal_volume.as_deref(), Some("140"));
+        assert_eq!(refs[0].journal_page.as_deref(), Some("835"));
+        assert_eq!(refs[0].journal_year.as_deref(), Some("2005"));
+    }
+
+    #[test]
+    fn custom_collaborations_are_merged() {
+        let kb = CollaborationKb::with_extra("# local additions\nDUNE Collaboration---DUNE Collaboration\n");
+        assert_eq!(match_collaboration(&kb, "DUNE Collaboration").as_deref(), Some("DUNE Collaboration"));
+        assert_eq!(match_collaboration(&COLLABORATIONS, "DUNE Collaboration"), None);
+        // The baked-in entries are still there, matched on whole words only
+        assert_eq!(match_collaboration(&kb, "the CMS collaboration").as_deref(), Some("CMS Collaboration"));
+        assert_eq!(match_collaboration(&kb, "ECMS Collaboration"), None);
+
+        let raw = RawReference {
+            text: "B. Abi et al. (DUNE Collaboration), JINST 15, T08008 (2020)".into(),
+            linemarker: None,
+            from_marker: None,
+            source: ReferenceSource::Text,
+            page_num: 0,
+            section: 0,
+            italic_runs: Vec::new(),
+        };
+        let opts = ParseOptions { collaborations: &kb, ..Default::default() };
+        let refs = crate::parse_all_references(std::slice::from_ref(&raw), &opts);
+        assert_eq!(refs[0].collaboration.as_deref(), Some("DUNE Collaboration"));
+    }
 }
@@ -1,37 +1,152 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::ocr::OCR_FONT_NAME;
+use crate::tokenizer::{ARXIV_OLD_ARCHIVES, normalize_dashes};
 use crate::types::{Block, Line, PageChars, Word};
 
+/// Font-size and position thresholds for layout and zone classification.
+/// The defaults suit typical LaTeX output; papers with unusual font
+/// scaling may need them adjusted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutConfig {
+    /// A word smaller than this fraction of the page's dominant font size
+    /// is superscript.
+    pub superscript_ratio: f32,
+    /// Blocks starting above this fraction of the page height are headers.
+    pub header_band: f32,
+    /// Blocks ending below this fraction of the page height may be page numbers.
+    pub page_number_band: f32,
+    /// Blocks ending below this fraction of the page height may be footnotes.
+    pub footnote_band: f32,
+    /// Footnotes are set smaller than this fraction of the body font size.
+    pub footnote_font_ratio: f32,
+    /// A gap between chars wider than this fraction of the average char
+    /// width at their font size starts a new word. Lower it for
+    /// tightly kerned fonts that glue words together.
+    pub word_gap_ratio: f32,
+    /// A vertical jump larger than this fraction of the dominant font size
+    /// starts a new word.
+    pub line_jump_ratio: f32,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            superscript_ratio: 0.75,
+            header_band: 0.95,
+            page_number_band: 0.03,
+            footnote_band: 0.25,
+            footnote_font_ratio: 0.9,
+            word_gap_ratio: 0.3,
+            line_jump_ratio: 0.5,
+        }
+    }
+}
+
 /// Group characters into words, lines, and blocks for a single page.
-pub fn group_page(page: &PageChars) -> Vec<Block> {
+pub fn group_page(page: &PageChars, config: &LayoutConfig) -> Vec<Block> {
     if page.chars.is_empty() {
         return Vec::new();
     }
+    let lines = split_columns(page_lines(page, config), page.width);
+    group_lines_into_blocks(&lines)
+}
+
+/// x-coordinate of the gap `group_page` splits a two-column page at, or
+/// None for a single-column page (`--debug-layout`).
+pub fn column_boundary(page: &PageChars, config: &LayoutConfig) -> Option<f32> {
+    if page.chars.is_empty() {
+        return None;
+    }
+    detect_column_boundary(&page_lines(page, config), page.width)
+}
 
-    let avg_char_width = compute_avg_char_width(page);
+/// Lines of a page in top-to-bottom order, before column splitting.
+fn page_lines(page: &PageChars, config: &LayoutConfig) -> Vec<Line> {
+    let char_widths = CharWidths::of(page);
     let dominant_font_size = compute_dominant_font_size(page);
 
-    let words = group_chars_into_words(&page.chars, avg_char_width, dominant_font_size);
-    let lines = group_words_into_lines(&words);
-    let lines = split_columns(lines, page.width);
-    group_lines_into_blocks(&lines)
+    let words = group_chars_into_words(&page.chars, &char_widths, dominant_font_size, config);
+    let mut lines = group_words_into_lines(&words);
+    repair_ocr_arxiv_slashes(&mut lines);
+    strip_margin_line_numbers(lines, page.width)
 }
 
-fn compute_avg_char_width(page: &PageChars) -> f32 {
-    let widths: Vec<f32> = page
-        .chars
-        .iter()
-        .filter(|c| c.width > 0.0)
-        .map(|c| c.width)
-        .collect();
-    if widths.is_empty() {
-        return 5.0;
+/// Tesseract often reads the slash of an old-style arXiv ID as "|", "l",
+/// "I" or "\": "hep-ph|0202058", or "hep-ph" and "|0202058" as two words.
+/// Put the slash back in OCR'd words so the tokenizer recognizes the ID.
+/// Text-layer words are left alone; there "l0202058" is what the PDF says.
+fn repair_ocr_arxiv_slashes(lines: &mut [Line]) {
+    static CATEGORY: Lazy<String> =
+        Lazy::new(|| format!(r"(?:{ARXIV_OLD_ARCHIVES})(?:[.\-][a-zA-Z]{{2,4}})?"));
+    static JOINED_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(&format!(r"^({})[|lI\\](\d{{7}}\S*)$", *CATEGORY)).unwrap());
+    static NUMBER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[|lI\\](\d{7}\S*)$").unwrap());
+    static CATEGORY_RE: Lazy<Regex> = Lazy::new(|| Regex::new(&format!(r"^{}$", *CATEGORY)).unwrap());
+
+    for line in lines {
+        for i in 0..line.words.len() {
+            if line.words[i].font_name != OCR_FONT_NAME {
+                continue;
+            }
+            let text = &line.words[i].text;
+            let repaired = if let Some(c) = JOINED_RE.captures(text) {
+                format!("{}/{}", &c[1], &c[2])
+            } else if let Some(c) = NUMBER_RE.captures(text)
+                && i > 0
+                && CATEGORY_RE.is_match(&line.words[i - 1].text)
+            {
+                format!("/{}", &c[1])
+            } else {
+                continue;
+            };
+            line.words[i].text = repaired;
+        }
     }
-    widths.iter().sum::<f32>() / widths.len() as f32
+}
+
+/// Average char width per font size (bucketed to 0.1pt), so superscripts
+/// and footnotes are measured against chars of their own size.
+struct CharWidths {
+    by_size: Vec<(i32, f32)>,
+    /// Page-wide average, for sizes without any measurable char.
+    overall: f32,
+}
+
+impl CharWidths {
+    fn of(page: &PageChars) -> Self {
+        // (size, width sum, char count)
+        let mut sums: Vec<(i32, f32, usize)> = Vec::new();
+        for c in page.chars.iter().filter(|c| c.width > 0.0) {
+            let key = size_key(c.font_size);
+            if let Some(entry) = sums.iter_mut().find(|(k, _, _)| *k == key) {
+                entry.1 += c.width;
+                entry.2 += 1;
+            } else {
+                sums.push((key, c.width, 1));
+            }
+        }
+        let (total, count) = sums.iter().fold((0.0, 0), |(t, n), &(_, w, k)| (t + w, n + k));
+        let overall = if count == 0 { 5.0 } else { total / count as f32 };
+        let by_size = sums.into_iter().map(|(key, w, n)| (key, w / n as f32)).collect();
+        CharWidths { by_size, overall }
+    }
+
+    fn at(&self, font_size: f32) -> f32 {
+        let key = size_key(font_size);
+        self.by_size.iter().find(|(k, _)| *k == key).map_or(self.overall, |&(_, w)| w)
+    }
+}
+
+fn size_key(font_size: f32) -> i32 {
+    (font_size * 10.0) as i32
 }
 
 fn compute_dominant_font_size(page: &PageChars) -> f32 {
     let mut size_counts: Vec<(i32, usize)> = Vec::new();
     for ch in &page.chars {
-        let key = (ch.font_size * 10.0) as i32;
+        let key = size_key(ch.font_size);
         if let Some(entry) = size_counts.iter_mut().find(|(k, _)| *k == key) {
             entry.1 += 1;
         } else {
@@ -45,8 +160,8 @@ fn compute_dominant_font_size(page: &PageChars) -> f32 {
         .unwrap_or(10.0)
 }
 
-fn is_superscript(ch_size: f32, dominant_size: f32) -> bool {
-    ch_size < dominant_size * 0.75
+fn is_superscript(ch_size: f32, dominant_size: f32, ratio: f32) -> bool {
+    ch_size < dominant_size * ratio
 }
 
 struct WordAccum {
@@ -56,12 +171,25 @@ struct WordAccum {
     max_x: f32,
     max_y: f32,
     font_size: f32,
+    is_italic: bool,
+    /// Char count per font name, in order of first use.
+    font_counts: Vec<(String, usize)>,
     prev_right: f32,
 }
 
 impl WordAccum {
     fn new() -> Self {
-        Self { text: String::new(), x: 0.0, y: 0.0, max_x: 0.0, max_y: 0.0, font_size: 0.0, prev_right: 0.0 }
+        Self {
+            text: String::new(),
+            x: 0.0,
+            y: 0.0,
+            max_x: 0.0,
+            max_y: 0.0,
+            font_size: 0.0,
+            is_italic: false,
+            font_counts: Vec::new(),
+            prev_right: 0.0,
+        }
     }
 
     fn start_char(&mut self, ch: &crate::types::PdfChar) {
@@ -70,53 +198,119 @@ impl WordAccum {
         self.max_x = ch.x + ch.width;
         self.max_y = ch.y + ch.height;
         self.font_size = ch.font_size;
+        self.is_italic = is_italic_font(&ch.font_name);
+        self.count_font(&ch.font_name);
     }
 
     fn extend_char(&mut self, ch: &crate::types::PdfChar) {
         self.max_x = self.max_x.max(ch.x + ch.width);
         self.max_y = self.max_y.max(ch.y + ch.height);
+        self.count_font(&ch.font_name);
+    }
+
+    fn count_font(&mut self, font_name: &str) {
+        match self.font_counts.iter_mut().find(|(name, _)| name == font_name) {
+            Some((_, count)) => *count += 1,
+            None => self.font_counts.push((font_name.to_string(), 1)),
+        }
     }
 
-    fn flush(&mut self, words: &mut Vec<Word>, dominant_font_size: f32) {
+    fn flush(&mut self, words: &mut Vec<Word>, dominant_font_size: f32, superscript_ratio: f32) {
         if self.text.is_empty() {
             return;
         }
         words.push(Word {
-            text: std::mem::take(&mut self.text),
+            text: normalize_dashes(&std::mem::take(&mut self.text)),
             x: self.x,
             y: self.y,
             width: self.max_x - self.x,
             height: self.max_y - self.y,
             font_size: self.font_size,
-            is_superscript: is_superscript(self.font_size, dominant_font_size),
+            is_superscript: is_superscript(self.font_size, dominant_font_size, superscript_ratio),
+            is_italic: self.is_italic,
+            font_name: most_common(std::mem::take(&mut self.font_counts)),
         });
     }
 }
 
+/// The name with the highest count; the first one seen wins ties.
+fn most_common(counts: Vec<(String, usize)>) -> String {
+    let mut best: Option<(String, usize)> = None;
+    for (name, count) in counts {
+        if best.as_ref().is_none_or(|(_, most)| count > *most) {
+            best = Some((name, count));
+        }
+    }
+    best.map(|(name, _)| name).unwrap_or_default()
+}
+
+/// Font of most of the words' text, counted in chars.
+fn dominant_font(words: &[Word]) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for word in words {
+        let chars = word.text.chars().count();
+        match counts.iter_mut().find(|(name, _)| *name == word.font_name) {
+            Some((_, count)) => *count += chars,
+            None => counts.push((word.font_name.clone(), chars)),
+        }
+    }
+    most_common(counts)
+}
+
+/// Italic or oblique font, judged by name: "Times-Italic",
+/// "Helvetica-Oblique", "MinionPro-It", LaTeX's "CMTI10". A subset
+/// prefix ("ABCDEF+") is ignored.
+pub(crate) fn is_italic_font(font_name: &str) -> bool {
+    let name = font_name.rsplit('+').next().unwrap_or(font_name);
+    let lower = name.to_ascii_lowercase();
+    lower.contains("italic")
+        || lower.contains("oblique")
+        || name.ends_with("-It")
+        || name.starts_with("CMTI")
+}
+
+/// Bold font, judged by name: "Times-Bold", "Helvetica-Black",
+/// "MinionPro-Semibold", "Arial-Bd", LaTeX's "CMBX12". A subset prefix is
+/// ignored.
+pub(crate) fn is_bold_font(font_name: &str) -> bool {
+    let name = font_name.rsplit('+').next().unwrap_or(font_name);
+    let lower = name.to_ascii_lowercase();
+    ["bold", "black", "heavy", "demi"].iter().any(|w| lower.contains(w))
+        || name.ends_with("-Bd")
+        || name.starts_with("CMBX")
+        || name.starts_with("SFBX")
+}
+
 fn group_chars_into_words(
     chars: &[crate::types::PdfChar],
-    avg_char_width: f32,
+    char_widths: &CharWidths,
     dominant_font_size: f32,
+    config: &LayoutConfig,
 ) -> Vec<Word> {
+    let superscript_ratio = config.superscript_ratio;
     let mut words = Vec::new();
-    let gap_threshold = avg_char_width * 0.3;
     let mut acc = WordAccum::new();
+    let mut prev_size = 0.0;
 
     for (i, ch) in chars.iter().enumerate() {
         let backward_jump = !acc.text.is_empty() && (ch.x + ch.width) < acc.x;
+        // The smaller size sets the gap: a superscript marker sits closer
+        // to the next word than body chars space their words.
+        let gap_threshold = config.word_gap_ratio * char_widths.at(ch.font_size).min(char_widths.at(prev_size));
         let is_break = i == 0
             || ch.ch == ' '
             || (ch.x - acc.prev_right) > gap_threshold
             || backward_jump
-            || (ch.y - acc.y).abs() > dominant_font_size * 0.5;
+            || (ch.y - acc.y).abs() > dominant_font_size * config.line_jump_ratio;
+        prev_size = ch.font_size;
 
         if ch.ch == ' ' {
-            acc.flush(&mut words, dominant_font_size);
+            acc.flush(&mut words, dominant_font_size, superscript_ratio);
             acc.prev_right = ch.x + ch.width;
             continue;
         }
         if is_break && !acc.text.is_empty() {
-            acc.flush(&mut words, dominant_font_size);
+            acc.flush(&mut words, dominant_font_size, superscript_ratio);
         }
         if acc.text.is_empty() {
             acc.start_char(ch);
@@ -126,7 +320,7 @@ fn group_chars_into_words(
         acc.text.push(ch.ch);
         acc.prev_right = ch.x + ch.width;
     }
-    acc.flush(&mut words, dominant_font_size);
+    acc.flush(&mut words, dominant_font_size, superscript_ratio);
     words
 }
 
@@ -149,6 +343,7 @@ fn group_words_into_lines(words: &[Word]) -> Vec<Line> {
                 x_start: word.x,
                 x_end: word.x + word.width,
                 font_size: word.font_size,
+                font_name: String::new(),
             });
         }
     }
@@ -156,27 +351,94 @@ fn group_words_into_lines(words: &[Word]) -> Vec<Line> {
     // Sort words within each line by x position
     for line in &mut lines {
         line.words.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap());
+        line.font_name = dominant_font(&line.words);
     }
     // Sort lines by y position (top to bottom = high y to low y in PDF coords)
     lines.sort_by(|a, b| b.y.partial_cmp(&a.y).unwrap());
     lines
 }
 
+/// Remove `lineno`-style line numbers from the left margin.
+///
+/// A line number is a 1-4 digit first word in the leftmost 15% of the page,
+/// clearly left of where the body text starts. They count as a margin
+/// column only when they share a right edge (numbers are right-aligned)
+/// and appear on at least 80% of the lines. Bare-number reference markers
+/// only open the first line of each entry, so they stay below that share.
+fn strip_margin_line_numbers(mut lines: Vec<Line>, page_width: f32) -> Vec<Line> {
+    let is_candidate = |line: &Line| {
+        line.words.len() >= 2 && {
+            let w = &line.words[0];
+            (1..=4).contains(&w.text.len())
+                && w.text.bytes().all(|b| b.is_ascii_digit())
+                && w.x < page_width * 0.15
+                && line.words[1].x - (w.x + w.width) > w.font_size * 0.5
+        }
+    };
+    let mut right_edges: Vec<f32> = lines
+        .iter()
+        .filter(|l| is_candidate(l))
+        .map(|l| l.words[0].x + l.words[0].width)
+        .collect();
+    if right_edges.len() < 5 || right_edges.len() * 5 < lines.len() * 4 {
+        return lines;
+    }
+    right_edges.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let column_edge = right_edges[right_edges.len() / 2];
+    let tolerance = lines[0].font_size.max(1.0);
+    let is_margin_number = |line: &Line| {
+        is_candidate(line)
+            && (line.words[0].x + line.words[0].width - column_edge).abs() <= tolerance
+    };
+    let in_column = lines.iter().filter(|l| is_margin_number(l)).count();
+    if in_column * 5 < lines.len() * 4 {
+        return lines;
+    }
+    // Body text must start well to the right of the number column.
+    let mut body_starts: Vec<f32> = lines
+        .iter()
+        .filter_map(|l| if is_margin_number(l) { l.words.get(1) } else { l.words.first() })
+        .map(|w| w.x)
+        .collect();
+    body_starts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if body_starts[body_starts.len() / 2] < column_edge + tolerance {
+        return lines;
+    }
+    for line in &mut lines {
+        if is_margin_number(line) {
+            line.words.remove(0);
+            line.x_start = line.words[0].x;
+        }
+    }
+    lines
+}
+
 /// Detect two-column layout and split lines into reading order.
 ///
 /// If a consistent vertical gap divides the page into two columns,
 /// splits each line at the boundary and returns left-column lines
 /// followed by right-column lines (both top-to-bottom).
+///
+/// A full-width line (a word straddling the gap, e.g. a spanning equation)
+/// ends the current column pair: the columns above it are emitted first,
+/// then the line itself, then column splitting resumes below it.
 fn split_columns(lines: Vec<Line>, page_width: f32) -> Vec<Line> {
     let boundary = detect_column_boundary(&lines, page_width);
     let Some(boundary) = boundary else {
         return lines;
     };
 
+    let mut ordered = Vec::with_capacity(lines.len());
     let mut left_lines = Vec::new();
     let mut right_lines = Vec::new();
 
-    for line in &lines {
+    for line in lines {
+        if crosses_boundary(&line, boundary) {
+            ordered.append(&mut left_lines);
+            ordered.append(&mut right_lines);
+            ordered.push(line);
+            continue;
+        }
         let (left_words, right_words) = partition_words(&line.words, boundary);
         if !left_words.is_empty() {
             left_lines.push(make_line(left_words, line.y, line.font_size));
@@ -186,8 +448,13 @@ fn split_columns(lines: Vec<Line>, page_width: f32) -> Vec<Line> {
         }
     }
 
-    left_lines.extend(right_lines);
-    left_lines
+    ordered.append(&mut left_lines);
+    ordered.append(&mut right_lines);
+    ordered
+}
+
+fn crosses_boundary(line: &Line, boundary: f32) -> bool {
+    line.words.iter().any(|w| w.x < boundary && w.x + w.width > boundary)
 }
 
 /// Find the x-coordinate of a column gap, if the page is two-column.
@@ -274,7 +541,8 @@ fn partition_words(words: &[Word], boundary: f32) -> (Vec<Word>, Vec<Word>) {
 fn make_line(words: Vec<Word>, y: f32, font_size: f32) -> Line {
     let x_start = words.iter().map(|w| w.x).reduce(f32::min).unwrap();
     let x_end = words.iter().map(|w| w.x + w.width).reduce(f32::max).unwrap();
-    Line { words, y, x_start, x_end, font_size }
+    let font_name = dominant_font(&words);
+    Line { words, y, x_start, x_end, font_size, font_name }
 }
 
 fn group_lines_into_blocks(lines: &[Line]) -> Vec<Block> {
@@ -317,3 +585,322 @@ fn update_block_bounds(block: &mut Block) {
     block.width = max_x - min_x;
     block.height = max_y - min_y + block.font_size;
 }
+
+/// A page with fewer letters and digits than this, not counting a filler
+/// phrase, holds at most a page number or a running header.
+const FILLER_PAGE_CHARS: usize = 16;
+/// Leading pages with fewer letters and digits than this are cover pages:
+/// title, author, degree statement. A paper's first page has an abstract.
+const COVER_PAGE_CHARS: usize = 400;
+
+/// Pages to leave out of body font size and zone classification: filler
+/// pages ("This page intentionally left blank", or just a page number)
+/// anywhere, and, when `pages` starts at the document's first page, sparse
+/// cover pages before the first page of running text. Their large, sparse
+/// text would otherwise skew the body font size.
+pub fn filler_pages(pages: &[Vec<Block>], from_first_page: bool) -> Vec<bool> {
+    static FILLER_RE: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)\b(?:this\s+page\s+(?:is\s+|has\s+been\s+)?(?:intentionally|deliberately)\s+left\s+blank|blank\s+page)\b")
+            .unwrap()
+    });
+    let text_chars: Vec<usize> = pages
+        .iter()
+        .map(|blocks| {
+            let text: Vec<String> = blocks.iter().map(|b| b.text()).collect();
+            let text = FILLER_RE.replace_all(&text.join(" "), "").into_owned();
+            text.chars().filter(|c| c.is_alphanumeric()).count()
+        })
+        .collect();
+    let mut filler: Vec<bool> = text_chars.iter().map(|&n| n < FILLER_PAGE_CHARS).collect();
+    // Cover pages only count as such when running text follows them.
+    if from_first_page && let Some(first_text) = text_chars.iter().position(|&n| n >= COVER_PAGE_CHARS) {
+        filler[..first_text].fill(true);
+    }
+    filler
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, x: f32) -> Word {
+        Word {
+            text: text.to_string(),
+            x,
+            y: 0.0,
+            width: text.len() as f32 * 5.0,
+            height: 10.0,
+            font_size: 10.0,
+            is_superscript: false,
+            is_italic: false,
+            font_name: "Times-Roman".into(),
+        }
+    }
+
+    fn line(words: Vec<Word>, y: f32) -> Line {
+        make_line(words, y, 10.0)
+    }
+
+    #[test]
+    fn superscript_ratio_is_configurable() {
+        assert!(!is_superscript(8.0, 10.0, LayoutConfig::default().superscript_ratio));
+        assert!(is_superscript(8.0, 10.0, 0.85));
+    }
+
+    #[test]
+    fn full_width_line_separates_column_runs() {
+        let column_line = |n: usize| {
+            line(vec![word(&format!("L{n}"), 50.0), word("left", 100.0), word(&format!("R{n}"), 330.0)], 700.0 - n as f32 * 12.0)
+        };
+        let mut lines: Vec<Line> = (1..=10).map(column_line).collect();
+        lines.push(line(vec![word(&"=".repeat(44), 200.0)], 700.0 - 10.5 * 12.0));
+        lines.extend((11..=20).map(column_line));
+
+        let ordered = split_columns(lines, 612.0);
+        let texts: Vec<String> = ordered.iter().map(|l| l.text()).collect();
+        let expected: Vec<String> = (1..=10)
+            .map(|n| format!("L{n} left"))
+            .chain((1..=10).map(|n| format!("R{n}")))
+            .chain(std::iter::once("=".repeat(44)))
+            .chain((11..=20).map(|n| format!("L{n} left")))
+            .chain((11..=20).map(|n| format!("R{n}")))
+            .collect();
+        assert_eq!(texts, expected);
+    }
+
+    #[test]
+    fn strips_lineno_margin_column() {
+        let lines: Vec<Line> = (1..=12)
+            .map(|n| {
+                let number = n.to_string();
+                // Right-aligned at x = 50
+                let number_x = 50.0 - number.len() as f32 * 5.0;
+                line(vec![word(&number, number_x), word("Phys.", 72.0), word("Rev.", 102.0)], 700.0 - n as f32 * 12.0)
+            })
+            .collect();
+        let stripped = strip_margin_line_numbers(lines, 612.0);
+        assert_eq!(stripped.len(), 12);
+        for l in &stripped {
+            assert_eq!(l.text(), "Phys. Rev.");
+            assert_eq!(l.x_start, 72.0);
+        }
+    }
+
+    #[test]
+    fn keeps_bare_reference_numbers() {
+        // "1 A. Author" list with hanging-indent continuation lines
+        let lines: Vec<Line> = (1..=12)
+            .flat_map(|n| {
+                let y = 700.0 - n as f32 * 24.0;
+                [
+                    line(vec![word(&n.to_string(), 72.0), word("A.", 90.0), word("Author,", 102.0)], y),
+                    line(vec![word("Phys.", 90.0), word("Rev.", 120.0)], y - 12.0),
+                ]
+            })
+            .collect();
+        let kept = strip_margin_line_numbers(lines, 612.0);
+        assert_eq!(kept[0].text(), "1 A. Author,");
+    }
+
+    #[test]
+    fn keeps_sparse_margin_numbers() {
+        let mut lines: Vec<Line> = (1..=12)
+            .map(|n| line(vec![word("Some", 72.0), word("text", 102.0)], 700.0 - n as f32 * 12.0))
+            .collect();
+        lines[3] = line(vec![word("12", 40.0), word("Some", 72.0)], 652.0);
+        let kept = strip_margin_line_numbers(lines, 612.0);
+        assert_eq!(kept[3].words.len(), 2);
+    }
+
+    #[test]
+    fn italic_font_names() {
+        for name in ["Times-Italic", "ABCDEF+Helvetica-Oblique", "MinionPro-It", "CMTI10", "LMRoman10-BoldItalic"] {
+            assert!(is_italic_font(name), "{name}");
+        }
+        for name in ["Times-Roman", "CMR10", "ABCDEF+Itim-Regular", "Helvetica"] {
+            assert!(!is_italic_font(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn bold_font_names() {
+        for name in ["Times-Bold", "ABCDEF+Helvetica-Black", "MinionPro-Semibold", "Arial-Bd", "CMBX12", "LMRoman10-BoldItalic"] {
+            assert!(is_bold_font(name), "{name}");
+        }
+        for name in ["Times-Roman", "CMR10", "CMTI10", "Helvetica"] {
+            assert!(!is_bold_font(name), "{name}");
+        }
+    }
+
+    /// Chars laid out left to right at `font_size`, `gap` apart, with a
+    /// wider `word_gap` at each space in `text` (no space chars emitted).
+    fn kerned_chars(text: &str, x: f32, font_size: f32, gap: f32, word_gap: f32) -> Vec<crate::types::PdfChar> {
+        let width = font_size / 2.0;
+        let mut x = x;
+        let mut chars = Vec::new();
+        for ch in text.chars() {
+            if ch == ' ' {
+                x += word_gap - gap;
+                continue;
+            }
+            chars.push(crate::types::PdfChar {
+                ch,
+                x,
+                y: 700.0,
+                width,
+                height: font_size,
+                font_size,
+                font_name: "Times-Roman".into(),
+            });
+            x += width + gap;
+        }
+        chars
+    }
+
+    #[test]
+    fn word_gaps_follow_the_font_size_and_ratio() {
+        let texts = |chars: &[crate::types::PdfChar], config: &LayoutConfig| -> Vec<String> {
+            let page = PageChars { page_num: 1, width: 612.0, height: 792.0, chars: chars.to_vec(), footnote_separator: None };
+            group_chars_into_words(chars, &CharWidths::of(&page), 10.0, config).into_iter().map(|w| w.text).collect()
+        };
+        // Tight kerning: words only 1.2pt apart, under 0.3 of the 5pt char width
+        let tight = kerned_chars("Phys. Rev. D72", 50.0, 10.0, 0.0, 1.2);
+        assert_eq!(texts(&tight, &LayoutConfig::default()), ["Phys.Rev.D72"]);
+        let tuned = LayoutConfig { word_gap_ratio: 0.2, ..LayoutConfig::default() };
+        assert_eq!(texts(&tight, &tuned), ["Phys.", "Rev.", "D72"]);
+
+        // A superscript marker 1pt before the word: a gap at its own size
+        let mut chars = kerned_chars("1", 50.0, 6.0, 0.0, 0.0);
+        chars.extend(kerned_chars("Phys. Rev.", 54.0, 10.0, 0.0, 2.0));
+        assert_eq!(texts(&chars, &LayoutConfig::default()), ["1", "Phys.", "Rev."]);
+    }
+
+    #[test]
+    fn words_and_lines_carry_their_font_name() {
+        let chars: Vec<crate::types::PdfChar> = "Nature Phys."
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| crate::types::PdfChar {
+                ch,
+                x: 50.0 + i as f32 * 5.0,
+                y: 700.0,
+                width: 5.0,
+                height: 10.0,
+                font_size: 10.0,
+                // One stray roman char doesn't change the word's font
+                font_name: if i < 6 && i != 2 { "Times-Italic" } else { "Times-Roman" }.into(),
+            })
+            .collect();
+        let widths = CharWidths { by_size: Vec::new(), overall: 5.0 };
+        let words = group_chars_into_words(&chars, &widths, 10.0, &LayoutConfig::default());
+        let fonts: Vec<&str> = words.iter().map(|w| w.font_name.as_str()).collect();
+        assert_eq!(fonts, ["Times-Italic", "Times-Roman"]);
+        assert!(words[0].is_italic);
+        let lines = group_words_into_lines(&words);
+        assert_eq!(lines[0].font_name, "Times-Italic");
+    }
+
+    #[test]
+    fn ocr_arxiv_slashes_are_repaired() {
+        let ocr = |text: &str, x: f32| Word { font_name: OCR_FONT_NAME.into(), ..word(text, x) };
+        let mut lines = vec![
+            line(vec![ocr("hep-ph|0202058,", 0.0), ocr("1999", 80.0)], 100.0),
+            line(vec![ocr("hep-ph", 0.0), ocr("|0202058", 35.0)], 88.0),
+            line(vec![ocr("math.AGl0202058", 0.0)], 76.0),
+            line(vec![word("hep-ph|0202058", 0.0), ocr("Il", 80.0)], 64.0),
+        ];
+        repair_ocr_arxiv_slashes(&mut lines);
+        let texts: Vec<String> = lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts, ["hep-ph/0202058, 1999", "hep-ph /0202058", "math.AG/0202058", "hep-ph|0202058 Il"]);
+
+        let ids: Vec<String> = texts[..3]
+            .iter()
+            .map(|t| {
+                let tokens = crate::tokenizer::tokenize(t);
+                tokens.into_iter().find(|t| t.kind == crate::types::TokenKind::ArxivId).unwrap().text
+            })
+            .collect();
+        assert_eq!(ids, ["hep-ph/0202058", "hep-ph/0202058", "math.AG/0202058"]);
+    }
+
+    #[test]
+    fn italic_runs_continue_across_lines() {
+        let italic = |text: &str, x: f32| Word { is_italic: true, ..word(text, x) };
+        let first = line(vec![word("A.", 0.0), word("Author,", 20.0), italic("et", 60.0), italic("al.,", 75.0)], 100.0);
+        let second = line(vec![italic("A", 0.0), italic("Model", 10.0), word("for", 40.0)], 88.0);
+        let third = line(vec![italic("of", 0.0), italic("Leptons,", 15.0), word("Phys.", 60.0)], 76.0);
+        let block = Block { lines: vec![first, second, third], x: 0.0, y: 100.0, width: 100.0, height: 34.0, font_size: 10.0 };
+        assert_eq!(block.italic_runs(), ["A Model", "of Leptons,"]);
+        let words = vec![italic("et", 0.0), italic("al.,", 15.0), italic("Title", 40.0), italic("Words", 70.0)];
+        let run_on = Block { lines: vec![line(words, 0.0)], ..block };
+        assert_eq!(run_on.italic_runs(), ["Title Words"]);
+    }
+
+    #[test]
+    fn column_boundary_of_a_page() {
+        let chars = |rows: &[(f32, f32, &str)]| -> Vec<crate::types::PdfChar> {
+            let mut chars = Vec::new();
+            for &(x, y, text) in rows {
+                for (i, ch) in text.chars().enumerate() {
+                    chars.push(crate::types::PdfChar {
+                        ch,
+                        x: x + i as f32 * 5.0,
+                        y,
+                        width: 5.0,
+                        height: 10.0,
+                        font_size: 10.0,
+                        font_name: "Times-Roman".into(),
+                    });
+                }
+            }
+            chars
+        };
+        let two_columns: Vec<(f32, f32, &str)> = (0..20)
+            .flat_map(|n| {
+                let y = 700.0 - n as f32 * 12.0;
+                [(50.0, y, "left column text"), (330.0, y, "right column text")]
+            })
+            .collect();
+        let page =
+            PageChars { page_num: 1, width: 612.0, height: 792.0, chars: chars(&two_columns), footnote_separator: None };
+        let boundary = column_boundary(&page, &LayoutConfig::default()).unwrap();
+        assert!(boundary > 130.0 && boundary < 330.0, "{boundary}");
+
+        // Word gaps that don't line up from one line to the next
+        let one_column: Vec<(f32, f32, &str)> = (0..40)
+            .map(|n| (50.0 + (n % 7) as f32 * 4.0, 700.0 - n as f32 * 12.0, "a single column of text across the whole width of the page, margin to margin"))
+            .collect();
+        let page = PageChars { chars: chars(&one_column), ..page };
+        assert_eq!(column_boundary(&page, &LayoutConfig::default()), None);
+    }
+
+
+    #[test]
+    fn blank_filler_and_cover_pages_are_flagged() {
+        let page = |texts: &[&str], size: f32| -> Vec<Block> {
+            let lines: Vec<Line> = texts
+                .iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    let mut w = word(t, 72.0);
+                    w.font_size = size;
+                    make_line(vec![w], 700.0 - i as f32 * 14.0, size)
+                })
+                .collect();
+            group_lines_into_blocks(&lines)
+        };
+        let body = "The quick brown fox jumps over the lazy dog again and again.";
+        let pages = vec![
+            page(&["A Thesis on Foxes", "by A. Author", "University of Somewhere, 2020"], 18.0),
+            page(&[body; 12], 10.0),
+            page(&["This page intentionally left blank", "vi"], 14.0),
+            page(&[body; 12], 10.0),
+            page(&["[45] A. Author, Phys. Rev. D 7, 1 (1973)."], 10.0),
+        ];
+        assert_eq!(filler_pages(&pages, true), [true, false, true, false, false]);
+        // No running text at all: nothing is a cover page
+        assert_eq!(filler_pages(&pages[..1], true), [false]);
+        // Nor when the pages are a range from inside the document
+        assert_eq!(filler_pages(&pages, false), [false, false, true, false, false]);
+    }
+}
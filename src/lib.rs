@@ -0,0 +1,717 @@
+//! Layout-aware reference extraction for HEP papers.
+//!
+//! The binary in `main.rs` drives the full PDF pipeline; this library exposes
+//! the modules plus text-level entry points that need no pdfium at all.
+//! `batch` streams references from many PDFs as NDJSON, and runs the
+//! reference logic on blocks from another layout engine
+//! (`batch::references_from_blocks`).
+
+pub mod batch;
+pub mod bbl;
+pub mod citations;
+pub mod collect;
+pub mod doi;
+pub mod error;
+pub mod fetch;
+pub mod kb;
+pub mod layout;
+pub mod markers;
+pub mod ocr;
+pub mod output;
+pub mod parse;
+pub mod pdf;
+pub mod schema;
+pub mod stats;
+pub mod tokenizer;
+pub mod types;
+pub mod zones;
+
+use std::collections::{HashMap, HashSet};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use parse::ParseOptions;
+use types::{ParsedReference, RawReference, ReferenceSource};
+
+/// Parse already-extracted reference strings, one reference per entry.
+/// Leading line markers (`[1]`, `1.`) are stripped into `linemarker`.
+/// Runs the same semicolon splitting, parsing and ibid resolution as the
+/// PDF pipeline, without touching pdfium.
+pub fn parse_reference_text(lines: &[String]) -> Vec<ParsedReference> {
+    let raw_refs = split_semicolon_subrefs(text_raw_references(lines));
+    let mut parsed = parse_all_references(&raw_refs, &ParseOptions::default());
+    resolve_ibid_journals(&mut parsed);
+    resolve_same_authors(&mut parsed);
+    parsed
+}
+
+/// Parse a single reference string, e.g. one typed into a citation form.
+/// The text is taken as is (no marker is split off) and runs through the
+/// same tokenizer and parser as extracted references. The result holds
+/// more than one reference when the string cites several works.
+///
+/// ```
+/// let refs = refextract::parse_one("Phys. Rev. D 72, 052002 (2005)");
+/// assert_eq!(refs[0].journal_title.as_deref(), Some("Phys. Rev. D"));
+/// assert_eq!(refs[0].journal_volume.as_deref(), Some("72"));
+/// assert_eq!(refs[0].journal_page.as_deref(), Some("052002"));
+/// assert_eq!(refs[0].journal_year.as_deref(), Some("2005"));
+/// ```
+pub fn parse_one(text: &str) -> Vec<ParsedReference> {
+    let raw = RawReference {
+        text: tokenizer::normalize_dashes(text.trim()),
+        linemarker: None,
+        from_marker: None,
+        source: ReferenceSource::Text,
+        page_num: 0,
+        section: 0,
+        italic_runs: Vec::new(),
+    };
+    let tokens = tokenizer::tokenize(&raw.text);
+    let mut parsed = parse::parse_references(&raw, &tokens);
+    resolve_ibid_journals(&mut parsed);
+    parsed
+}
+
+/// Turn reference strings into `RawReference`s, splitting off line markers.
+pub fn text_raw_references(lines: &[String]) -> Vec<RawReference> {
+    lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (linemarker, text) = markers::split_line_marker(line.trim());
+            RawReference {
+                text: tokenizer::normalize_dashes(&text),
+                linemarker,
+                from_marker: None,
+                source: ReferenceSource::Text,
+                page_num: 0,
+                section: 0,
+                italic_runs: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Split a plain-text reference list into entries. Blank-line separated
+/// paragraphs are one reference each (lines inside are joined); without any
+/// blank lines, every line is its own reference.
+pub fn split_text_entries(text: &str) -> Vec<String> {
+    let has_paragraphs = text.lines().any(|l| l.trim().is_empty())
+        && text.lines().filter(|l| !l.trim().is_empty()).count() > 1;
+    if !has_paragraphs {
+        return text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    text.split("\n\n")
+        .flat_map(|p| p.split("\r\n\r\n"))
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+pub fn parse_all_references(raw_refs: &[RawReference], opts: &ParseOptions) -> Vec<ParsedReference> {
+    raw_refs
+        .iter()
+        .flat_map(|raw| {
+            let tokens = tokenizer::tokenize(&raw.text);
+            parse::parse_references_with(raw, &tokens, opts)
+        })
+        .collect()
+}
+
+/// Like `parse_all_references`, but exactly one reference per entry: the
+/// primary citation, without sub-references.
+pub fn parse_primary_references(raw_refs: &[RawReference], opts: &ParseOptions) -> Vec<ParsedReference> {
+    raw_refs
+        .iter()
+        .filter_map(|raw| {
+            let tokens = tokenizer::tokenize(&raw.text);
+            parse::parse_references_with(raw, &tokens, opts).into_iter().next()
+        })
+        .collect()
+}
+
+/// Write arXiv IDs in the canonical `arXiv:` form (`--arxiv-prefix`).
+pub fn prefix_arxiv_ids(refs: &mut [ParsedReference]) {
+    for id in refs.iter_mut().filter_map(|r| r.arxiv_id.as_mut()) {
+        if !id.starts_with("arXiv:") {
+            *id = format!("arXiv:{id}");
+        }
+    }
+}
+
+/// Leave `raw_ref` out of the output (`--no-raw`). Parsing and lookups
+/// read it, so this runs last.
+pub fn drop_raw_refs(refs: &mut [ParsedReference]) {
+    for r in refs {
+        r.raw_ref.clear();
+    }
+}
+
+/// Keep only references with a DOI (`doi`), an arXiv ID (`arxiv`), or,
+/// with both set, either of the two (`--only-with-doi`,
+/// `--only-with-arxiv`). With neither set every reference is kept.
+pub fn retain_linked(refs: &mut Vec<ParsedReference>, doi: bool, arxiv: bool) {
+    if !doi && !arxiv {
+        return;
+    }
+    refs.retain(|r| (doi && r.doi.is_some()) || (arxiv && r.arxiv_id.is_some()));
+}
+
+/// Reduce `authors` to the first author's surname (`--first-author-only`).
+/// `authors_list` keeps the full names. A first author given only as
+/// initials has no surname and is kept as written.
+pub fn first_author_only(refs: &mut [ParsedReference]) {
+    for r in refs {
+        let first = r.authors_list.as_ref().and_then(|names| names.first()).or(r.authors.as_ref());
+        if let Some(first) = first {
+            r.authors = Some(parse::first_author_surname(first).unwrap_or_else(|| first.clone()));
+        }
+    }
+}
+
+/// Split reference entries that contain semicolons into sub-references.
+/// In HEP papers, semicolons within a single numbered reference entry
+/// typically separate distinct citations (e.g., "[1] Author1; Author2").
+pub fn split_semicolon_subrefs(refs: Vec<RawReference>) -> Vec<RawReference> {
+    let mut result = Vec::new();
+    for raw in refs {
+        if !raw.text.contains(';') {
+            result.push(raw);
+            continue;
+        }
+        let parts = split_outside_identifiers(&raw.text);
+        if parts.len() <= 1 {
+            result.push(raw);
+            continue;
+        }
+        // Only split if sub-parts look like citations
+        let subrefs: Vec<&str> = parts
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if subrefs.len() <= 1 {
+            result.push(raw);
+            continue;
+        }
+        // Check: at least 2 sub-parts should look like citations
+        let citation_count = subrefs.iter().filter(|s| looks_like_citation(s)).count();
+        if citation_count < 2 {
+            result.push(raw);
+            continue;
+        }
+        for (i, subref) in subrefs.iter().enumerate() {
+            result.push(RawReference {
+                text: subref.to_string(),
+                linemarker: raw.linemarker.clone(),
+                from_marker: (i > 0).then(|| raw.linemarker.clone()).flatten(),
+                source: raw.source,
+                page_num: raw.page_num,
+                section: raw.section,
+                italic_runs: raw.italic_runs.clone(),
+            });
+        }
+    }
+    result
+}
+
+/// Split on semicolons, except those inside a URL or DOI
+/// ("https://a.org/x;y", "10.1002/(SICI)1097-4636;2-X").
+fn split_outside_identifiers(text: &str) -> Vec<&str> {
+    static IDENT_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:https?://|www\.|\b10\.\d{4,}/)\S+").unwrap());
+
+    // A trailing ";" (or other punctuation) ends the identifier, not part of it.
+    let protected: Vec<(usize, usize)> = IDENT_RE
+        .find_iter(text)
+        .map(|m| {
+            let kept = m.as_str().trim_end_matches(|c: char| ";,.)]}>".contains(c));
+            (m.start(), m.start() + kept.len())
+        })
+        .collect();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (pos, _) in text.match_indices(';') {
+        if protected.iter().any(|&(s, e)| pos >= s && pos < e) {
+            continue;
+        }
+        parts.push(&text[start..pos]);
+        start = pos + 1;
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Heuristic: does this text fragment look like a citation?
+/// Checks for patterns common in HEP references.
+fn looks_like_citation(text: &str) -> bool {
+    static YEAR_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:19|20)\d{2}").unwrap());
+    static ARXIV_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:arXiv|hep-|astro-|gr-qc|cond-mat|nucl-|math-|quant-ph|physics/)").unwrap());
+
+    YEAR_RE.is_match(text)
+        || ARXIV_RE.is_match(text)
+        || text.contains("doi")
+        || text.contains("DOI")
+        || text.contains("Preprint")
+        || text.contains("preprint")
+}
+
+/// Resolve ibid placeholders from semicolon-split references.
+/// When parse.rs finds a standalone "ibid. V, P (Y)" ref, it sets
+/// journal_title to "ibid". Here we replace that with the actual journal
+/// from the nearest prior ref with the same linemarker and section.
+pub fn resolve_ibid_journals(refs: &mut [ParsedReference]) {
+    for i in 1..refs.len() {
+        if refs[i].journal_title.as_deref() != Some("ibid") {
+            continue;
+        }
+        let (linemarker, section) = (&refs[i].linemarker, refs[i].section);
+        for j in (0..i).rev() {
+            if refs[j].linemarker != *linemarker || refs[j].section != section {
+                continue;
+            }
+            match refs[j].journal_title.as_deref() {
+                Some("ibid") | None => continue,
+                Some(_) => {
+                    refs[i].journal_title = refs[j].journal_title.clone();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Fill in authors for author-date entries that open with a dash run
+/// ("———, 2006, ...") meaning "same authors as above": they take the
+/// authors of the reference before them. A dash entry with nothing before
+/// it is left without authors.
+pub fn resolve_same_authors(refs: &mut [ParsedReference]) {
+    for i in 0..refs.len() {
+        if !markers::starts_with_same_author(&refs[i].raw_ref) {
+            continue;
+        }
+        let (authors, authors_list, et_al) = match i.checked_sub(1).map(|j| &refs[j]) {
+            Some(prev) => (prev.authors.clone(), prev.authors_list.clone(), prev.et_al),
+            None => (None, None, false),
+        };
+        let r = &mut refs[i];
+        (r.authors, r.authors_list, r.et_al) = (authors, authors_list, et_al);
+    }
+}
+
+/// Average references per page above which a document is probably
+/// misdetected, e.g. a table or index read as a bibliography.
+pub const MAX_REFS_PER_PAGE: usize = 40;
+
+/// Sanity checks on one document's reference count. With more than
+/// `MAX_REFS_PER_PAGE` references per page every confidence is halved
+/// (`pages` is 0 for text input, which skips this); with more than
+/// `max_refs` references the list is cut to the first `max_refs`.
+/// Returns a warning for each check that fired.
+pub fn cap_references(refs: &mut Vec<ParsedReference>, pages: usize, max_refs: Option<usize>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let count = refs.len();
+    if pages > 0 && count > pages * MAX_REFS_PER_PAGE {
+        for r in refs.iter_mut() {
+            r.confidence /= 2.0;
+        }
+        warnings.push(format!(
+            "{count} references on {pages} pages is implausibly many; confidence halved"
+        ));
+    }
+    if let Some(max) = max_refs
+        && count > max
+    {
+        refs.truncate(max);
+        warnings.push(format!(
+            "{count} references exceed --max-refs {max}; kept the first {max}. \
+             The reference section may be misdetected (a table or index read as references)"
+        ));
+    }
+    warnings
+}
+
+/// A flaw in a reference section's numbering, found by
+/// `check_marker_sequence`. Usually a sign that collection split one
+/// entry in two or merged two entries into one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceIssue {
+    /// `[marker]` starts more than one entry.
+    Duplicate { section: usize, marker: usize },
+    /// No entry is numbered `[from]` to `[to]` (inclusive).
+    Missing { section: usize, from: usize, to: usize },
+}
+
+impl std::fmt::Display for SequenceIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (section, issue) = match self {
+            SequenceIssue::Duplicate { section, marker } => (section, format!("marker [{marker}] is used twice")),
+            SequenceIssue::Missing { section, from, to } if from == to => (section, format!("marker [{from}] is missing")),
+            SequenceIssue::Missing { section, from, to } => (section, format!("markers [{from}]-[{to}] are missing")),
+        };
+        match section {
+            0 => write!(f, "{issue}"),
+            _ => write!(f, "reference section {section}: {issue}"),
+        }
+    }
+}
+
+/// Check that the numeric markers of each reference section run 1, 2, 3...
+/// without repeats or holes. Sub-references and semicolon pieces repeat
+/// their entry's marker and carry `from_marker`, so they count with their
+/// entry; a doubled "[12] [12]" from OCR or a column join is reported.
+/// Footnotes, which number their own sequence, are skipped.
+pub fn check_marker_sequence(refs: &[ParsedReference]) -> Vec<SequenceIssue> {
+    let mut issues = Vec::new();
+    let mut sections: Vec<usize> = refs.iter().map(|r| r.section).collect();
+    sections.sort_unstable();
+    sections.dedup();
+    for section in sections {
+        let entries: Vec<usize> = refs
+            .iter()
+            .filter(|r| r.section == section && r.source != ReferenceSource::Footnote)
+            .filter(|r| r.from_marker.is_none())
+            .filter_map(|r| r.linemarker.as_deref()?.parse().ok())
+            .collect();
+        let mut seen = HashSet::new();
+        for &marker in &entries {
+            if !seen.insert(marker) {
+                issues.push(SequenceIssue::Duplicate { section, marker });
+            }
+        }
+        let mut numbers: Vec<usize> = seen.into_iter().collect();
+        numbers.sort_unstable();
+        let mut expected = 1;
+        for n in numbers {
+            if n > expected {
+                issues.push(SequenceIssue::Missing { section, from: expected, to: n - 1 });
+            }
+            expected = n + 1;
+        }
+    }
+    issues
+}
+
+/// Drop references that repeat an earlier one, keeping the first.
+/// Two references are duplicates when they share a DOI, an arXiv ID, or
+/// the full journal/volume/page triple. A shared year alone never merges.
+/// Within one entry (same marker and section; an unmarked entry is its
+/// raw text) only the triple counts: sub-references often carry the
+/// entry's arXiv ID or DOI.
+pub fn dedup_references(refs: Vec<ParsedReference>) -> Vec<ParsedReference> {
+    let mut seen: HashMap<String, (Option<String>, usize, String)> = HashMap::new();
+    refs.into_iter()
+        .filter(|r| {
+            let raw = if r.linemarker.is_some() { String::new() } else { r.raw_ref.clone() };
+            let entry = (r.linemarker.clone(), r.section, raw);
+            let keys = dedup_keys(r);
+            let repeated = keys.iter().any(|k| {
+                seen.get(k).is_some_and(|from| k.starts_with("journal:") || *from != entry)
+            });
+            if repeated {
+                return false;
+            }
+            for key in keys {
+                seen.entry(key).or_insert_with(|| entry.clone());
+            }
+            true
+        })
+        .collect()
+}
+
+/// Order references by numeric marker ("[3]" before "[12]") within each
+/// reference section, footnotes after the section's list since their
+/// numbering is separate. References whose marker isn't a number
+/// (author-year tags, or none at all) follow the numbered ones by page,
+/// keeping document order within a page. The sort is stable, so
+/// sub-references stay right after their entry.
+pub fn sort_by_marker(refs: &mut [ParsedReference]) {
+    refs.sort_by_key(|r| {
+        let marker = match r.linemarker.as_deref().and_then(|m| m.parse::<usize>().ok()) {
+            Some(n) => (false, n),
+            None => (true, r.found_on_page),
+        };
+        (r.section, r.source == ReferenceSource::Footnote, marker)
+    });
+}
+
+fn dedup_keys(r: &ParsedReference) -> Vec<String> {
+    let norm = collect::normalize_for_dedup;
+    let mut keys = Vec::new();
+    if let Some(doi) = &r.doi {
+        keys.push(format!("doi:{}", doi.to_lowercase()));
+    }
+    if let Some(arxiv) = &r.arxiv_id {
+        // Versions of one preprint are the same work.
+        let unversioned = match arxiv.rfind('v') {
+            Some(i) if i > 0 && arxiv[i + 1..].chars().all(|c| c.is_ascii_digit()) => &arxiv[..i],
+            _ => arxiv.as_str(),
+        };
+        keys.push(format!("arxiv:{}", norm(unversioned)));
+    }
+    if let (Some(journal), Some(volume), Some(page)) = (&r.journal_title, &r.journal_volume, &r.journal_page)
+        && journal != "ibid"
+    {
+        keys.push(format!("journal:{}|{}|{}", norm(journal), norm(volume), norm(page)));
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_lines_are_separate_references() {
+        let entries = split_text_entries(
+            "[1] J. D. Bekenstein, Phys. Rev. D 7, 2333 (1973).\n\
+             [2] S. W. Hawking, Commun. Math. Phys. 43, 199 (1975).\n",
+        );
+        assert_eq!(entries.len(), 2);
+        let refs = parse_reference_text(&entries);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].linemarker.as_deref(), Some("1"));
+        assert_eq!(refs[0].journal_volume.as_deref(), Some("7"));
+        assert_eq!(refs[1].linemarker.as_deref(), Some("2"));
+        assert_eq!(refs[1].source, ReferenceSource::Text);
+    }
+
+    #[test]
+    fn text_paragraphs_join_wrapped_lines() {
+        let entries = split_text_entries(
+            "J. D. Bekenstein, Phys. Rev.\nD 7, 2333 (1973).\n\nS. W. Hawking,\nNature 248, 30 (1974).\n",
+        );
+        assert_eq!(
+            entries,
+            vec![
+                "J. D. Bekenstein, Phys. Rev. D 7, 2333 (1973).",
+                "S. W. Hawking, Nature 248, 30 (1974).",
+            ]
+        );
+    }
+
+    #[test]
+    fn semicolons_inside_urls_do_not_split() {
+        let refs = split_semicolon_subrefs(text_raw_references(&[
+            "[1] A. Author, see https://a.org/x;y;z and Phys. Rev. D 1, 2 (2000); \
+             B. Other, Phys. Lett. B 3, 4 (2001)"
+                .to_string(),
+        ]));
+        let texts: Vec<&str> = refs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "A. Author, see https://a.org/x;y;z and Phys. Rev. D 1, 2 (2000)",
+                "B. Other, Phys. Lett. B 3, 4 (2001)",
+            ]
+        );
+        assert!(refs.iter().all(|r| r.linemarker.as_deref() == Some("1")));
+    }
+
+    #[test]
+    fn semicolon_after_url_still_splits() {
+        let parts = split_outside_identifiers("see https://a.org/x; doi:10.1000/a;b; end");
+        assert_eq!(parts, vec!["see https://a.org/x", " doi:10.1000/a;b", " end"]);
+    }
+
+    #[test]
+    fn dedup_drops_repeated_arxiv_and_journal_refs() {
+        let refs = parse_reference_text(&[
+            "[1] A. Author, Some preprint, arXiv:1001.0785".to_string(),
+            "[2] B. Other, Phys. Rev. D 72, 052002 (2005)".to_string(),
+            "[3] A. Author et al., arXiv:1001.0785v2".to_string(),
+            "[4] B. Other, Phys. Rev. D 72, 052002".to_string(),
+            "[5] C. Third, Nucl. Phys. B 417, 181 (2005)".to_string(),
+        ]);
+        let kept = dedup_references(refs);
+        let markers: Vec<&str> = kept.iter().filter_map(|r| r.linemarker.as_deref()).collect();
+        assert_eq!(markers, vec!["1", "2", "5"]);
+    }
+
+    #[test]
+    fn dedup_keeps_sub_references_sharing_the_entry_arxiv_id() {
+        let mut refs = parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005); Phys. Lett. B 716, 1 (2012), arXiv:1207.7214".to_string(),
+        ]);
+        // Both halves of the entry carry the arXiv ID
+        for r in &mut refs {
+            r.arxiv_id = Some("1207.7214".to_string());
+        }
+        assert_eq!(dedup_references(refs).len(), 2);
+    }
+
+    #[test]
+    fn dedup_keeps_refs_sharing_only_a_year() {
+        let refs = parse_reference_text(&[
+            "[1] A. Author, Some title (2005)".to_string(),
+            "[2] B. Other, Another title (2005)".to_string(),
+        ]);
+        assert_eq!(dedup_references(refs).len(), 2);
+    }
+
+    #[test]
+    fn first_author_reduced_to_surname() {
+        let mut refs = parse_reference_text(&[
+            "[1] J. D. Bekenstein and B. Other, Phys. Rev. D 7, 2333 (1973)".to_string(),
+            "[2] A. Author, Nature 248, 30 (1974)".to_string(),
+        ]);
+        refs[1].authors_list = Some(vec!["J. D.".into()]);
+        first_author_only(&mut refs);
+        assert_eq!(refs[0].authors.as_deref(), Some("Bekenstein"));
+        assert_eq!(refs[1].authors.as_deref(), Some("J. D."));
+    }
+
+    #[test]
+    fn subrefs_expanded_or_suppressed() {
+        let raw_refs = text_raw_references(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005); Phys. Lett. B 716, 1 (2012), arXiv:1207.7214".to_string(),
+            "[2] B. Other, Nucl. Phys. B 417, 181 (1994)".to_string(),
+        ]);
+        let expanded = parse_all_references(&raw_refs, &ParseOptions::default());
+        let markers: Vec<Option<&str>> = expanded.iter().map(|r| r.from_marker.as_deref()).collect();
+        assert_eq!(markers, [None, Some("1"), None]);
+        assert_eq!(expanded[1].journal_title.as_deref(), Some("Phys. Lett. B"));
+        assert!(expanded[..2].iter().all(|r| r.linemarker.as_deref() == Some("1")));
+
+        let primary = parse_primary_references(&raw_refs, &ParseOptions::default());
+        assert_eq!(primary.len(), raw_refs.len());
+        assert!(primary.iter().all(|r| r.from_marker.is_none()));
+        assert_eq!(primary[0].journal_volume.as_deref(), Some("72"));
+    }
+
+    #[test]
+    fn sort_orders_numeric_markers_first() {
+        let mut refs = parse_reference_text(&[
+            "[12] A. Author, Phys. Rev. D 72, 052002 (2005); Phys. Lett. B 716, 1 (2012)".to_string(),
+            "[Wei67] S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967)".to_string(),
+            "[3] B. Other, Nucl. Phys. B 417, 181 (1994)".to_string(),
+            "[1] C. Third, arXiv:1001.0785".to_string(),
+            "[Hig64] P. Higgs, Phys. Rev. Lett. 13, 508 (1964)".to_string(),
+        ]);
+        refs[2].found_on_page = 9;
+        refs[5].found_on_page = 2;
+        sort_by_marker(&mut refs);
+        let markers: Vec<&str> = refs.iter().filter_map(|r| r.linemarker.as_deref()).collect();
+        assert_eq!(markers, ["1", "3", "12", "12", "Hig64", "Wei67"]);
+        assert_eq!(refs[3].journal_title.as_deref(), Some("Phys. Lett. B"));
+
+        // Restarted numbering and footnotes keep their own runs
+        let mut refs = parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string(),
+            "[2] B. Other, Nucl. Phys. B 417, 181 (1994)".to_string(),
+            "[1] C. Third, Phys. Lett. B 716, 1 (2012)".to_string(),
+            "[2] D. Fourth, JHEP 05, 026 (2006)".to_string(),
+        ]);
+        refs[0].source = ReferenceSource::Footnote;
+        refs[2].section = 1;
+        refs[3].section = 1;
+        sort_by_marker(&mut refs);
+        let authors: Vec<&str> = refs.iter().filter_map(|r| r.authors.as_deref()).collect();
+        assert_eq!(authors, ["B. Other", "A. Author", "C. Third", "D. Fourth"]);
+    }
+
+    #[test]
+    fn implausible_reference_counts_are_capped() {
+        let lines: Vec<String> = (1..=100).map(|i| format!("[{i}] A. Author, Phys. Rev. D {i}, 1 (2005)")).collect();
+        let mut refs = parse_reference_text(&lines);
+        let confidence = refs[0].confidence;
+        assert!(cap_references(&mut refs, 3, None).is_empty());
+        assert_eq!(refs.len(), 100);
+
+        let warnings = cap_references(&mut refs, 2, Some(30));
+        assert_eq!(refs.len(), 30);
+        assert_eq!(refs[29].linemarker.as_deref(), Some("30"));
+        assert_eq!(refs[0].confidence, confidence / 2.0);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("100 references on 2 pages"));
+        assert!(warnings[1].contains("--max-refs 30"), "{}", warnings[1]);
+    }
+
+    #[test]
+    fn arxiv_prefix_is_added_once() {
+        let mut refs = parse_reference_text(&[
+            "[1] A. Author, arXiv:2101.12345".to_string(),
+            "[2] B. Other, hep-ph/0202058".to_string(),
+        ]);
+        prefix_arxiv_ids(&mut refs);
+        prefix_arxiv_ids(&mut refs);
+        let ids: Vec<&str> = refs.iter().filter_map(|r| r.arxiv_id.as_deref()).collect();
+        assert_eq!(ids, ["arXiv:2101.12345", "arXiv:hep-ph/0202058"]);
+    }
+
+    #[test]
+    fn raw_ref_is_omitted_only_when_dropped() {
+        let mut refs = parse_reference_text(&["[1] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string()]);
+        let json = serde_json::to_value(&refs).unwrap();
+        assert_eq!(json[0]["raw_ref"], "A. Author, Phys. Rev. D 72, 052002 (2005)");
+        drop_raw_refs(&mut refs);
+        let json = serde_json::to_value(&refs).unwrap();
+        assert!(json[0].get("raw_ref").is_none());
+        assert_eq!(json[0]["journal_volume"], "72");
+    }
+
+    #[test]
+    fn duplicated_and_skipped_markers_are_reported() {
+        let lines: Vec<String> = [
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005); B. Author, Phys. Lett. B 716, 1 (2012)",
+            "[2] C. Author, Nucl. Phys. B 417, 181 (1994)",
+            "[3] D. Author, Phys. Rev. Lett. 19, 1264 (1967)",
+            "[2] D. Author, Phys. Rev. Lett. 19, 1264 (1967)",
+            "[5] E. Author, Nature 248, 30 (1974)",
+            "[6] F. Author, Phys. Rev. D 7, 2333 (1973)",
+        ]
+        .map(String::from)
+        .to_vec();
+        let parse = |lines: &[String]| {
+            parse_all_references(&split_semicolon_subrefs(text_raw_references(lines)), &ParseOptions::default())
+        };
+        let mut refs = parse(&lines);
+        assert_eq!(
+            check_marker_sequence(&refs),
+            [SequenceIssue::Duplicate { section: 0, marker: 2 }, SequenceIssue::Missing { section: 0, from: 4, to: 4 }]
+        );
+        // The semicolon split of [1] is one entry, an adjacent doubled one is not
+        assert!(refs.iter().filter(|r| r.linemarker.as_deref() == Some("1")).count() > 1);
+        let mut markers: Vec<usize> = (1..=13).collect();
+        markers.insert(12, 12);
+        let doubled: Vec<String> = markers.iter().map(|n| format!("[{n}] A. Author, Nature 248, 30 (1974)")).collect();
+        assert_eq!(check_marker_sequence(&parse(&doubled)), [SequenceIssue::Duplicate { section: 0, marker: 12 }]);
+        assert_eq!(check_marker_sequence(&refs)[1].to_string(), "marker [4] is missing");
+
+        // A second section starts over at [1]
+        refs.retain(|r| !matches!(r.linemarker.as_deref(), Some("2" | "3")));
+        let last = refs.len() - 1;
+        refs[last].section = 1;
+        refs[last].linemarker = Some("1".into());
+        let issues = check_marker_sequence(&refs);
+        assert_eq!(issues, [SequenceIssue::Missing { section: 0, from: 2, to: 4 }]);
+        assert_eq!(issues[0].to_string(), "markers [2]-[4] are missing");
+    }
+
+    #[test]
+    fn linked_filters_keep_either_identifier() {
+        let refs = parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), doi:10.1103/PhysRevD.72.052002".to_string(),
+            "[2] B. Author, arXiv:1001.0785".to_string(),
+            "[3] C. Author, Nucl. Phys. B 417, 181 (1994)".to_string(),
+        ]);
+        let kept = |doi, arxiv| {
+            let mut refs = refs.clone();
+            retain_linked(&mut refs, doi, arxiv);
+            refs.iter().map(|r| r.linemarker.clone().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(kept(true, false), ["1"]);
+        assert_eq!(kept(false, true), ["2"]);
+        assert_eq!(kept(true, true), ["1", "2"]);
+        assert_eq!(kept(false, false), ["1", "2", "3"]);
+    }
+}
@@ -1,313 +1,772 @@
-mod collect;
-mod doi;
-mod kb;
-mod layout;
-mod markers;
-mod ocr;
-mod parse;
-mod pdf;
-mod tokenizer;
-mod types;
-mod zones;
-
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, mpsc};
+use std::thread;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use pdfium_render::prelude::*;
-use serde::Serialize;
 
-use types::ParsedReference;
+use refextract::error::RefextractError;
+use refextract::parse::ParseOptions;
+use refextract::stats::Stats;
+use refextract::types::{self, ParsedReference};
+use refextract::batch::{self, BatchOptions, BatchResult, DoiLookup, Extraction};
+use refextract::{collect, doi, fetch, kb, layout, ocr, output, pdf, schema, tokenizer, zones};
 
 #[derive(Parser)]
 #[command(name = "refextract", about = "Extract references from HEP papers")]
 struct Cli {
-    /// PDF file(s) to process
+    /// PDF file(s) to process (`.txt` files are read as plain-text reference
+    /// lists; `-` reads a single document from stdin)
     files: Vec<PathBuf>,
 
+    /// Treat every input as a plain-text reference list (one reference per
+    /// line, or per paragraph when blank lines separate entries)
+    #[arg(long)]
+    text: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
+
     /// Pretty-print JSON output
     #[arg(long)]
     pretty: bool,
 
+    /// Print the JSON Schema of the JSON output and exit
+    #[arg(long)]
+    print_schema: bool,
+
     /// Show zone classification per page (debug)
     #[arg(long)]
     debug_layout: bool,
 
+    /// Show each collected reference and its tokens (debug)
+    #[arg(long)]
+    debug_tokens: bool,
+
+    /// Print a summary of reference, DOI, arXiv and journal counts to stderr
+    #[arg(long)]
+    stats: bool,
+
     /// Skip footnote extraction
     #[arg(long)]
     no_footnotes: bool,
 
-    /// Skip DOI lookup via CrossRef
+    /// Keep only footnotes with a DOI, arXiv ID, handle or journal name, not just a year
+    #[arg(long)]
+    exclude_footnotes_without_markers: bool,
+
+    /// Keep at most N references per document, warning when there were more
+    #[arg(long, value_name = "N")]
+    max_refs: Option<usize>,
+
+    /// Drop references scoring below this confidence (0.0-1.0)
+    #[arg(long, value_name = "SCORE", value_parser = parse_confidence)]
+    min_confidence: Option<f32>,
+
+    /// Take unquoted titles from italic text (PDF input)
+    #[arg(long)]
+    title_from_italics: bool,
+
+    /// Also accept this reference section heading, e.g. "Works Cited" (repeatable)
+    #[arg(long, value_name = "TEXT")]
+    ref_heading: Vec<String>,
+
+    /// Write arXiv IDs as "arXiv:2101.12345" instead of the bare ID
+    #[arg(long)]
+    arxiv_prefix: bool,
+
+    /// Leave the raw reference text (raw_ref) out of the output
+    #[arg(long)]
+    no_raw: bool,
+
+    /// One reference per entry: don't split extra citations into sub-references
+    #[arg(long)]
+    no_subrefs: bool,
+
+    /// List the body pages citing each entry ("[12]", "[3-5]", superscripts) in cited_on_pages
+    #[arg(long)]
+    cited_on_pages: bool,
+
+    /// Reduce each author list to the first author's surname
+    #[arg(long)]
+    first_author_only: bool,
+
+    /// Keep journal names matched without a volume (normally dropped as false positives)
+    #[arg(long)]
+    no_journal_clearing: bool,
+
+    /// Extra collaboration names, as "name---standardized" lines, added to the baked-in KB
+    #[arg(long, value_name = "PATH")]
+    collaborations_kb: Option<PathBuf>,
+
+    /// Keep only references with a DOI (with --only-with-arxiv: a DOI or an arXiv ID)
+    #[arg(long)]
+    only_with_doi: bool,
+
+    /// Keep only references with an arXiv ID (with --only-with-doi: an arXiv ID or a DOI)
+    #[arg(long)]
+    only_with_arxiv: bool,
+
+    /// Drop references repeating an earlier DOI, arXiv ID or journal/volume/page
+    #[arg(long)]
+    dedup: bool,
+
+    /// Order references by marker number; unnumbered ones follow in document order
+    #[arg(long)]
+    sort: bool,
+
+    /// Skip DOI lookup
     #[arg(long)]
     no_doi_lookup: bool,
 
+    /// Service used for DOI lookup
+    #[arg(long, value_enum, default_value_t = doi::Resolver::CrossRef)]
+    resolver: doi::Resolver,
+
+    /// Fill missing titles from Semantic Scholar (requires --resolver s2)
+    #[arg(long)]
+    enrich_titles: bool,
+
+    /// Fill a missing arXiv ID from the DOI, or the DOI from the arXiv ID, via INSPIRE
+    #[arg(long)]
+    cross_link: bool,
+
+    /// Number of concurrent DOI lookups (capped at 16)
+    #[arg(long, default_value_t = 4)]
+    doi_concurrency: usize,
+
+    /// Retries per DOI lookup on rate limiting or server errors (1s, 2s, 4s, ... backoff)
+    #[arg(long, value_name = "N", default_value_t = doi::DEFAULT_RETRIES)]
+    doi_retries: u32,
+
+    /// Write one JSON file per input into this directory instead of NDJSON
+    /// on stdout (implies batch mode)
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// Number of files processed in parallel in batch mode (0 = all cores)
+    #[arg(long, default_value_t = 0)]
+    jobs: usize,
+
+    /// Retry cached negative DOI lookups after this many days
+    #[arg(long, value_name = "N", default_value_t = doi::DEFAULT_NEGATIVE_TTL_DAYS)]
+    doi_cache_ttl_days: u64,
+
+    /// Never touch the network; resolve DOIs from the local cache only
+    #[arg(long)]
+    offline: bool,
+
+    /// Export the DOI cache as NDJSON to this file
+    #[arg(long, value_name = "PATH")]
+    dump_cache: Option<PathBuf>,
+
+    /// Import DOI cache entries from an NDJSON file written by --dump-cache
+    #[arg(long, value_name = "PATH")]
+    load_cache: Option<PathBuf>,
+
+    /// Print DOI cache entry counts and exit (no network, no PDFs)
+    #[arg(long)]
+    cache_stats: bool,
+
+    /// Delete cached negative DOI lookups older than this many days
+    #[arg(long, value_name = "DAYS")]
+    purge_negative: Option<u64>,
+
     /// Use OCR for pages where text extraction fails (requires tesseract)
     #[arg(long)]
     ocr_fallback: bool,
 
+    /// Tesseract language(s) for OCR, e.g. "eng+deu"
+    #[arg(long, value_name = "CODE", default_value = ocr::DEFAULT_LANG)]
+    ocr_lang: String,
+
+    /// Render resolution for OCR; higher helps small fonts
+    #[arg(long, value_name = "N", default_value_t = ocr::DEFAULT_DPI,
+          value_parser = clap::value_parser!(u32).range(72..=1200))]
+    ocr_dpi: u32,
+
+    /// Drop OCR words below this tesseract confidence (0-100)
+    #[arg(long, value_name = "N", default_value_t = ocr::DEFAULT_MIN_CONFIDENCE,
+          value_parser = clap::value_parser!(i32).range(0..=100))]
+    ocr_min_confidence: i32,
+
+    /// Words smaller than this fraction of the page's main font are superscript
+    #[arg(long, value_name = "RATIO", default_value_t = layout::LayoutConfig::default().superscript_ratio, value_parser = parse_ratio)]
+    superscript_ratio: f32,
+
+    /// Footnotes must end in this bottom fraction of the page
+    #[arg(long, value_name = "FRACTION", default_value_t = layout::LayoutConfig::default().footnote_band, value_parser = parse_ratio)]
+    footnote_band: f32,
+
+    /// Footnotes are set smaller than this fraction of the body font
+    #[arg(long, value_name = "RATIO", default_value_t = layout::LayoutConfig::default().footnote_font_ratio, value_parser = parse_ratio)]
+    footnote_font_ratio: f32,
+
+    /// Gaps wider than this fraction of the average char width split words; lower it for tight kerning
+    #[arg(long, value_name = "RATIO", default_value_t = layout::LayoutConfig::default().word_gap_ratio, value_parser = parse_ratio)]
+    word_gap_ratio: f32,
+
+    /// Password for encrypted PDFs
+    #[arg(long, env = "REFEXTRACT_PDF_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+
+    /// Only extract these pages: "180-210", "180-", "7", or "-20" for the last 20
+    #[arg(long, value_name = "RANGE", allow_hyphen_values = true)]
+    pages: Option<pdf::PageRange>,
+
     /// Override pdfium library path
     #[arg(long, env = "PDFIUM_LIB_PATH")]
     pdfium_path: Option<String>,
 }
 
-#[derive(Serialize)]
-struct BatchResult {
-    file: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    references: Option<Vec<ParsedReference>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
+fn parse_confidence(s: &str) -> Result<f32, String> {
+    let value: f32 = s.parse().map_err(|_| format!("not a number: {s}"))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("confidence must be between 0.0 and 1.0, got {value}"))
+    }
+}
+
+fn parse_ratio(s: &str) -> Result<f32, String> {
+    let value: f32 = s.parse().map_err(|_| format!("not a number: {s}"))?;
+    if value > 0.0 && value <= 1.0 {
+        Ok(value)
+    } else {
+        Err(format!("must be greater than 0.0 and at most 1.0, got {value}"))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// JSON array (NDJSON of per-file results in batch mode)
+    Json,
+    /// RIS records for EndNote/Mendeley/Zotero
+    Ris,
+    /// CSV with a header row; batch mode concatenates all files
+    Csv,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    if cli.print_schema {
+        println!("{}", serde_json::to_string_pretty(&schema::reference_list_schema())?);
+        return Ok(());
+    }
+    if cli.dump_cache.is_some()
+        || cli.load_cache.is_some()
+        || cli.purge_negative.is_some()
+        || cli.cache_stats
+    {
+        run_cache_commands(&cli)?;
+        if cli.files.is_empty() {
+            return Ok(());
+        }
+    }
     if cli.files.is_empty() {
         anyhow::bail!("No input files specified");
     }
-    if cli.ocr_fallback && !ocr::tesseract_available() {
+    if cli.files.len() > 1 && cli.files.iter().any(|f| is_stdin(f)) {
+        anyhow::bail!("Reading from stdin ('-') supports exactly one input");
+    }
+    if let Some(dir) = &cli.output_dir {
+        if cli.format != OutputFormat::Json {
+            anyhow::bail!("--output-dir only supports --format json");
+        }
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+    }
+    if cli.enrich_titles && (cli.resolver != doi::Resolver::SemanticScholar || cli.no_doi_lookup) {
+        anyhow::bail!("--enrich-titles requires --resolver s2 and DOI lookup");
+    }
+    if cli.cross_link && cli.no_doi_lookup {
+        anyhow::bail!("--cross-link requires DOI lookup");
+    }
+    if cli.ocr_fallback && !ocr::tesseract_available(&cli.ocr_lang) {
         anyhow::bail!(
-            "--ocr-fallback requires tesseract with eng traineddata. \
-             Install tesseract-ocr and tesseract-ocr-eng."
+            "--ocr-fallback requires tesseract with traineddata for '{}'. \
+             Install tesseract-ocr and the matching language packs (e.g. tesseract-ocr-eng).",
+            cli.ocr_lang
         );
     }
-    let pdfium = bind_pdfium(&cli.pdfium_path)?;
-    let batch = cli.files.len() > 1;
+    // Bind pdfium only when some input is a PDF, so text-only runs work
+    // on machines without libpdfium.
+    let pdfium = if cli.files.iter().any(|f| !is_text_input(&cli, f)) {
+        Some(pdf::bind_pdfium(cli.pdfium_path.as_deref())?)
+    } else {
+        None
+    };
+    let batch = cli.files.len() > 1 || cli.output_dir.is_some();
 
     // Force KB initialization upfront (amortize ~500ms regex compilation).
     let _ = (&*kb::JOURNAL_TITLES, &*kb::JOURNAL_ABBREVS, &*kb::REPORT_NUMBERS);
 
+    let collaborations = match &cli.collaborations_kb {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read collaborations KB: {}", path.display()))?;
+            kb::CollaborationKb::with_extra(&text)
+        }
+        None => kb::COLLABORATIONS.clone(),
+    };
+    let opts = batch_options(&cli, &collaborations);
+
     let doi_cache = if !cli.no_doi_lookup {
-        Some(doi::DoiCache::open()?)
+        Some(doi::DoiCache::open()?.with_negative_ttl_days(cli.doi_cache_ttl_days))
     } else {
         None
     };
-
+    let doi_lookup = doi_cache.as_ref().map(|cache| DoiLookup {
+        cache,
+        enrich: doi::EnrichOptions {
+            concurrency: cli.doi_concurrency,
+            offline: cli.offline,
+            resolver: cli.resolver,
+            retries: cli.doi_retries,
+            enrich_titles: cli.enrich_titles,
+        },
+        cross_link: cli.cross_link,
+    });
+
+    let totals = Mutex::new(Stats::default());
     if batch {
-        run_batch(&pdfium, &cli, &doi_cache)
+        run_batch(pdfium.as_ref(), &cli, &opts, doi_lookup.as_ref(), &totals)?;
     } else {
-        run_single(&pdfium, &cli, &doi_cache)
+        run_single(pdfium.as_ref(), &cli, &opts, doi_lookup.as_ref(), &totals)?;
+    }
+    if cli.stats {
+        eprintln!("{}", totals.into_inner().unwrap());
     }
+    Ok(())
+}
+
+/// A loaded input, ready for the pdfium-free part of the pipeline.
+enum Document {
+    Pages(Vec<types::PageChars>),
+    Text(String),
+    /// LaTeX `.bbl` bibliography
+    Bbl(String),
 }
 
-fn run_single(pdfium: &Pdfium, cli: &Cli, doi_cache: &Option<doi::DoiCache>) -> Result<()> {
+/// Name used for stdin input in output and error messages.
+const STDIN_LABEL: &str = "<stdin>";
+
+fn is_stdin(file: &Path) -> bool {
+    file.as_os_str() == "-"
+}
+
+fn input_label(file: &Path) -> String {
+    if is_stdin(file) {
+        STDIN_LABEL.to_string()
+    } else {
+        file.display().to_string()
+    }
+}
+
+fn is_text_input(cli: &Cli, file: &Path) -> bool {
+    cli.text || has_extension(file, "txt") || is_bbl(file)
+}
+
+fn is_bbl(file: &Path) -> bool {
+    has_extension(file, "bbl")
+}
+
+/// Extension check that looks through ".gz": "refs.txt.gz" is a txt file.
+fn has_extension(file: &Path, wanted: &str) -> bool {
+    let is = |p: &Path| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(wanted));
+    let inner = fetch::is_gzip(file).then(|| file.file_stem().map(Path::new)).flatten();
+    is(file) || inner.is_some_and(is)
+}
+
+/// A text input: a `.bbl` bibliography or a plain reference list.
+fn text_document(text: String, file: &Path) -> Document {
+    if is_bbl(file) { Document::Bbl(text) } else { Document::Text(text) }
+}
+
+/// Load an input file. pdfium is only touched for PDF inputs; it is `None`
+/// exactly when every input is text.
+fn load_document(pdfium: Option<&Pdfium>, file: &Path, cli: &Cli) -> Result<Document> {
+    if is_stdin(file) {
+        return load_stdin_document(pdfium, cli);
+    }
+    let label = input_label(file);
+    if fetch::is_url(file) {
+        if cli.offline {
+            anyhow::bail!("--offline: refusing to download {label}");
+        }
+        let mut bytes = fetch::download(&label)?;
+        if fetch::is_gzip(file) {
+            bytes = fetch::gunzip(&bytes, &label)?;
+        }
+        return document_from_bytes(pdfium, bytes, &label, is_text_input(cli, file), cli);
+    }
+    if fetch::is_gzip(file) {
+        let compressed = std::fs::read(file)
+            .with_context(|| format!("Failed to read file: {label}"))?;
+        let bytes = fetch::gunzip(&compressed, &label)?;
+        return document_from_bytes(pdfium, bytes, &label, is_text_input(cli, file), cli);
+    }
+    if is_text_input(cli, file) {
+        let text = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read text file: {}", file.display()))?;
+        return Ok(text_document(text, file));
+    }
+    let pdfium = pdfium.context("pdfium is not loaded")?;
+    pdf_document(pdf::extract_chars(pdfium, file, &extract_options(cli)))
+}
+
+/// A PDF without a text layer gives no references, with a warning, rather
+/// than failing the run.
+fn pdf_document(pages: Result<Vec<types::PageChars>, RefextractError>) -> Result<Document> {
+    match pages {
+        Err(e @ RefextractError::NoText { .. }) => {
+            eprintln!("warning: {e}");
+            Ok(Document::Pages(Vec::new()))
+        }
+        pages => Ok(Document::Pages(pages?)),
+    }
+}
+
+fn extract_options(cli: &Cli) -> pdf::ExtractOptions {
+    pdf::ExtractOptions {
+        ocr_fallback: cli.ocr_fallback,
+        ocr: ocr::OcrOptions {
+            lang: cli.ocr_lang.clone(),
+            dpi: cli.ocr_dpi,
+            min_confidence: cli.ocr_min_confidence,
+        },
+        pages: cli.pages,
+        debug: cli.debug_layout,
+        password: cli.password.clone(),
+    }
+}
+
+fn load_stdin_document(pdfium: Option<&Pdfium>, cli: &Cli) -> Result<Document> {
+    let mut bytes = Vec::new();
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .context("Failed to read stdin")?;
+    document_from_bytes(pdfium, bytes, STDIN_LABEL, cli.text, cli)
+}
+
+/// Build a document from an in-memory input (stdin, download, gunzipped file).
+fn document_from_bytes(
+    pdfium: Option<&Pdfium>,
+    bytes: Vec<u8>,
+    label: &str,
+    text: bool,
+    cli: &Cli,
+) -> Result<Document> {
+    if text {
+        let text = String::from_utf8(bytes).with_context(|| format!("{label} is not valid UTF-8 text"))?;
+        return Ok(text_document(text, Path::new(label)));
+    }
+    let pdfium = pdfium.context("pdfium is not loaded")?;
+    pdf_document(pdf::extract_chars_from_bytes(pdfium, bytes, label, &extract_options(cli)))
+}
+
+/// Cache maintenance flags, applied in order: load, purge, dump, stats.
+fn run_cache_commands(cli: &Cli) -> Result<()> {
+    let cache = doi::DoiCache::open()?;
+    if let Some(path) = &cli.load_cache {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open cache file: {}", path.display()))?;
+        let count = cache.load(std::io::BufReader::new(file))?;
+        eprintln!("Loaded {count} cache entries from {}", path.display());
+    }
+    if let Some(days) = cli.purge_negative {
+        let count = cache.purge_negative(days as i64 * doi::SECS_PER_DAY)?;
+        eprintln!("Purged {count} negative cache entries older than {days} days");
+    }
+    if let Some(path) = &cli.dump_cache {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create cache file: {}", path.display()))?;
+        let mut out = std::io::BufWriter::new(file);
+        let count = cache.dump(&mut out)?;
+        out.flush()?;
+        eprintln!("Dumped {count} cache entries to {}", path.display());
+    }
+    if cli.cache_stats {
+        print_cache_stats(&cache.stats()?);
+    }
+    Ok(())
+}
+
+fn print_cache_stats(stats: &doi::CacheStats) {
+    println!("entries:  {}", stats.total);
+    println!("positive: {}", stats.positive);
+    println!("negative: {}", stats.negative);
+    if let Some(oldest) = stats.oldest_created_at {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let age_days = (now - oldest) / doi::SECS_PER_DAY;
+        println!("oldest:   {oldest} (unix time, {age_days} days ago)");
+    }
+}
+
+fn run_single(
+    pdfium: Option<&Pdfium>,
+    cli: &Cli,
+    opts: &BatchOptions,
+    doi: Option<&DoiLookup>,
+    totals: &Mutex<Stats>,
+) -> Result<()> {
+    let document = load_document(pdfium, &cli.files[0], cli)?;
     if cli.debug_layout {
-        let page_chars = pdf::extract_chars(pdfium, &cli.files[0], cli.ocr_fallback)?;
-        let all_blocks = build_page_blocks(&page_chars);
-        let body_font_size = zones::compute_body_font_size(&all_blocks);
-        let zoned_pages = classify_all_pages(&page_chars, &all_blocks, body_font_size);
-        print_debug_layout(&zoned_pages);
+        let Document::Pages(page_chars) = document else {
+            anyhow::bail!("--debug-layout requires a PDF input");
+        };
+        print_debug_layout(&page_chars, &opts.layout);
+        return Ok(());
+    }
+    if cli.debug_tokens {
+        print_debug_tokens(&refextract::split_semicolon_subrefs(raw_references(&document, opts)));
         return Ok(());
     }
 
-    let parsed = process_pdf(pdfium, &cli.files[0], doi_cache, cli.ocr_fallback)?;
-    print_output(&parsed, cli.pretty)
+    let label = input_label(&cli.files[0]);
+    let parsed = references_from_document(&document, &label, opts, doi, cli, totals).references;
+    if cli.format == OutputFormat::Csv {
+        print!("{}", output::csv_header());
+    }
+    print_output(&parsed, &label, cli)
 }
 
-fn run_batch(pdfium: &Pdfium, cli: &Cli, doi_cache: &Option<doi::DoiCache>) -> Result<()> {
+/// Process many files concurrently. PDF loading stays on the calling thread
+/// because pdfium bindings are not `Sync`; the CPU-bound layout/parse stage
+/// and DOI enrichment run on `--jobs` workers. Results are printed in input
+/// order as soon as every earlier file has finished.
+fn run_batch(
+    pdfium: Option<&Pdfium>,
+    cli: &Cli,
+    opts: &BatchOptions,
+    doi: Option<&DoiLookup>,
+    totals: &Mutex<Stats>,
+) -> Result<()> {
     let total = cli.files.len();
-    for (i, file) in cli.files.iter().enumerate() {
-        eprint!("\r[{}/{}] {}", i + 1, total, file.display());
-
-        let result = match process_pdf(pdfium, file, doi_cache, cli.ocr_fallback) {
-            Ok(refs) => BatchResult {
-                file: file.display().to_string(),
-                references: Some(refs),
-                error: None,
-            },
-            Err(e) => BatchResult {
-                file: file.display().to_string(),
-                references: None,
-                error: Some(format!("{e:#}")),
-            },
-        };
-        println!("{}", serde_json::to_string(&result)?);
-    }
-    eprintln!();
-    Ok(())
+    let workers = batch_workers(cli.jobs, total);
+    // Bounded so extraction can't run far ahead of the workers.
+    let (job_tx, job_rx) = mpsc::sync_channel::<(usize, Result<Document>)>(workers);
+    let job_rx = Mutex::new(job_rx);
+    let (result_tx, result_rx) = mpsc::channel::<(usize, BatchResult)>();
+
+    thread::scope(|s| {
+        for _ in 0..workers {
+            let result_tx = result_tx.clone();
+            let job_rx = &job_rx;
+            s.spawn(move || {
+                loop {
+                    let job = job_rx.lock().unwrap().recv();
+                    let Ok((idx, document)) = job else { break };
+                    let refs = document.map(|doc| {
+                        let label = input_label(&cli.files[idx]);
+                        references_from_document(&doc, &label, opts, doi, cli, totals)
+                    });
+                    if result_tx.send((idx, batch_result(&cli.files[idx], refs))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(result_tx);
+        let printer = s.spawn(move || print_in_order(result_rx, cli));
+
+        for (i, file) in cli.files.iter().enumerate() {
+            eprint!("\r[{}/{}] {}", i + 1, total, input_label(file));
+            let document = load_document(pdfium, file, cli);
+            if job_tx.send((i, document)).is_err() {
+                break;
+            }
+        }
+        drop(job_tx);
+        let printed = printer.join().unwrap();
+        eprintln!();
+        printed
+    })
 }
 
-fn process_pdf(
-    pdfium: &Pdfium,
-    file: &Path,
-    doi_cache: &Option<doi::DoiCache>,
-    ocr_fallback: bool,
-) -> Result<Vec<ParsedReference>> {
-    let page_chars = pdf::extract_chars(pdfium, file, ocr_fallback)?;
-    let all_blocks = build_page_blocks(&page_chars);
-    let body_font_size = zones::compute_body_font_size(&all_blocks);
-    let zoned_pages = classify_all_pages(&page_chars, &all_blocks, body_font_size);
-    let raw_refs = collect::collect_references(&zoned_pages);
-    let raw_refs = split_semicolon_subrefs(raw_refs);
-    let mut parsed = parse_all_references(&raw_refs);
-    resolve_ibid_journals(&mut parsed);
-    if let Some(cache) = doi_cache {
-        doi::enrich_dois(&mut parsed, cache);
-    }
-    Ok(parsed)
-}
-
-const DEFAULT_PDFIUM_PATHS: &[&str] = &[
-    "/usr/local/lib/libpdfium.so",
-    "/usr/lib/libpdfium.so",
-    "/usr/local/lib/libpdfium.dylib",
-    "/usr/lib/libpdfium.dylib",
-];
-
-fn bind_pdfium(pdfium_path: &Option<String>) -> Result<Pdfium> {
-    let bindings = if let Some(path) = pdfium_path {
-        Pdfium::bind_to_library(path)
-            .with_context(|| format!("Failed to load pdfium from: {path}"))?
-    } else if let Ok(bindings) = Pdfium::bind_to_system_library() {
-        bindings
+/// Worker count for batch mode: `--jobs`, or the number of cores when 0,
+/// never more than the number of files.
+fn batch_workers(jobs: usize, total: usize) -> usize {
+    let jobs = if jobs == 0 {
+        thread::available_parallelism().map_or(1, |n| n.get())
     } else {
-        try_default_pdfium_paths()?
+        jobs
     };
-    Ok(Pdfium::new(bindings))
+    jobs.min(total).max(1)
 }
 
-fn try_default_pdfium_paths() -> Result<Box<dyn PdfiumLibraryBindings>> {
-    for path in DEFAULT_PDFIUM_PATHS {
-        if let Ok(bindings) = Pdfium::bind_to_library(path) {
-            return Ok(bindings);
-        }
+fn batch_result(file: &Path, refs: Result<Extraction>) -> BatchResult {
+    BatchResult::new(input_label(file), refs.map_err(|e| format!("{e:#}")))
+}
+
+/// Print batch results in input order, buffering results that finish
+/// before their predecessors. With `--output-dir`, each result (errors
+/// included) goes to its own file instead.
+fn print_in_order(results: mpsc::Receiver<(usize, BatchResult)>, cli: &Cli) -> Result<()> {
+    if cli.format == OutputFormat::Csv {
+        print!("{}", output::csv_header());
     }
-    anyhow::bail!(
-        "Failed to find pdfium. Searched system library path and {:?}. \
-         Use --pdfium-path or set PDFIUM_LIB_PATH.",
-        DEFAULT_PDFIUM_PATHS
-    )
-}
-
-fn build_page_blocks(page_chars: &[types::PageChars]) -> Vec<Vec<types::Block>> {
-    page_chars.iter().map(layout::group_page).collect()
-}
-
-fn classify_all_pages(
-    page_chars: &[types::PageChars],
-    all_blocks: &[Vec<types::Block>],
-    body_font_size: f32,
-) -> Vec<Vec<types::ZonedBlock>> {
-    page_chars
-        .iter()
-        .zip(all_blocks.iter())
-        .map(|(pc, blocks)| {
-            zones::classify_page(blocks, pc.page_num, pc.height, body_font_size)
-        })
-        .collect()
-}
-
-fn parse_all_references(
-    raw_refs: &[types::RawReference],
-) -> Vec<ParsedReference> {
-    raw_refs
-        .iter()
-        .flat_map(|raw| {
-            let tokens = tokenizer::tokenize(&raw.text);
-            parse::parse_references(raw, &tokens)
-        })
-        .collect()
-}
-
-/// Split reference entries that contain semicolons into sub-references.
-/// In HEP papers, semicolons within a single numbered reference entry
-/// typically separate distinct citations (e.g., "[1] Author1; Author2").
-fn split_semicolon_subrefs(
-    refs: Vec<types::RawReference>,
-) -> Vec<types::RawReference> {
-    let mut result = Vec::new();
-    for raw in refs {
-        if !raw.text.contains(';') {
-            result.push(raw);
-            continue;
-        }
-        let parts: Vec<&str> = raw.text.split(';').collect();
-        if parts.len() <= 1 {
-            result.push(raw);
-            continue;
-        }
-        // Only split if sub-parts look like citations
-        let subrefs: Vec<&str> = parts
-            .iter()
-            .map(|p| p.trim())
-            .filter(|p| !p.is_empty())
-            .collect();
-        if subrefs.len() <= 1 {
-            result.push(raw);
-            continue;
+    let file_names = cli.output_dir.as_ref().map(|_| output::output_file_names(&cli.files));
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+    for (idx, result) in results {
+        pending.insert(idx, result);
+        while let Some(result) = pending.remove(&next) {
+            match (&cli.output_dir, &file_names) {
+                (Some(dir), Some(names)) => {
+                    output::write_json_file(&dir.join(&names[next]), &result, cli.pretty)?
+                }
+                _ => print_batch_result(&result, cli)?,
+            }
+            next += 1;
         }
-        // Check: at least 2 sub-parts should look like citations
-        let citation_count = subrefs.iter().filter(|s| looks_like_citation(s)).count();
-        if citation_count < 2 {
-            result.push(raw);
-            continue;
+    }
+    Ok(())
+}
+
+/// JSON batches are NDJSON, one `BatchResult` per line. Record formats
+/// concatenate every file's references; per-file errors go to stderr.
+fn print_batch_result(result: &BatchResult, cli: &Cli) -> Result<()> {
+    if cli.format == OutputFormat::Json {
+        batch::write_result(std::io::stdout().lock(), result)?;
+        return Ok(());
+    }
+    if let Some(error) = &result.error {
+        eprintln!("\n{}: {error}", result.file);
+    }
+    if let Some(refs) = &result.references {
+        print_output(refs, &result.file, cli)?;
+    }
+    Ok(())
+}
+
+/// Everything after loading: layout, zoning, collection, parsing and DOI
+/// enrichment (`batch::references_from_raw`). Needs no pdfium access, so
+/// it can run on any thread. Warnings go to stderr; with `--stats`, the
+/// document's counts are added to `totals`.
+fn references_from_document(
+    document: &Document,
+    label: &str,
+    opts: &BatchOptions,
+    doi: Option<&DoiLookup>,
+    cli: &Cli,
+    totals: &Mutex<Stats>,
+) -> Extraction {
+    let extraction = match document {
+        Document::Pages(page_chars) => {
+            batch::references_from_zoned(&zones::zone_pages(page_chars, &opts.layout), opts, doi)
         }
-        for subref in &subrefs {
-            result.push(types::RawReference {
-                text: subref.to_string(),
-                linemarker: raw.linemarker.clone(),
-                source: raw.source,
-                page_num: raw.page_num,
-            });
+        Document::Text(_) | Document::Bbl(_) => {
+            batch::references_from_raw(raw_references(document, opts), &HashMap::new(), 0, opts, doi)
         }
+    };
+    for issue in &extraction.marker_issues {
+        eprintln!("warning: {label}: {issue}");
+    }
+    for warning in &extraction.warnings {
+        eprintln!("warning: {label}: {warning}");
+    }
+    if cli.stats {
+        totals.lock().unwrap().add(&extraction.stats);
     }
-    result
-}
-
-/// Heuristic: does this text fragment look like a citation?
-/// Checks for patterns common in HEP references.
-fn looks_like_citation(text: &str) -> bool {
-    use once_cell::sync::Lazy;
-    use regex::Regex;
-    static YEAR_RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"(?:19|20)\d{2}").unwrap());
-    static ARXIV_RE: Lazy<Regex> =
-        Lazy::new(|| Regex::new(r"(?:arXiv|hep-|astro-|gr-qc|cond-mat|nucl-|math-|quant-ph|physics/)").unwrap());
-
-    YEAR_RE.is_match(text)
-        || ARXIV_RE.is_match(text)
-        || text.contains("doi")
-        || text.contains("DOI")
-        || text.contains("Preprint")
-        || text.contains("preprint")
-}
-
-/// Resolve ibid placeholders from semicolon-split references.
-/// When parse.rs finds a standalone "ibid. V, P (Y)" ref, it sets
-/// journal_title to "ibid". Here we replace that with the actual journal
-/// from the nearest prior ref with the same linemarker.
-fn resolve_ibid_journals(refs: &mut [ParsedReference]) {
-    for i in 1..refs.len() {
-        if refs[i].journal_title.as_deref() != Some("ibid") {
-            continue;
+    extraction
+}
+
+/// Collected references, one per entry; `split_semicolon_subrefs` turns
+/// them into what the parser sees.
+fn raw_references(document: &Document, opts: &BatchOptions) -> Vec<types::RawReference> {
+    match document {
+        Document::Pages(page_chars) => {
+            batch::collect_raw_references(&zones::zone_pages(page_chars, &opts.layout), opts).0
         }
-        let linemarker = &refs[i].linemarker;
-        for j in (0..i).rev() {
-            if refs[j].linemarker != *linemarker {
-                continue;
-            }
-            match refs[j].journal_title.as_deref() {
-                Some("ibid") | None => continue,
-                Some(_) => {
-                    refs[i].journal_title = refs[j].journal_title.clone();
-                    break;
-                }
-            }
+        Document::Text(text) => {
+            refextract::text_raw_references(&refextract::split_text_entries(text))
         }
+        Document::Bbl(bbl) => refextract::bbl::raw_references(bbl),
     }
 }
 
-fn print_output(parsed: &[ParsedReference], pretty: bool) -> Result<()> {
-    let json = if pretty {
-        serde_json::to_string_pretty(parsed)?
-    } else {
-        serde_json::to_string(parsed)?
-    };
-    println!("{json}");
+/// Every pipeline setting taken from the command line.
+fn batch_options<'a>(cli: &Cli, collaborations: &'a kb::CollaborationKb) -> BatchOptions<'a> {
+    BatchOptions {
+        extract: extract_options(cli),
+        layout: layout_config(cli),
+        collect: collect::CollectOptions { ref_headings: cli.ref_heading.clone() },
+        parse: ParseOptions { keep_journal_without_volume: cli.no_journal_clearing, collaborations },
+        refs: batch::RefOptions {
+            no_subrefs: cli.no_subrefs,
+            cited_on_pages: cli.cited_on_pages,
+            exclude_footnotes_without_markers: cli.exclude_footnotes_without_markers,
+            title_from_italics: cli.title_from_italics,
+            max_refs: cli.max_refs,
+            first_author_only: cli.first_author_only,
+            min_confidence: cli.min_confidence,
+            dedup: cli.dedup,
+            sort: cli.sort,
+            arxiv_prefix: cli.arxiv_prefix,
+            no_raw: cli.no_raw,
+            only_with_doi: cli.only_with_doi,
+            only_with_arxiv: cli.only_with_arxiv,
+        },
+    }
+}
+
+fn layout_config(cli: &Cli) -> layout::LayoutConfig {
+    layout::LayoutConfig {
+        superscript_ratio: cli.superscript_ratio,
+        footnote_band: cli.footnote_band,
+        footnote_font_ratio: cli.footnote_font_ratio,
+        word_gap_ratio: cli.word_gap_ratio,
+        ..Default::default()
+    }
+}
+
+/// Print one document's references. `file` feeds the CSV `file` column.
+fn print_output(parsed: &[ParsedReference], file: &str, cli: &Cli) -> Result<()> {
+    match cli.format {
+        OutputFormat::Json => {
+            let json = if cli.pretty {
+                serde_json::to_string_pretty(parsed)?
+            } else {
+                serde_json::to_string(parsed)?
+            };
+            println!("{json}");
+        }
+        OutputFormat::Ris => print!("{}", output::to_ris(parsed)),
+        OutputFormat::Csv => print!("{}", output::to_csv_rows(file, parsed)),
+    }
     Ok(())
 }
 
-fn print_debug_layout(zoned_pages: &[Vec<types::ZonedBlock>]) {
-    for page_blocks in zoned_pages {
+/// Print each reference followed by its tokens as `[Kind:"text"(→normalized)]`.
+fn print_debug_tokens(raw_refs: &[types::RawReference]) {
+    for raw in raw_refs {
+        let marker = raw.linemarker.as_deref().unwrap_or("-");
+        println!("[{marker}] p{} {:?}: {}", raw.page_num, raw.source, raw.text);
+        let tokens: Vec<String> = tokenizer::tokenize(&raw.text)
+            .iter()
+            .map(|t| match &t.normalized {
+                Some(n) => format!("[{:?}:{:?}(→{n})]", t.kind, t.text),
+                None => format!("[{:?}:{:?}]", t.kind, t.text),
+            })
+            .collect();
+        println!("  {}", tokens.join(" "));
+    }
+}
+
+fn print_debug_layout(page_chars: &[types::PageChars], config: &layout::LayoutConfig) {
+    let zoned_pages = zones::zone_pages(page_chars, config);
+    for (page, page_blocks) in page_chars.iter().zip(&zoned_pages) {
+        match layout::column_boundary(page, config) {
+            Some(x) => println!("p{} columns: two, split at x={x:.1}", page.page_num),
+            None => println!("p{} columns: single column", page.page_num),
+        }
         for zb in page_blocks {
             let zone_label = format!("{:?}", zb.zone);
             let text = zb.block.text();
@@ -1,20 +1,49 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::types::{RawReference, ReferenceSource, ZoneKind, ZonedBlock};
+use crate::tokenizer;
+use crate::types::{Line, RawReference, ReferenceSource, ZoneKind, ZonedBlock};
 
 /// Line marker patterns: [1], (1), 1., 1), [Author+Year] at the start of a line.
 /// Bracketed/paren forms allow up to 4 digits (review papers with 2000+ refs).
 /// Bare-number variants (N./N)) limited to 1-3 digits to avoid matching years like "2024.".
 /// Bare variants also require trailing whitespace/EOL to reject decimals like "0.01".
 /// Author-year markers: [Aal+12], [ABG14], [Kim+15a], [ATL14a], [CMS15c].
+/// Letter and roman-numeral markers (a), b., iii.) are capture group 5; they
+/// only count as markers when citation content follows (`is_marker_line`).
 pub(crate) static LINE_MARKER_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r"^\s*(?:\[(\d{1,4})\]|\((\d{1,4})\)|(\d{1,3})[.\)](?:\s|$)|\[([A-Z][\p{L}+]{0,7}\d{2}[a-z]?)\])\s*",
+        r"^\s*(?:\[(\d{1,4})\]|\((\d{1,4})\)|(\d{1,3})[.\)](?:\s|$)|\[([A-Z][\p{L}+]{0,7}\d{2}[a-z]?)\]|(viii|vii|iii|ii|iv|vi|ix|[a-z])[.\)](?:\s|$))\s*",
     )
     .unwrap()
 });
 
+/// Match a line marker, rejecting letter/roman markers not followed by
+/// citation content ("a) we note that ..." is prose, not a reference).
+pub(crate) fn match_line_marker(line: &str) -> Option<regex::Captures<'_>> {
+    LINE_MARKER_RE.captures(line).filter(|caps| {
+        caps.get(5).is_none() || has_citation_content(&line[caps.get(0).unwrap().end()..])
+    })
+}
+
+pub(crate) fn is_marker_line(line: &str) -> bool {
+    match_line_marker(line).is_some()
+}
+
+/// A run of dashes or underscores standing in for the previous entry's
+/// authors in author-date bibliographies: "———, 2006, Phys. Rev. ...".
+static SAME_AUTHOR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:[—–―-]{2,}|_{2,})").unwrap());
+
+/// The same run inside a joined blob. Two hyphens are left alone there:
+/// "12--15" is a page range.
+static SAME_AUTHOR_INNER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\s(?:[—―]{2,}|-{3,}|_{2,})[\s,.]").unwrap());
+
+/// Whether the text opens with a "same author" dash run.
+pub(crate) fn starts_with_same_author(text: &str) -> bool {
+    SAME_AUTHOR_RE.is_match(text.trim_start())
+}
+
 /// Check if text contains citation-like content (years, journals, arXiv IDs).
 pub(crate) fn has_citation_content(text: &str) -> bool {
     static CITATION_RE: Lazy<Regex> = Lazy::new(|| {
@@ -26,27 +55,64 @@ pub(crate) fn has_citation_content(text: &str) -> bool {
 /// Score a block for citation content. Lines with markers + citations score 2,
 /// lines with just citation content score 1.
 pub(crate) fn score_citation_block(block: &crate::types::Block) -> usize {
-    block
-        .lines
+    block.lines.iter().map(|l| score_citation_line(&l.text())).sum()
+}
+
+fn score_citation_line(text: &str) -> usize {
+    if let Some(caps) = match_line_marker(text) {
+        let end = caps.get(0).unwrap().end();
+        if has_citation_content(&text[end..]) { 2 } else { 0 }
+    } else if has_citation_content(text) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Citation features of a block, computed once after zoning so that
+/// heading verification and page gathering don't rescan its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct BlockFeatures {
+    /// As `score_citation_block`.
+    pub citation_score: usize,
+    /// Lines starting with a reference marker.
+    pub marker_lines: usize,
+    /// Lines with citation content.
+    pub citation_lines: usize,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Lines scanned by `block_features`, for the collection tests.
+    pub(crate) static FEATURE_LINE_SCANS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+pub(crate) fn block_features(block: &crate::types::Block) -> BlockFeatures {
+    let mut features = BlockFeatures::default();
+    for line in &block.lines {
+        #[cfg(test)]
+        FEATURE_LINE_SCANS.with(|n| n.set(n.get() + 1));
+        let text = line.text();
+        features.citation_score += score_citation_line(&text);
+        features.marker_lines += usize::from(is_marker_line(&text));
+        features.citation_lines += usize::from(has_citation_content(&text));
+    }
+    features
+}
+
+/// `block_features` of every block, indexed like `zoned_pages`.
+pub(crate) fn page_features(zoned_pages: &[Vec<ZonedBlock>]) -> Vec<Vec<BlockFeatures>> {
+    zoned_pages
         .iter()
-        .map(|l| {
-            let text = l.text();
-            if let Some(m) = LINE_MARKER_RE.find(&text) {
-                if has_citation_content(&text[m.end()..]) { 2 } else { 0 }
-            } else if has_citation_content(&text) {
-                1
-            } else {
-                0
-            }
-        })
-        .sum()
+        .map(|page| page.iter().map(|zb| block_features(&zb.block)).collect())
+        .collect()
 }
 
 pub(crate) fn count_markers_in_block(block: &crate::types::Block) -> usize {
     block
         .lines
         .iter()
-        .filter(|l| LINE_MARKER_RE.is_match(&l.text()))
+        .filter(|l| is_marker_line(&l.text()))
         .count()
 }
 
@@ -54,12 +120,12 @@ pub(crate) fn has_any_marker(block: &crate::types::Block) -> bool {
     block
         .lines
         .iter()
-        .any(|l| LINE_MARKER_RE.is_match(&l.text()))
+        .any(|l| is_marker_line(&l.text()))
 }
 
 pub(crate) fn count_markers_in_text(text: &str) -> usize {
     text.lines()
-        .filter(|l| LINE_MARKER_RE.is_match(l))
+        .filter(|l| is_marker_line(l))
         .count()
 }
 
@@ -114,16 +180,25 @@ fn collect_marker_block_lines(
 }
 
 /// Blocks with 3+ markers AND citation content — dense reference lists.
+/// An unmarked block opening the next column between two dense blocks is
+/// kept too: it is the end of the entry that wrapped there.
 fn collect_dense_marker_blocks(
     zoned_pages: &[Vec<ZonedBlock>],
 ) -> Vec<(String, usize)> {
     let mut blocks = Vec::new();
     for page_blocks in zoned_pages {
-        for zb in page_blocks {
-            if zb.zone == ZoneKind::Header || zb.zone == ZoneKind::PageNumber {
-                continue;
-            }
-            if is_dense_ref_block(&zb.block) {
+        let page_blocks: Vec<&ZonedBlock> = page_blocks
+            .iter()
+            .filter(|zb| zb.zone != ZoneKind::Header && zb.zone != ZoneKind::PageNumber)
+            .collect();
+        let dense: Vec<bool> = page_blocks.iter().map(|zb| is_dense_ref_block(&zb.block)).collect();
+        for (i, zb) in page_blocks.iter().enumerate() {
+            let column_wrap = i > 0
+                && dense[i - 1]
+                && dense.get(i + 1) == Some(&true)
+                && !has_any_marker(&zb.block)
+                && continues_in_next_column(&page_blocks[i - 1].block, &zb.block);
+            if dense[i] || column_wrap {
                 blocks.push((zb.block.text(), zb.page_num));
             }
         }
@@ -131,6 +206,13 @@ fn collect_dense_marker_blocks(
     blocks
 }
 
+/// `next` opens the column right of `prev`: it starts past `prev`'s middle
+/// and above its last line, as text wrapping from the foot of the left
+/// column to the top of the right one does.
+fn continues_in_next_column(prev: &crate::types::Block, next: &crate::types::Block) -> bool {
+    next.x > prev.x + prev.width / 2.0 && next.y > prev.y - prev.height
+}
+
 /// A block qualifies as a dense reference block if it has enough markers
 /// and citation content. For author-date papers, lines rarely start with
 /// `(year)` so we also accept blocks with very high citation density.
@@ -164,6 +246,8 @@ fn collect_trailing_marker_blocks(
     let mut blocks = Vec::new();
     let mut pages_without_refs = 0;
 
+    // Pages are scanned from the back; each is prepended whole, so its
+    // blocks (left column before right) keep their reading order.
     for page_blocks in zoned_pages.iter().rev() {
         let mut page_has_markers = false;
         let mut page_citation_lines = 0;
@@ -192,7 +276,7 @@ fn collect_trailing_marker_blocks(
         let page_has_refs =
             page_has_markers || (!blocks.is_empty() && has_citation_density);
         if page_has_refs {
-            blocks.extend(page_blocks_collected);
+            blocks.splice(0..0, page_blocks_collected);
             pages_without_refs = 0;
         } else {
             pages_without_refs += 1;
@@ -213,8 +297,6 @@ fn collect_trailing_marker_blocks(
     if total_markers < 5 {
         return Vec::new();
     }
-
-    blocks.reverse();
     blocks
 }
 
@@ -244,6 +326,84 @@ fn collect_superscript_marker_refs(
         .collect()
 }
 
+/// Nature-style lists number references with a superscript at the start of
+/// each line ("¹² Smith, J. Nature 400, 1"), which LINE_MARKER_RE can't see.
+/// Rewrite those numbers as "12." markers so the regular marker logic
+/// applies. Only a monotonically increasing run of at least 5 numbers is
+/// trusted, which rules out exponents and stray superscripts. Footnotes are
+/// left alone. Returns None when there is no such run.
+pub(crate) fn with_superscript_line_markers(
+    zoned_pages: &[Vec<ZonedBlock>],
+) -> Option<Vec<Vec<ZonedBlock>>> {
+    let mut candidates = Vec::new();
+    for (p, page) in zoned_pages.iter().enumerate() {
+        for (b, zb) in page.iter().enumerate() {
+            if matches!(zb.zone, ZoneKind::Header | ZoneKind::PageNumber | ZoneKind::Footnote) {
+                continue;
+            }
+            for (l, line) in zb.block.lines.iter().enumerate() {
+                if let Some(num) = superscript_line_number(line) {
+                    candidates.push(((p, b, l), num));
+                }
+            }
+        }
+    }
+    let run = longest_increasing_run(&candidates);
+    if run.len() < 5 {
+        return None;
+    }
+    let mut marked = zoned_pages.to_vec();
+    for (p, b, l) in run {
+        let word = &mut marked[p][b].block.lines[l].words[0];
+        let digits = word.text.bytes().take_while(u8::is_ascii_digit).count();
+        let rest = word.text[digits..].to_string();
+        word.text = if rest.is_empty() {
+            format!("{}.", &word.text[..digits])
+        } else {
+            format!("{}. {rest}", &word.text[..digits])
+        };
+    }
+    Some(marked)
+}
+
+/// A 1-3 digit superscript number opening a line that has text after it.
+fn superscript_line_number(line: &Line) -> Option<u32> {
+    let first = line.words.first().filter(|w| w.is_superscript)?;
+    let digits = first.text.bytes().take_while(u8::is_ascii_digit).count();
+    if !(1..=3).contains(&digits) {
+        return None;
+    }
+    let rest = &first.text[digits..];
+    let has_text_after = line.words.len() > 1 || rest.starts_with(char::is_alphabetic);
+    if !has_text_after || !(rest.is_empty() || rest.starts_with(char::is_alphabetic)) {
+        return None;
+    }
+    first.text[..digits].parse().ok().filter(|&n| n > 0)
+}
+
+/// Longest chain of numbers increasing by 1 (or 2, tolerating one missed
+/// marker). Numbers that don't continue the chain are skipped as exponents;
+/// a new chain starts at "1" or when there is none yet.
+fn longest_increasing_run<K: Copy>(candidates: &[(K, u32)]) -> Vec<K> {
+    let mut best: Vec<(K, u32)> = Vec::new();
+    let mut run: Vec<(K, u32)> = Vec::new();
+    for &(key, num) in candidates {
+        let extends = run.last().is_some_and(|&(_, prev)| num > prev && num - prev <= 2);
+        if extends {
+            run.push((key, num));
+        } else if run.is_empty() || num == 1 {
+            if run.len() > best.len() {
+                best = std::mem::take(&mut run);
+            }
+            run = vec![(key, num)];
+        }
+    }
+    if run.len() > best.len() {
+        best = run;
+    }
+    best.into_iter().map(|(key, _)| key).collect()
+}
+
 /// Find pairs of (bare_number, citation_text) from the tail of the document.
 fn find_superscript_pairs(
     all_blocks: &[&ZonedBlock],
@@ -264,8 +424,8 @@ fn find_superscript_pairs(
 
         if let Some(caps) = bare_num_re.captures(trimmed) {
             let num: u32 = caps[1].parse().unwrap_or(0);
-            // Skip year-like numbers (1900-2099) — not reference markers
-            if (1900..2100).contains(&num) {
+            // Skip year-like numbers — not reference markers
+            if tokenizer::is_plausible_year(num) {
                 gap += 1;
                 continue;
             }
@@ -307,7 +467,7 @@ fn collect_citation_after(all_blocks: &[&ZonedBlock], start: usize) -> String {
         // Stop at bare numbers that aren't years (next reference marker)
         if let Some(caps) = BARE_NUM.captures(trimmed) {
             let num: u32 = caps[1].parse().unwrap_or(0);
-            if !(1900..2100).contains(&num) {
+            if !tokenizer::is_plausible_year(num) {
                 break;
             }
         }
@@ -324,7 +484,7 @@ fn is_valid_trailing_cluster(blocks: &[(String, usize)]) -> bool {
     let mut citation_lines = 0;
     for (text, _) in blocks {
         for line in text.lines() {
-            if LINE_MARKER_RE.is_match(line) {
+            if is_marker_line(line) {
                 total_markers += 1;
                 let after = LINE_MARKER_RE.replace(line, "");
                 if has_citation_content(after.trim()) {
@@ -337,6 +497,9 @@ fn is_valid_trailing_cluster(blocks: &[(String, usize)]) -> bool {
 }
 
 /// Split concatenated text blocks into individual references by line markers.
+/// Marker-less lines at the top of a page continue the previous reference
+/// only if the previous page ended mid-sentence; otherwise they start a new
+/// unmarked reference instead of being glued onto a finished one.
 pub(crate) fn split_into_references(
     blocks: &[(String, usize)],
     source: ReferenceSource,
@@ -345,6 +508,8 @@ pub(crate) fn split_into_references(
     let mut current_text = String::new();
     let mut current_marker: Option<String> = None;
     let mut current_page = 0;
+    let mut last_page = None;
+    let mut last_line = "";
 
     for (text, page_num) in blocks {
         for line in text.split('\n') {
@@ -352,11 +517,13 @@ pub(crate) fn split_into_references(
             if line.is_empty() {
                 continue;
             }
-            if let Some(caps) = LINE_MARKER_RE.captures(line) {
+            let new_page = last_page.is_some_and(|p| p != *page_num);
+            last_page = Some(*page_num);
+            let prev_line = std::mem::replace(&mut last_line, line);
+            if let Some(caps) = match_line_marker(line) {
                 if is_year_continuation(&caps, line) && !current_text.is_empty() {
                     // "(2011)." on its own line — append to current ref
-                    current_text.push(' ');
-                    current_text.push_str(line);
+                    append_line(&mut current_text, line);
                 } else {
                     flush_reference(
                         &mut refs,
@@ -370,9 +537,21 @@ pub(crate) fn split_into_references(
                         LINE_MARKER_RE.replace(line, "").trim().to_string();
                     current_page = *page_num;
                 }
+            } else if !current_text.is_empty()
+                && (starts_with_same_author(line) || new_page && ends_sentence(prev_line))
+            {
+                flush_reference(
+                    &mut refs,
+                    &mut current_text,
+                    &current_marker,
+                    current_page,
+                    source,
+                );
+                current_marker = None;
+                current_text = line.to_string();
+                current_page = *page_num;
             } else if !current_text.is_empty() {
-                current_text.push(' ');
-                current_text.push_str(line);
+                append_line(&mut current_text, line);
             } else {
                 current_text = line.to_string();
                 current_page = *page_num;
@@ -384,6 +563,51 @@ pub(crate) fn split_into_references(
     refs
 }
 
+/// Append a continuation line to a reference. Lines are joined with a
+/// space, except where a DOI, URL or arXiv ID was wrapped mid-identifier
+/// ("10.1103/PhysRevD." + "72.052002"): then the halves are glued back.
+fn append_line(text: &mut String, line: &str) {
+    let prev = text.rsplit(char::is_whitespace).next().unwrap_or("");
+    let next = line.split_whitespace().next().unwrap_or("");
+    if !continues_identifier(prev, next) {
+        text.push(' ');
+    }
+    text.push_str(line);
+}
+
+/// `prev` ends a line inside an identifier and `next` carries on with it.
+/// Only identifiers with a recognizable start are considered, and only breaks
+/// after ".", "/", "-" or "_", so an identifier ending a sentence ("...052002.")
+/// is only glued when the next word also looks like an identifier fragment
+/// (it contains a digit or a slash).
+fn continues_identifier(prev: &str, next: &str) -> bool {
+    static IDENTIFIER_START_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?i)(?:10\.\d{4,9}/|https?://|arxiv:)").unwrap());
+    let fragment = next.trim_end_matches([',', ';', '.', ')']);
+    IDENTIFIER_START_RE.is_match(prev)
+        && prev.ends_with(['.', '/', '-', '_'])
+        && next.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && fragment.contains(|c: char| c.is_ascii_digit() || c == '/')
+}
+
+/// Whether a line ends with sentence-final punctuation. A trailing period
+/// after an initial ("J.") or a journal abbreviation ("Phys. Rev.") does not
+/// end the reference.
+fn ends_sentence(line: &str) -> bool {
+    let line = line.trim_end();
+    if line.ends_with(['?', '!']) {
+        return true;
+    }
+    let Some(without_period) = line.strip_suffix('.') else {
+        return false;
+    };
+    let last_token = without_period.split_whitespace().last().unwrap_or("");
+    let is_abbreviation = last_token.len() <= 5
+        && last_token.starts_with(|c: char| c.is_ascii_uppercase())
+        && last_token.chars().all(|c| c.is_alphabetic() || c == '.');
+    !last_token.is_empty() && !is_abbreviation && last_token != "al"
+}
+
 fn split_author_date_blobs(refs: &mut Vec<RawReference>) {
     let mut i = 0;
     while i < refs.len() {
@@ -392,13 +616,17 @@ fn split_author_date_blobs(refs: &mut Vec<RawReference>) {
             if splits.len() >= 2 {
                 let source = refs[i].source;
                 let page = refs[i].page_num;
+                let section = refs[i].section;
                 let new_refs: Vec<RawReference> = splits
                     .into_iter()
                     .map(|t| RawReference {
                         text: t,
                         linemarker: None,
+                        from_marker: None,
                         source,
                         page_num: page,
+                        section,
+                        italic_runs: Vec::new(),
                     })
                     .collect();
                 refs.splice(i..i + 1, new_refs);
@@ -418,6 +646,12 @@ static AUTHOR_START_RE: Lazy<Regex> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Whether the text opens with an author name in "Surname, I." form,
+/// as author-date bibliography entries do.
+pub(crate) fn starts_with_author(text: &str) -> bool {
+    AUTHOR_START_RE.find(text.trim_start()).is_some_and(|m| m.start() == 0)
+}
+
 /// Match "Surname I." pattern (no comma between surname and initial).
 static AUTHOR_START_NOCOMMA_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"[A-Z][a-z]{2,}(?:[\s-][A-Z][a-z]+)* [A-Z]\.").unwrap()
@@ -469,6 +703,13 @@ fn find_author_split_positions(text: &str) -> Vec<usize> {
         }
     }
 
+    for m in SAME_AUTHOR_INNER_RE.find_iter(text) {
+        let pos = m.start() + 1;
+        if validate_split_position(text, pos).is_some() && !positions.contains(&pos) {
+            positions.push(pos);
+        }
+    }
+
     // Bibliography labels: "Surname et al. YYYY:" or "Surname and Foo YYYY:"
     for pos in find_biblio_label_positions(text) {
         if !positions.contains(&pos) {
@@ -620,7 +861,7 @@ fn is_ref_ending_period(before: &str) -> bool {
     !is_initial_token(clean)
 }
 
-fn is_initial_token(token: &str) -> bool {
+pub(crate) fn is_initial_token(token: &str) -> bool {
     if token.is_empty() {
         return false;
     }
@@ -636,7 +877,7 @@ fn is_initial_token(token: &str) -> bool {
 fn is_year_continuation(caps: &regex::Captures, line: &str) -> bool {
     if let Some(m) = caps.get(2) {
         let num: u32 = m.as_str().parse().unwrap_or(0);
-        if (1900..2100).contains(&num) {
+        if tokenizer::is_plausible_year(num) {
             let rest = LINE_MARKER_RE.replace(line, "");
             let trimmed = rest.trim();
             if trimmed.len() < 40 {
@@ -650,11 +891,24 @@ fn is_year_continuation(caps: &regex::Captures, line: &str) -> bool {
     false
 }
 
+/// Split a leading line marker off a single reference line:
+/// "[12] A. Author, ..." → (Some("12"), "A. Author, ...").
+pub(crate) fn split_line_marker(line: &str) -> (Option<String>, String) {
+    match match_line_marker(line) {
+        Some(caps) => {
+            let rest = line[caps.get(0).unwrap().end()..].trim().to_string();
+            (extract_marker(&caps), rest)
+        }
+        None => (None, line.to_string()),
+    }
+}
+
 fn extract_marker(caps: &regex::Captures) -> Option<String> {
     caps.get(1)
         .or_else(|| caps.get(2))
         .or_else(|| caps.get(3))
         .or_else(|| caps.get(4))
+        .or_else(|| caps.get(5))
         .map(|m| m.as_str().to_string())
 }
 
@@ -670,9 +924,191 @@ fn flush_reference(
         refs.push(RawReference {
             text: trimmed,
             linemarker: marker.clone(),
+            from_marker: None,
             source,
             page_num,
+            section: 0,
+            italic_runs: Vec::new(),
         });
     }
     text.clear();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letter_and_roman_markers() {
+        let blocks = vec![(
+            "a) A. Author, Phys. Rev. D 72, 052002 (2005)\nb) B. Other, Nucl. Phys. B 417 (1994) 181\niii. C. Third, JHEP 05 (2010) 1"
+                .to_string(),
+            3,
+        )];
+        let refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+        let markers: Vec<_> = refs.iter().map(|r| r.linemarker.as_deref()).collect();
+        assert_eq!(markers, vec![Some("a"), Some("b"), Some("iii")]);
+        assert!(refs[0].text.starts_with("A. Author, Phys. Rev."));
+    }
+
+    #[test]
+    fn wrapped_identifiers_are_rejoined() {
+        let blocks = vec![(
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), doi:10.1103/PhysRevD.\n72.052002.\n\
+             [2] B. Other, https://arxiv.org/abs/hep-\nph/0202058\n\
+             [3] C. Third, doi:10.1088/1126-6708/2006/05/026.\nPhys. Lett. B 716, 1 (2012)"
+                .to_string(),
+            3,
+        )];
+        let refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+        assert_eq!(refs[0].text, "A. Author, Phys. Rev. D 72, 052002 (2005), doi:10.1103/PhysRevD.72.052002.");
+        assert_eq!(refs[1].text, "B. Other, https://arxiv.org/abs/hep-ph/0202058");
+        // A complete DOI ending the sentence is not glued onto the next citation
+        assert_eq!(refs[2].text, "C. Third, doi:10.1088/1126-6708/2006/05/026. Phys. Lett. B 716, 1 (2012)");
+
+        let tokens = tokenizer::tokenize(&refs[0].text);
+        let doi = tokens.iter().find(|t| t.kind == crate::types::TokenKind::Doi).unwrap();
+        assert_eq!(doi.text, "10.1103/PhysRevD.72.052002");
+    }
+
+    #[test]
+    fn reference_continues_across_page_break() {
+        let blocks = vec![
+            ("[16] B. Other, JHEP 05 (2010) 1.\n[17] A. Author, Phys. Rev.".to_string(), 3),
+            ("D 72, 052002 (2005).\n[18] C. Third, Nucl. Phys. B 417 (1994) 181.".to_string(), 4),
+        ];
+        let refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs[1].linemarker.as_deref(), Some("17"));
+        assert_eq!(refs[1].text, "A. Author, Phys. Rev. D 72, 052002 (2005).");
+        assert_eq!(refs[1].page_num, 3);
+    }
+
+    #[test]
+    fn finished_reference_does_not_absorb_next_page() {
+        let blocks = vec![
+            ("[17] A. Author, Phys. Rev. D 72, 052002 (2005).".to_string(), 3),
+            ("Appendix text that was not skipped\n[18] C. Third, Nucl. Phys. B 417 (1994) 181.".to_string(), 4),
+        ];
+        let refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+        assert_eq!(refs[0].text, "A. Author, Phys. Rev. D 72, 052002 (2005).");
+        assert_eq!(refs[1].linemarker, None);
+        assert_eq!(refs[2].linemarker.as_deref(), Some("18"));
+    }
+
+    #[test]
+    fn dash_entries_repeat_the_previous_authors() {
+        let blocks = vec![(
+            "Smith, J. 2005, Phys. Rev. D 72, 052002\n\
+             ———, 2006, Phys. Rev. Lett. 96, 011101\n\
+             ---. 2007, Nucl. Phys. B 417, 181"
+                .to_string(),
+            3,
+        )];
+        let refs = split_into_references(&blocks, ReferenceSource::ReferenceSection);
+        let texts: Vec<&str> = refs.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "Smith, J. 2005, Phys. Rev. D 72, 052002",
+                "———, 2006, Phys. Rev. Lett. 96, 011101",
+                "---. 2007, Nucl. Phys. B 417, 181",
+            ]
+        );
+
+        let mut parsed = crate::parse_all_references(&refs, &Default::default());
+        crate::resolve_same_authors(&mut parsed);
+        let authors: Vec<Option<&str>> = parsed.iter().map(|r| r.authors.as_deref()).collect();
+        assert_eq!(authors, [Some("Smith, J."); 3]);
+        assert_eq!(parsed[2].journal_title.as_deref(), Some("Nucl. Phys. B"));
+
+        // Joined into one blob, the dash run still starts a new reference.
+        let blob = "Smith, J. 2005, On the quantum theory of something long enough to need splitting, \
+                    Phys. Rev. D 72, 052002 ———, 2006, A second title that is also fairly long, \
+                    Phys. Rev. Lett. 96, 011101 ———, 2007, Nucl. Phys. B 417, 181";
+        assert_eq!(split_author_date_text(blob).len(), 3);
+        assert_eq!(split_author_date_text("Smith, J. 2005, Phys. Rev. D 72, 12--15").len(), 1);
+    }
+
+    fn line(words: &[(&str, bool)]) -> Line {
+        let words = words
+            .iter()
+            .enumerate()
+            .map(|(i, &(text, is_superscript))| crate::types::Word {
+                text: text.to_string(),
+                x: i as f32 * 20.0,
+                y: 0.0,
+                width: 18.0,
+                height: 10.0,
+                font_size: if is_superscript { 6.0 } else { 10.0 },
+                is_superscript,
+                is_italic: false,
+                font_name: "Times-Roman".into(),
+            })
+            .collect();
+        Line { words, y: 0.0, x_start: 0.0, x_end: 100.0, font_size: 10.0, font_name: "Times-Roman".into() }
+    }
+
+    fn zoned(lines: Vec<Line>, zone: ZoneKind) -> ZonedBlock {
+        let block = crate::types::Block {
+            lines,
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 100.0,
+            font_size: 10.0,
+        };
+        ZonedBlock { block, zone, page_num: 1 }
+    }
+
+    #[test]
+    fn superscript_line_numbers_become_markers() {
+        let mut lines: Vec<Line> = (1..=6)
+            .map(|n| {
+                let marker = n.to_string();
+                line(&[(&marker, true), ("Smith,", false), ("Nature", false), ("400,", false), ("(1999)", false)])
+            })
+            .collect();
+        // An exponent-like superscript in between is not part of the run
+        lines.insert(3, line(&[("12", true), ("GeV", false)]));
+        let pages = vec![vec![zoned(lines, ZoneKind::Body)]];
+        let marked = with_superscript_line_markers(&pages).expect("run of 6 markers");
+        let texts: Vec<String> = marked[0][0].block.lines.iter().map(|l| l.text()).collect();
+        assert_eq!(texts[0], "1. Smith, Nature 400, (1999)");
+        assert_eq!(texts[3], "12 GeV");
+        assert_eq!(texts[6], "6. Smith, Nature 400, (1999)");
+    }
+
+    #[test]
+    fn superscript_numbered_reference_section() {
+        let heading = zoned(vec![line(&[("References", false)])], ZoneKind::Body);
+        let entries = (1..=5)
+            .map(|n| {
+                let marker = format!("{n}Author,");
+                line(&[(&marker, true), ("A.", false), ("Nature", false), ("400,", false), ("1999.", false)])
+            })
+            .collect();
+        let pages = vec![vec![heading, zoned(entries, ZoneKind::Body)]];
+        let refs = crate::collect::collect_references(&pages);
+        let markers: Vec<_> = refs.iter().map(|r| r.linemarker.as_deref().unwrap_or("")).collect();
+        assert_eq!(markers, ["1", "2", "3", "4", "5"]);
+        assert_eq!(refs[0].text, "Author, A. Nature 400, 1999.");
+    }
+
+    #[test]
+    fn non_monotonic_superscripts_are_ignored() {
+        let lines = [7, 3, 9, 2, 5, 8]
+            .iter()
+            .map(|n| line(&[(&n.to_string(), true), ("text", false)]))
+            .collect();
+        let pages = vec![vec![zoned(lines, ZoneKind::Body)]];
+        assert!(with_superscript_line_markers(&pages).is_none());
+    }
+
+    #[test]
+    fn prose_is_not_a_letter_marker() {
+        assert!(!is_marker_line("e.g. Phys. Rev. D 72 (2005)"));
+        assert!(!is_marker_line("a) we note that the bound is weaker"));
+        assert!(is_marker_line("a) Author, Phys. Rev. Lett. 115, 072001 (2015)"));
+    }
+}
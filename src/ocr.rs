@@ -7,28 +7,52 @@ use pdfium_render::prelude::*;
 
 use crate::types::PdfChar;
 
-const DPI: f32 = 300.0;
-const MIN_CONFIDENCE: i32 = 40;
+pub const DEFAULT_LANG: &str = "eng";
+pub const DEFAULT_DPI: u32 = 300;
+pub const DEFAULT_MIN_CONFIDENCE: i32 = 40;
+/// Font name given to OCR'd characters.
+pub const OCR_FONT_NAME: &str = "OCR";
+
+/// Tesseract settings for OCR fallback.
+#[derive(Debug, Clone)]
+pub struct OcrOptions {
+    /// Tesseract language code(s), e.g. "eng" or "eng+deu".
+    pub lang: String,
+    /// Render resolution for the page bitmap.
+    pub dpi: u32,
+    /// Words with a lower mean confidence (0-100) are dropped.
+    pub min_confidence: i32,
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        OcrOptions {
+            lang: DEFAULT_LANG.to_string(),
+            dpi: DEFAULT_DPI,
+            min_confidence: DEFAULT_MIN_CONFIDENCE,
+        }
+    }
+}
 
-/// Check if tesseract is available (eng traineddata exists).
-pub fn tesseract_available() -> bool {
-    LepTess::new(None, "eng").is_ok()
+/// Check if tesseract is available with traineddata for `lang`.
+pub fn tesseract_available(lang: &str) -> bool {
+    LepTess::new(None, lang).is_ok()
 }
 
 /// OCR a single PDF page: render to bitmap, run tesseract, return PdfChars.
-pub fn ocr_page(page: &PdfPage, page_idx: usize) -> Result<Vec<PdfChar>> {
-    let bitmap = render_page(page, page_idx)?;
+pub fn ocr_page(page: &PdfPage, page_idx: usize, opts: &OcrOptions) -> Result<Vec<PdfChar>> {
+    let bitmap = render_page(page, page_idx, opts.dpi)?;
     let dynamic_image = bitmap.as_image();
     let gray = dynamic_image.to_luma8();
     let tiff_bytes = encode_tiff(&gray)?;
-    let words = run_tesseract(&tiff_bytes)?;
+    let words = run_tesseract(&tiff_bytes, opts)?;
     let page_height_px = bitmap.height() as f32;
     let page_height_pt = page.height().value;
     Ok(words_to_chars(&words, page_height_px, page_height_pt))
 }
 
-fn render_page<'a>(page: &'a PdfPage, page_idx: usize) -> Result<PdfBitmap<'a>> {
-    let scale = DPI / 72.0;
+fn render_page<'a>(page: &'a PdfPage, page_idx: usize, dpi: u32) -> Result<PdfBitmap<'a>> {
+    let scale = dpi as f32 / 72.0;
     let config = PdfRenderConfig::new().scale_page_by_factor(scale);
     page.render_with_config(&config)
         .map_err(|e| anyhow::anyhow!("Failed to render page {} for OCR: {e}", page_idx + 1))
@@ -49,8 +73,9 @@ struct OcrWord {
     h: i32,
 }
 
-fn run_tesseract(tiff_bytes: &[u8]) -> Result<Vec<OcrWord>> {
-    let mut lt = LepTess::new(None, "eng").context("Failed to init tesseract")?;
+fn run_tesseract(tiff_bytes: &[u8], opts: &OcrOptions) -> Result<Vec<OcrWord>> {
+    let mut lt = LepTess::new(None, &opts.lang)
+        .with_context(|| format!("Failed to init tesseract for language {}", opts.lang))?;
     lt.set_image_from_mem(tiff_bytes)
         .map_err(|_| anyhow::anyhow!("Failed to load image into tesseract"))?;
 
@@ -63,7 +88,7 @@ fn run_tesseract(tiff_bytes: &[u8]) -> Result<Vec<OcrWord>> {
         let geo = b.get_geometry();
         lt.set_rectangle(geo.x, geo.y, geo.w, geo.h);
         let conf = lt.mean_text_conf();
-        if conf < MIN_CONFIDENCE {
+        if conf < opts.min_confidence {
             continue;
         }
         let text = match lt.get_utf8_text() {
@@ -119,7 +144,7 @@ fn words_to_chars(
                 width: w_pt,
                 height: h_pt,
                 font_size,
-                font_name: "OCR".to_string(),
+                font_name: OCR_FONT_NAME.to_string(),
             });
         }
 
@@ -132,7 +157,7 @@ fn words_to_chars(
             width: char_w_px * scale,
             height: h_pt,
             font_size,
-            font_name: "OCR".to_string(),
+            font_name: OCR_FONT_NAME.to_string(),
         });
     }
 
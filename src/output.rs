@@ -0,0 +1,325 @@
+//! Non-JSON output formats, and per-input JSON files for `--output-dir`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::parse::split_authors;
+use crate::types::{ParsedReference, RefType};
+
+/// Render references as RIS records (EndNote, Mendeley, Zotero).
+/// Uses CRLF line endings as required by the RIS specification.
+pub fn to_ris(refs: &[ParsedReference]) -> String {
+    let mut out = String::new();
+    for r in refs {
+        write_ris_record(&mut out, r);
+    }
+    out
+}
+
+fn write_ris_record(out: &mut String, r: &ParsedReference) {
+    let ty = match r.ref_type {
+        RefType::Journal => "JOUR",
+        RefType::Book => "BOOK",
+        RefType::Proceedings => "CONF",
+        RefType::Thesis => "THES",
+        RefType::Preprint | RefType::Unknown => "GEN",
+    };
+    push_tag(out, "TY", ty);
+    for name in r.authors_list.iter().flatten() {
+        push_tag(out, "AU", name);
+    }
+    if let Some(editors) = &r.editors {
+        for name in split_authors(editors) {
+            push_tag(out, "ED", &name);
+        }
+    }
+    push_opt(out, "TI", &r.title);
+    push_opt(out, "JO", &r.journal_title);
+    push_opt(out, "VL", &r.journal_volume);
+    push_opt(out, "IS", &r.journal_issue);
+    if let Some(page) = &r.journal_page {
+        let (first, last) = split_page_range(page);
+        push_tag(out, "SP", first);
+        if let Some(last) = last {
+            push_tag(out, "EP", last);
+        }
+    }
+    push_opt(out, "PY", &r.journal_year);
+    push_opt(out, "PB", &r.publisher);
+    push_opt(out, "DO", &r.doi);
+    // Keep arXiv-only references clickable.
+    if r.doi.is_none() {
+        if let Some(arxiv_id) = &r.arxiv_id {
+            let arxiv_id = arxiv_id.trim_start_matches("arXiv:");
+            push_tag(out, "UR", &format!("https://arxiv.org/abs/{arxiv_id}"));
+        } else if let Some(handle) = &r.handle {
+            push_tag(out, "UR", &format!("https://hdl.handle.net/{handle}"));
+        } else if let Some(url) = &r.url {
+            push_tag(out, "UR", url);
+        }
+    }
+    push_opt(out, "SN", &r.isbn);
+    push_tag(out, "ER", "");
+    out.push_str("\r\n");
+}
+
+fn push_tag(out: &mut String, tag: &str, value: &str) {
+    out.push_str(tag);
+    out.push_str("  - ");
+    out.push_str(value);
+    out.push_str("\r\n");
+}
+
+fn push_opt(out: &mut String, tag: &str, value: &Option<String>) {
+    if let Some(v) = value {
+        push_tag(out, tag, v);
+    }
+}
+
+/// Column order of the CSV export.
+const CSV_COLUMNS: [&str; 12] = [
+    "file",
+    "linemarker",
+    "authors",
+    "title",
+    "journal_title",
+    "journal_volume",
+    "journal_year",
+    "journal_page",
+    "doi",
+    "arxiv_id",
+    "report_number",
+    "source",
+];
+
+/// The CSV header row, printed once per output.
+pub fn csv_header() -> String {
+    format!("{}\r\n", CSV_COLUMNS.join(","))
+}
+
+/// One CSV row per reference (RFC 4180 quoting, CRLF line endings). The
+/// `file` column lets batch output from many documents share one sheet.
+pub fn to_csv_rows(file: &str, refs: &[ParsedReference]) -> String {
+    let mut out = String::new();
+    for r in refs {
+        let source = format!("{:?}", r.source);
+        let fields = [
+            Some(file),
+            r.linemarker.as_deref(),
+            r.authors.as_deref(),
+            r.title.as_deref(),
+            r.journal_title.as_deref(),
+            r.journal_volume.as_deref(),
+            r.journal_year.as_deref(),
+            r.journal_page.as_deref(),
+            r.doi.as_deref(),
+            r.arxiv_id.as_deref(),
+            r.report_number.as_deref(),
+            Some(source.as_str()),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_escape(f.unwrap_or(""))).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Quote a field when it contains a delimiter, quote or line break;
+/// embedded quotes are doubled.
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// "1547-1553" → ("1547", Some("1553")); "052002" → ("052002", None).
+fn split_page_range(page: &str) -> (&str, Option<&str>) {
+    match page.split_once(['-', '–', '—']) {
+        Some((first, last)) if !last.trim().is_empty() => (first.trim(), Some(last.trim())),
+        _ => (page.trim(), None),
+    }
+}
+
+/// `--output-dir` file names, one per input: the input's stem plus
+/// `.json`. Inputs sharing a stem get their parent directory's name
+/// prefixed (`a/paper.pdf` -> `a-paper.json`), then a counter if that is
+/// still ambiguous.
+pub fn output_file_names(files: &[PathBuf]) -> Vec<String> {
+    let stems: Vec<String> = files.iter().map(|f| input_stem(f)).collect();
+    let mut names: Vec<String> = files
+        .iter()
+        .zip(&stems)
+        .map(|(file, stem)| {
+            if stems.iter().filter(|s| *s == stem).count() == 1 {
+                return stem.clone();
+            }
+            match file.parent().and_then(Path::file_name) {
+                Some(dir) => format!("{}-{stem}", dir.to_string_lossy()),
+                None => stem.clone(),
+            }
+        })
+        .collect();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for name in &mut names {
+        let count = seen.entry(name.clone()).or_default();
+        *count += 1;
+        if *count > 1 {
+            *name = format!("{name}-{count}");
+        }
+    }
+    names.into_iter().map(|name| format!("{name}.json")).collect()
+}
+
+/// File name without `.gz` and the input extension. Only known extensions
+/// are stripped, so `https://arxiv.org/pdf/1001.0785` keeps its `.0785`.
+fn input_stem(file: &Path) -> String {
+    if file.as_os_str() == "-" {
+        return "stdin".to_string();
+    }
+    let name = file.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    let strip = |name: &str, ext: &str| {
+        let cut = name.len().checked_sub(ext.len())?;
+        (name.is_char_boundary(cut) && name[cut..].eq_ignore_ascii_case(ext)).then(|| name[..cut].to_string())
+    };
+    let name = strip(&name, ".gz").unwrap_or(name);
+    let stem = [".pdf", ".txt", ".bbl"].iter().find_map(|ext| strip(&name, ext)).unwrap_or(name);
+    if stem.is_empty() { "input".to_string() } else { stem }
+}
+
+/// Write `value` as JSON to `path`, replacing an existing file.
+pub fn write_json_file(path: &Path, value: &impl Serialize, pretty: bool) -> Result<()> {
+    let mut json = if pretty { serde_json::to_string_pretty(value)? } else { serde_json::to_string(value)? };
+    json.push('\n');
+    std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RawReference, ReferenceSource};
+
+    fn parse(text: &str) -> ParsedReference {
+        let raw = RawReference {
+            text: text.to_string(),
+            linemarker: None,
+            from_marker: None,
+            source: ReferenceSource::Text,
+            page_num: 0,
+            section: 0,
+            italic_runs: Vec::new(),
+        };
+        let tokens = crate::tokenizer::tokenize(text);
+        crate::parse::parse_references(&raw, &tokens).remove(0)
+    }
+
+    #[test]
+    fn ris_journal_record() {
+        let r = parse("J. D. Bekenstein and S. W. Hawking, Phys. Rev. D 7, 2333-2346 (1973)");
+        let ris = to_ris(&[r]);
+        assert!(ris.starts_with("TY  - JOUR\r\n"));
+        assert!(ris.contains("AU  - J. D. Bekenstein\r\n"));
+        assert!(ris.contains("AU  - S. W. Hawking\r\n"));
+        assert!(ris.contains("JO  - Phys. Rev. D\r\n"));
+        assert!(ris.contains("VL  - 7\r\n"));
+        assert!(ris.contains("SP  - 2333\r\nEP  - 2346\r\n"));
+        assert!(ris.contains("PY  - 1973\r\n"));
+        assert!(ris.ends_with("ER  - \r\n\r\n"));
+        assert!(!ris.replace("\r\n", "").contains('\n'), "only CRLF line endings");
+    }
+
+    #[test]
+    fn ris_arxiv_only_record_links_to_abs_page() {
+        let r = parse("A. Author, Some preprint, arXiv:1001.0785");
+        let ris = to_ris(&[r]);
+        assert!(ris.starts_with("TY  - GEN\r\n"));
+        assert!(ris.contains("UR  - https://arxiv.org/abs/1001.0785\r\n"));
+    }
+
+    /// Minimal RFC 4180 reader, only for checking what `to_csv_rows` wrote.
+    fn read_csv(text: &str) -> Vec<Vec<String>> {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut chars = text.chars().peekable();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => row.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn csv_round_trips_adversarial_fields() {
+        let mut r = parse("J. D. Bekenstein, Phys. Rev. D 7, 2333 (1973)");
+        r.authors = Some("Bekenstein, J. D., \"Jr.\"".to_string());
+        r.title = Some("Black holes,\nand \"entropy\"\r\nrevisited".to_string());
+        let text = csv_header() + &to_csv_rows("dir/a,b.pdf", &[r.clone()]);
+        let rows = read_csv(&text);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0], CSV_COLUMNS);
+        assert_eq!(rows[1].len(), CSV_COLUMNS.len());
+        assert_eq!(rows[1][0], "dir/a,b.pdf");
+        assert_eq!(rows[1][2], r.authors.unwrap());
+        assert_eq!(rows[1][3], r.title.unwrap());
+        assert_eq!(rows[1][4], "Phys. Rev. D");
+        assert_eq!(rows[1][11], "Text");
+    }
+
+    #[test]
+    fn csv_leaves_plain_fields_unquoted() {
+        assert_eq!(csv_escape("Phys. Rev. D"), "Phys. Rev. D");
+        assert_eq!(csv_escape(""), "");
+        assert_eq!(csv_escape("a\"b"), "\"a\"\"b\"");
+    }
+
+    #[test]
+    fn output_dir_gets_one_json_file_per_input() {
+        let files: Vec<PathBuf> = ["a/refs.txt", "b/refs.txt", "paper.pdf.gz", "https://arxiv.org/pdf/1001.0785"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let names = output_file_names(&files);
+        assert_eq!(names, ["a-refs.json", "b-refs.json", "paper.json", "1001.0785.json"]);
+        let same_dir_names = output_file_names(&[PathBuf::from("x/a/refs.txt"), PathBuf::from("y/a/refs.txt")]);
+        assert_eq!(same_dir_names, ["a-refs.json", "a-refs-2.json"]);
+
+        let dir = std::env::temp_dir().join(format!("refextract-test-output-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let inputs = [
+            vec!["[1] A. Author, Phys. Rev. D 72, 052002 (2005)".to_string()],
+            vec!["[1] B. Other, Nucl. Phys. B 417, 181 (1994)".to_string()],
+        ];
+        for (i, lines) in inputs.iter().enumerate() {
+            let refs = crate::parse_reference_text(lines);
+            let result = serde_json::json!({ "file": files[i].display().to_string(), "references": refs });
+            write_json_file(&dir.join(&names[i]), &result, i == 0).unwrap();
+        }
+        for (i, name) in names[..2].iter().enumerate() {
+            let text = std::fs::read_to_string(dir.join(name)).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(value["file"], files[i].display().to_string());
+            assert_eq!(value["references"].as_array().unwrap().len(), 1);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
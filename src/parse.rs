@@ -1,75 +1,236 @@
-use crate::types::{ParsedReference, RawReference, Token, TokenKind};
+use crate::kb::CollaborationKb;
+use crate::{doi, kb, markers};
+use crate::types::{ParsedReference, RawReference, RefType, Token, TokenKind};
+
+/// Parser switches set from the command line.
+#[derive(Debug, Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Keep a journal name matched without a volume instead of treating it
+    /// as a false positive (`--no-journal-clearing`).
+    pub keep_journal_without_volume: bool,
+    /// Collaboration names, the baked-in KB unless `--collaborations-kb`
+    /// added some.
+    pub collaborations: &'a CollaborationKb,
+}
+
+impl Default for ParseOptions<'_> {
+    fn default() -> Self {
+        ParseOptions { keep_journal_without_volume: false, collaborations: &kb::COLLABORATIONS }
+    }
+}
 
 /// Parse a raw reference into one or more structured ParsedReferences.
 /// When a single reference string contains multiple journal citations
 /// (e.g., "Phys. Rev. D72, 052002. ... Phys. Rev. D72, 052008."),
 /// produce a sub-reference for each additional journal citation.
+///
+/// ```
+/// use refextract::types::{RawReference, ReferenceSource};
+///
+/// let text = "A. Aaltonen et al., Phys. Rev. D 72, 052002 (2005)";
+/// let raw = RawReference {
+///     text: text.to_string(),
+///     linemarker: Some("1".to_string()),
+///     from_marker: None,
+///     source: ReferenceSource::ReferenceSection,
+///     page_num: 12,
+///     section: 0,
+///     italic_runs: Vec::new(),
+/// };
+/// let tokens = refextract::tokenizer::tokenize(text);
+/// let parsed = refextract::parse::parse_references(&raw, &tokens);
+/// assert_eq!(parsed[0].linemarker.as_deref(), Some("1"));
+/// assert_eq!(parsed[0].journal_title.as_deref(), Some("Phys. Rev. D"));
+/// assert_eq!(parsed[0].found_on_page, 12);
+/// ```
 pub fn parse_references(raw: &RawReference, tokens: &[Token]) -> Vec<ParsedReference> {
+    parse_references_with(raw, tokens, &ParseOptions::default())
+}
+
+/// `parse_references` with non-default options.
+pub fn parse_references_with(raw: &RawReference, tokens: &[Token], opts: &ParseOptions) -> Vec<ParsedReference> {
     let mut result = ParsedReference {
         raw_ref: raw.text.clone(),
         linemarker: raw.linemarker.clone(),
+        from_marker: raw.from_marker.clone(),
         authors: None,
+        authors_list: None,
+        et_al: false,
+        editors: None,
         title: None,
         journal_title: None,
         journal_volume: None,
+        journal_issue: None,
         journal_year: None,
         journal_page: None,
+        numeration_raw: None,
+        status: None,
+        note: None,
         doi: None,
+        short_doi: None,
+        doi_prefix: None,
+        handle: None,
         arxiv_id: None,
+        texkey: None,
         isbn: None,
         report_number: None,
+        report_numbers: Vec::new(),
         url: None,
         collaboration: None,
+        ref_type: RefType::Unknown,
+        publisher: None,
+        confidence: 0.0,
         source: raw.source,
+        found_on_page: raw.page_num,
+        cited_on_pages: Vec::new(),
+        section: raw.section,
+        warnings: Vec::new(),
     };
 
     extract_identifiers(tokens, &mut result);
     extract_journal_info(tokens, &mut result);
     // A journal name without a volume is almost always a false positive
-    // (word like "Science" or "Computing" in a title). Clear it.
+    // (word like "Science" or "Computing" in a title). Clear it, unless
+    // the reference says it is forthcoming there ("submitted to JHEP").
     if result.journal_title.is_some() && result.journal_volume.is_none() {
-        result.journal_title = None;
+        if follows_status_phrase(tokens) {
+            result.status = Some("in press".to_string());
+        } else if !opts.keep_journal_without_volume {
+            result.journal_title = None;
+        }
     }
     // Standalone ibid ref (from semicolon splitting): extract numeration
     // after the Ibid token. Journal will be resolved later by caller.
     if result.journal_title.is_none() {
         extract_standalone_ibid(tokens, &mut result);
     }
-    extract_authors(tokens, &mut result);
+    extract_authors(tokens, &raw.italic_runs, opts.collaborations, &mut result);
+    extract_editors(tokens, &mut result);
+    classify_ref_type(tokens, &mut result);
 
     let mut refs = vec![result.clone()];
     refs.extend(extract_sub_references(raw, tokens, &result));
+    for r in &mut refs {
+        r.confidence = confidence(r);
+        r.warnings = validation_warnings(r);
+    }
     refs
 }
 
+/// Flag journal references whose numeration looks mis-read, so they can be
+/// triaged without being dropped:
+/// - a 6-digit volume ("052002") is almost always an article number
+/// - any other volume above 9999 is implausible for a journal
+pub fn validation_warnings(r: &ParsedReference) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(volume) = r.journal_volume.as_deref().filter(|_| r.journal_title.is_some()) else {
+        return warnings;
+    };
+    let digits = volume.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return warnings;
+    }
+    if digits.len() == 6 {
+        warnings.push("volume looks like article number".to_string());
+    } else if digits.parse::<u64>().is_ok_and(|n| n > 9999) {
+        warnings.push("volume is implausibly large".to_string());
+    }
+    warnings
+}
+
+/// Score how likely a parsed reference is a real citation, from 0.0 to 1.0.
+/// Evidence adds up, penalties subtract, and the sum is clamped:
+/// - journal title with volume: +0.5
+/// - DOI, arXiv ID or handle: +0.5
+/// - report number or ISBN: +0.3
+/// - year: +0.2
+/// - quoted title: +0.1
+/// - no authors: -0.15
+/// - raw text under 25 characters: -0.3 (stray list items, page furniture)
+pub fn confidence(r: &ParsedReference) -> f32 {
+    let mut score = 0.0f32;
+    if r.journal_title.is_some() && r.journal_volume.is_some() {
+        score += 0.5;
+    }
+    if r.doi.is_some() || r.arxiv_id.is_some() || r.handle.is_some() {
+        score += 0.5;
+    }
+    if r.report_number.is_some() || r.isbn.is_some() {
+        score += 0.3;
+    }
+    if r.journal_year.is_some() {
+        score += 0.2;
+    }
+    if r.title.is_some() {
+        score += 0.1;
+    }
+    if r.authors.is_none() {
+        score -= 0.15;
+    }
+    if r.raw_ref.chars().count() < 25 {
+        score -= 0.3;
+    }
+    // Two decimals keep the JSON output readable.
+    (score.clamp(0.0, 1.0) * 100.0).round() / 100.0
+}
+
+/// Store a DOI token's text: a shortDOI goes to `short_doi`, anything
+/// else to `doi` with its registrant.
+fn set_doi(result: &mut ParsedReference, text: Option<String>) {
+    match text {
+        Some(short) if doi::is_short_doi(&short) => result.short_doi = Some(short),
+        text => {
+            result.doi_prefix = text.as_deref().and_then(doi::doi_registrant).map(str::to_string);
+            result.doi = text;
+        }
+    }
+}
+
 fn extract_identifiers(tokens: &[Token], result: &mut ParsedReference) {
-    for token in tokens {
+    let primary_end = primary_segment_end(tokens);
+    for (i, token) in tokens.iter().enumerate() {
         match &token.kind {
-            TokenKind::Doi if result.doi.is_none() => {
-                result.doi = Some(token.text.clone());
+            TokenKind::Doi if result.doi.is_none() && result.short_doi.is_none() && i < primary_end => {
+                set_doi(result, Some(token.text.clone()));
             }
-            TokenKind::ArxivId if result.arxiv_id.is_none() => {
+            TokenKind::Hdl if result.handle.is_none() => {
+                result.handle = Some(token.text.clone());
+            }
+            TokenKind::ArxivId if result.arxiv_id.is_none() && i < primary_end => {
                 result.arxiv_id = Some(token.text.clone());
             }
+            TokenKind::Texkey if result.texkey.is_none() => {
+                result.texkey = Some(token.text.clone());
+            }
             TokenKind::Isbn if result.isbn.is_none() => {
                 result.isbn = Some(token.text.clone());
             }
-            TokenKind::ReportNumber if result.report_number.is_none() => {
-                result.report_number =
-                    Some(token.normalized.clone().unwrap_or(token.text.clone()));
+            TokenKind::ReportNumber => {
+                let number = token.normalized.clone().unwrap_or(token.text.clone());
+                result.report_number.get_or_insert_with(|| number.clone());
+                result.report_numbers.push(number);
             }
             TokenKind::Url if result.url.is_none() => {
                 result.url = Some(token.text.clone());
             }
-            TokenKind::Collaboration if result.collaboration.is_none() => {
-                result.collaboration =
-                    Some(token.normalized.clone().unwrap_or(token.text.clone()));
-            }
             _ => {}
         }
     }
 }
 
+/// Where the first sub-reference segment starts: the second journal name,
+/// or an ibid after the first ("[Erratum-ibid. 85, 029901 (2012)]"). A
+/// DOI or arXiv ID from there on belongs to that sub-reference.
+fn primary_segment_end(tokens: &[Token]) -> usize {
+    let Some(jpos) = tokens.iter().position(|t| t.kind == TokenKind::JournalName) else {
+        return tokens.len();
+    };
+    tokens[jpos + 1..]
+        .iter()
+        .position(|t| matches!(t.kind, TokenKind::JournalName | TokenKind::Ibid))
+        .map_or(tokens.len(), |p| jpos + 1 + p)
+}
+
 /// Walk tokens to find journal name + numeration (volume, year, page).
 fn extract_journal_info(tokens: &[Token], result: &mut ParsedReference) {
     let journal_pos = tokens
@@ -86,15 +247,73 @@ fn extract_journal_info(tokens: &[Token], result: &mut ParsedReference) {
         .clone()
         .or_else(|| Some(tokens[jpos].text.clone()));
 
-    // Scan tokens after journal name for volume, year, page
-    let window = &tokens[jpos + 1..];
-    assign_numeration(window, result);
+    // Scan tokens after journal name for volume, year, page, up to the
+    // next journal: in "Phys. Rev. D 72, and Phys. Rev. D 73, 011101 (2006)"
+    // the page and year belong to the second citation.
+    let next_journal = tokens[jpos + 1..]
+        .iter()
+        .position(|t| t.kind == TokenKind::JournalName)
+        .map_or(tokens.len(), |p| jpos + 1 + p);
+    assign_numeration(&tokens[jpos + 1..next_journal], result);
+    // Trailing journal: "72, 052002 (2005), Phys. Rev. D". Only when
+    // nothing followed the name, so a volume is never taken from both sides.
+    if result.journal_volume.is_none() {
+        assign_numeration(numeration_before(tokens, jpos), result);
+    }
 
     if result.journal_year.is_none() {
-        extract_standalone_year(tokens, result);
+        extract_standalone_year(&tokens[..next_journal], result);
+    }
+}
+
+/// The run of numeration tokens directly before the journal name at `jpos`.
+/// Empty unless it holds a volume or page; a year alone is not enough.
+fn numeration_before(tokens: &[Token], jpos: usize) -> &[Token] {
+    let is_numeration = |t: &Token| {
+        matches!(
+            t.kind,
+            TokenKind::Number | TokenKind::PageRange | TokenKind::Year | TokenKind::Issue
+        )
+    };
+    let start = tokens[..jpos]
+        .iter()
+        .rposition(|t| !is_numeration(t))
+        .map_or(0, |i| i + 1);
+    let run = &tokens[start..jpos];
+    if run.iter().any(|t| matches!(t.kind, TokenKind::Number | TokenKind::PageRange)) {
+        run
+    } else {
+        &[]
     }
 }
 
+/// Phrases that announce a not-yet-published journal article.
+const STATUS_PHRASES: &[&[&str]] = &[
+    &["submitted", "to"],
+    &["to", "appear", "in"],
+    &["accepted", "by"],
+];
+
+/// True when the first journal name is immediately preceded by a
+/// forthcoming-publication phrase such as "to appear in".
+fn follows_status_phrase(tokens: &[Token]) -> bool {
+    status_phrase_start(tokens).is_some()
+}
+
+/// Index of the token starting the forthcoming-publication phrase right
+/// before the first journal name: the "submitted" of "submitted to JHEP".
+fn status_phrase_start(tokens: &[Token]) -> Option<usize> {
+    let jpos = tokens.iter().position(|t| t.kind == TokenKind::JournalName)?;
+    let before: Vec<String> = tokens[..jpos]
+        .iter()
+        .map(|t| t.text.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .collect();
+    STATUS_PHRASES.iter().find_map(|phrase| {
+        let start = before.len().checked_sub(phrase.len())?;
+        before[start..].iter().zip(phrase.iter()).all(|(w, p)| w == p).then_some(start)
+    })
+}
+
 /// Handle standalone ibid refs (e.g., "ibid. 94 (1954) 7") from semicolon
 /// splitting. Extract numeration after the Ibid token and mark journal as
 /// "ibid" placeholder for later resolution.
@@ -105,10 +324,37 @@ fn extract_standalone_ibid(tokens: &[Token], result: &mut ParsedReference) {
     assign_numeration(window, result);
     if result.journal_volume.is_some() {
         result.journal_title = Some("ibid".to_string());
+        result.note = correction_note(tokens, ipos);
     }
 }
 
-/// Try to extract volume from a Word token (letter-prefixed, old-style, conference).
+/// "erratum", "addendum" or "publisher's note" when the citation at token
+/// `pos` is a correction: introduced by "[Erratum:" or "Publisher's Note:"
+/// right before it, or an "Erratum-ibid" token itself.
+fn correction_note(tokens: &[Token], pos: usize) -> Option<String> {
+    let preceding = tokens[..pos].iter().rev().take_while(|t| t.kind == TokenKind::Word).take(2);
+    let mut words: Vec<&str> = preceding.map(|t| t.text.as_str()).collect();
+    words.reverse();
+    if tokens[pos].kind == TokenKind::Ibid {
+        words.push(&tokens[pos].text);
+    }
+    let lower = words.join(" ").to_lowercase();
+    let note = if lower.contains("erratum") || lower.contains("corrigendum") {
+        "erratum"
+    } else if lower.contains("addendum") {
+        "addendum"
+    } else if lower.contains("publisher") && lower.contains("note") {
+        "publisher's note"
+    } else {
+        return None;
+    };
+    Some(note.to_string())
+}
+
+/// Try to extract volume from a Word token (letter-prefixed, old-style,
+/// conference, or volume and page joined by a comma). A comma that reads
+/// as a thousands separator ("1,234") makes the token a page instead,
+/// leaving the volume unset.
 fn try_word_as_volume(token: &Token, result: &mut ParsedReference) -> bool {
     if let Some(vol) = extract_letter_prefixed_number(&token.text) {
         result.journal_volume = Some(vol);
@@ -126,18 +372,46 @@ fn try_word_as_volume(token: &Token, result: &mut ParsedReference) -> bool {
         }
         return true;
     }
+    if let Some(page) = extract_grouped_page(&token.text) {
+        result.journal_page.get_or_insert(page);
+        return false;
+    }
+    if let Some((vol, page)) = extract_comma_joined_numeration(&token.text) {
+        result.journal_volume = Some(vol);
+        if result.journal_page.is_none() {
+            result.journal_page = Some(page);
+        }
+        return true;
+    }
     false
 }
 
 fn assign_numeration(window: &[Token], result: &mut ParsedReference) {
     let mut volume_found = false;
+    // Byte span in raw_ref of the tokens that filled volume/year/page
+    let mut consumed: Option<(usize, usize)> = None;
+    // Token indices of a volume and page read by position alone, and of the year
+    let (mut bare_volume, mut bare_page, mut year_at) = (None, None, None);
     let tokens: Vec<&Token> = window.iter().take(8).collect();
     for (i, token) in tokens.iter().enumerate() {
+        let before = numeration_fields(result);
+        let keyword = i.checked_sub(1).map(|k| tokens[k]);
         match &token.kind {
+            // "vol. 72, pp. 1-10": an explicit keyword beats the positional rules
+            TokenKind::Number if keyword.is_some_and(is_volume_keyword) => {
+                let clean = token.text.trim_matches(|c: char| !c.is_ascii_digit());
+                result.journal_volume = Some(clean.to_string());
+                volume_found = true;
+            }
+            TokenKind::Number | TokenKind::PageRange if keyword.is_some_and(is_page_keyword) => {
+                let clean = token.text.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '–');
+                result.journal_page = Some(clean.to_string());
+            }
             TokenKind::Number if !volume_found && result.journal_volume.is_none() => {
                 let clean = token.text.trim_matches(|c: char| !c.is_ascii_digit());
                 result.journal_volume = Some(clean.to_string());
                 volume_found = true;
+                bare_volume = Some(i);
             }
             // Bare year followed by a number: year(issue) format (JCAP/JHEP).
             // Treat year as journal_year, next number becomes volume.
@@ -159,8 +433,14 @@ fn assign_numeration(window: &[Token], result: &mut ParsedReference) {
             TokenKind::Year if result.journal_year.is_none() => {
                 result.journal_year =
                     token.normalized.clone().or(Some(token.text.clone()));
+                year_at = Some(i);
             }
-            TokenKind::PageRange if !volume_found && result.journal_volume.is_none() => {
+            // A range before any volume is the volume ("JHEP 10-11"), but not
+            // once a parenthesized year has been read: in "Phys. Rept. (2005)
+            // 279-360" the range is the page.
+            TokenKind::PageRange if !volume_found && result.journal_volume.is_none()
+                && result.journal_year.is_none() =>
+            {
                 let clean = token.text.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '–');
                 result.journal_volume = Some(clean.to_string());
                 volume_found = true;
@@ -169,47 +449,152 @@ fn assign_numeration(window: &[Token], result: &mut ParsedReference) {
                 let clean = token.text.trim_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '–');
                 result.journal_page = Some(clean.to_string());
             }
+            TokenKind::Issue if volume_found && result.journal_issue.is_none() => {
+                result.journal_issue = Some(token.text.clone());
+            }
+            // "vol. 82, no. 3": the number after "no." is the issue, not the page
+            TokenKind::Number if volume_found && result.journal_issue.is_none()
+                && i > 0 && is_issue_keyword(tokens[i - 1]) =>
+            {
+                let clean = token.text.trim_matches(|c: char| !c.is_ascii_digit());
+                result.journal_issue = Some(clean.to_string());
+            }
             TokenKind::Number if volume_found && result.journal_page.is_none() => {
                 let clean = token.text.trim_matches(|c: char| !c.is_ascii_digit());
                 result.journal_page = Some(clean.to_string());
+                bare_page = Some(i);
             }
             TokenKind::Word if !volume_found && result.journal_volume.is_none() => {
                 volume_found = try_word_as_volume(token, result);
             }
             TokenKind::Word if volume_found && result.journal_page.is_none() => {
-                if let Some(page) = extract_letter_prefixed_number(&token.text) {
+                let page = extract_letter_prefixed_number(&token.text)
+                    .or_else(|| extract_grouped_page(&token.text));
+                if let Some(page) = page {
                     result.journal_page = Some(page);
                 }
             }
             TokenKind::JournalName | TokenKind::Doi | TokenKind::ArxivId => break,
             _ => {}
         }
+        if numeration_fields(result) != before {
+            let start = consumed.map_or(token.start, |(start, _)| start);
+            consumed = Some((start, token.end));
+        }
+    }
+    // The year must follow the first number, between the two or after both
+    if let (Some(v), Some(_), Some(y)) = (bare_volume, bare_page, year_at)
+        && v < y
+        && let (Some(volume), Some(page)) = (&result.journal_volume, &result.journal_page)
+        && page_precedes_volume(volume, page)
+    {
+        std::mem::swap(&mut result.journal_volume, &mut result.journal_page);
+    }
+    if let Some(page) = &result.journal_page {
+        result.journal_page = Some(normalize_page_range(page));
+    }
+    if let Some((start, end)) = consumed && result.numeration_raw.is_none() {
+        result.numeration_raw = result
+            .raw_ref
+            .get(start..end)
+            .map(|s| trim_unbalanced_close(s.trim_end_matches([',', '.', ';', ':'])).to_string());
     }
 }
 
+/// Whether two bare numbers read as volume then page are really page then
+/// volume: "052002 (2005) 72" or "052002, 72 (2005)". Keywords ("p. 835, vol. 140") are handled
+/// before this and always win. Only done when the positional reading is
+/// implausible: the "volume" has six digits, too many for any volume but
+/// typical of article numbers, and the "page" has at most three. Five
+/// digits never count, as SPIE volumes run to five digits ("Proc. SPIE
+/// 10699 (2018) 12"), nor do four: "JHEP 1106 (2011) 077" is volume 1106,
+/// and so are Lecture Notes volumes.
+fn page_precedes_volume(volume: &str, page: &str) -> bool {
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit()).then_some(s.len());
+    matches!((digits(volume), digits(page)), (Some(6), Some(1..=3)))
+}
+
+/// Drop closing brackets that close something opened before the numeration,
+/// like the `]` of "[Sov. Phys. JETP 5, 1174 (1957)]".
+fn trim_unbalanced_close(s: &str) -> &str {
+    let mut s = s;
+    while let Some(rest) = s.strip_suffix([')', ']']) {
+        let open = s.matches(['(', '[']).count();
+        let close = s.matches([')', ']']).count();
+        if close <= open {
+            break;
+        }
+        s = rest.trim_end_matches([',', '.', ';', ':']);
+    }
+    s
+}
+
+/// Canonical page range: one ASCII hyphen, no surrounding spaces.
+/// "1547–1553", "1547 — 1553" and "1547--1553" all become "1547-1553";
+/// abbreviated ranges like "1547-53" are kept as written.
+pub fn normalize_page_range(page: &str) -> String {
+    let unified: String = page
+        .chars()
+        .map(|c| if matches!(c, '–' | '—' | '‐' | '‑' | '−') { '-' } else { c })
+        .collect();
+    let parts: Vec<&str> = unified.split('-').map(str::trim).filter(|p| !p.is_empty()).collect();
+    if parts.len() < 2 {
+        return unified.trim().to_string();
+    }
+    parts.join("-")
+}
+
+fn numeration_fields(result: &ParsedReference) -> [Option<String>; 4] {
+    [
+        result.journal_volume.clone(),
+        result.journal_issue.clone(),
+        result.journal_year.clone(),
+        result.journal_page.clone(),
+    ]
+}
+
+/// "vol." / "Vol." / "volume" introducing a volume.
+fn is_volume_keyword(token: &Token) -> bool {
+    token.kind == TokenKind::Word
+        && ["vol.", "volume"].iter().any(|k| token.text.eq_ignore_ascii_case(k))
+}
+
+/// "p." / "pp." introducing a page or page range.
+fn is_page_keyword(token: &Token) -> bool {
+    token.kind == TokenKind::Word && ["p.", "pp."].iter().any(|k| token.text.eq_ignore_ascii_case(k))
+}
+
+/// "no." / "No." introducing an issue number.
+fn is_issue_keyword(token: &Token) -> bool {
+    token.kind == TokenKind::Word && token.text.eq_ignore_ascii_case("no.")
+}
+
 /// Extract conference identifier as volume: "LAT2005" → ("LAT2005", None)
-/// Also handles compound "LAT2006:022" → ("LAT2006", Some("022"))
-/// Requires 2+ uppercase letters followed by 4 digits (year).
+/// Also handles compound "LAT2006:022" → ("LAT2006", Some("022")) and the
+/// compact PoS form "(LATTICE2019)123" → ("LATTICE2019", Some("123")).
 fn extract_conference_volume(text: &str) -> Option<(String, Option<String>)> {
     let clean = text.trim_matches(|c: char| c == ',' || c == '.' || c == ';');
-    // Check for conference:page compound (e.g., "LAT2006:022")
-    if let Some((conf, page)) = clean.split_once(':') {
-        let letter_count = conf.bytes().take_while(|b| b.is_ascii_uppercase()).count();
-        if letter_count >= 2 && conf.len() == letter_count + 4
-            && conf[letter_count..].chars().all(|c| c.is_ascii_digit())
-            && !page.is_empty() && page.chars().all(|c| c.is_ascii_digit())
-        {
-            return Some((conf.to_string(), Some(page.to_string())));
-        }
-    }
-    let letter_count = clean.bytes().take_while(|b| b.is_ascii_uppercase()).count();
-    if letter_count >= 2 && clean.len() == letter_count + 4
-        && clean[letter_count..].chars().all(|c| c.is_ascii_digit())
-    {
-        Some((clean.to_string(), None))
-    } else {
-        None
+    let (conf, page) = match clean.strip_prefix('(') {
+        Some(rest) => rest.split_once(')')?,
+        // Check for conference:page compound (e.g., "LAT2006:022")
+        None => clean.split_once(':').unwrap_or((clean, "")),
+    };
+    if !is_conference_id(conf) || !page.chars().all(|c| c.is_ascii_digit()) {
+        return None;
     }
+    Some((conf.to_string(), (!page.is_empty()).then(|| page.to_string())))
+}
+
+/// 2+ uppercase letters followed by 4 digits (year): "LAT2005", and with
+/// hyphens between the letters, "EPS-HEP2019".
+fn is_conference_id(text: &str) -> bool {
+    let letters = text.bytes().take_while(|b| b.is_ascii_uppercase() || *b == b'-').count();
+    let name = &text[..letters];
+    name.bytes().filter(u8::is_ascii_uppercase).count() >= 2
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && text.len() == letters + 4
+        && text[letters..].bytes().all(|b| b.is_ascii_digit())
 }
 
 /// Old-style volume with trailing section letter: "249B" → ("249", 'B')
@@ -255,6 +640,27 @@ fn extract_letter_prefixed_number(text: &str) -> Option<String> {
     }
 }
 
+/// Volume and page run together with a comma: "88,1025" → ("88", "1025").
+/// Callers try `extract_grouped_page` first: a comma followed by exactly
+/// three digits is a thousands separator.
+fn extract_comma_joined_numeration(text: &str) -> Option<(String, String)> {
+    let clean = text.trim_matches(|c: char| c == ',' || c == '.' || c == ';' || c == ':');
+    let (vol, page) = clean.split_once(',')?;
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    (digits(vol) && digits(page)).then(|| (vol.to_string(), page.to_string()))
+}
+
+/// Page or article number printed with a thousands separator, after the
+/// volume: "1,025" → "1025". Only a single group ("d,ddd" to "ddd,ddd")
+/// is accepted, so the value is always 4-6 digits.
+fn extract_grouped_page(text: &str) -> Option<String> {
+    let clean = text.trim_matches(|c: char| c == ',' || c == '.' || c == ';' || c == ':');
+    let (head, group) = clean.split_once(',')?;
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let valid = (1..=3).contains(&head.len()) && group.len() == 3 && digits(head) && digits(group);
+    valid.then(|| format!("{head}{group}"))
+}
+
 fn extract_standalone_year(tokens: &[Token], result: &mut ParsedReference) {
     if result.journal_year.is_some() {
         return;
@@ -266,11 +672,86 @@ fn extract_standalone_year(tokens: &[Token], result: &mut ParsedReference) {
 
 /// Extract authors and title from the raw reference text.
 /// Authors are text before the first quoted title or journal/identifier.
-/// Title is text within quotes.
-fn extract_authors(tokens: &[Token], result: &mut ParsedReference) {
+/// Title is text within quotes, or else an italic run (PDF input).
+fn extract_authors(
+    tokens: &[Token],
+    italic_runs: &[String],
+    collaborations: &CollaborationKb,
+    result: &mut ParsedReference,
+) {
     // Use raw_ref to extract quoted title and author text before it
     extract_title_from_raw(&result.raw_ref.clone(), result);
+    let mut tokens = tokens;
+    if result.title.is_none()
+        && let Some((start, title)) = italic_title(tokens, italic_runs, &result.raw_ref)
+    {
+        result.title = Some(title);
+        // Unlike a quote, an italic title leaves no mark in the tokens
+        let end = tokens.iter().position(|t| t.start >= start).unwrap_or(tokens.len());
+        tokens = &tokens[..end];
+    }
+    // "A. Author, submitted to Phys. Rev. Lett.": the phrase is no author
+    if let Some(start) = status_phrase_start(tokens) {
+        tokens = &tokens[..start];
+    }
+
+    let mut author_words = if result.linemarker.is_none() && markers::starts_with_author(&result.raw_ref) {
+        author_date_words(tokens)
+    } else {
+        numbered_author_words(tokens)
+    };
+    if result.collaboration.is_none() {
+        result.collaboration = take_collaboration(&mut author_words, collaborations);
+    }
+    let author_text = author_words.join(" ");
+    let mut author_text = author_text.trim().trim_end_matches(',').trim();
+    if let Some((names, _)) = split_et_al(author_text) {
+        result.et_al = true;
+        author_text = names;
+    }
+    if !author_text.is_empty() && author_text.len() > 2 {
+        result.authors = Some(author_text.to_string());
+        let names = split_authors(author_text);
+        if !names.is_empty() {
+            result.authors_list = Some(names);
+        }
+    }
+}
+
+/// Take a collaboration name out of the author words, wherever it stands:
+/// "ATLAS Collaboration, G. Aad et al.", "G. Aad et al. [ATLAS
+/// Collaboration]", "Aad, G. et al. (ATLAS Collaboration)". The longest
+/// run of words naming one wins; a "the" before it and brackets standing
+/// alone around it go too.
+fn take_collaboration(words: &mut Vec<&str>, collaborations: &CollaborationKb) -> Option<String> {
+    let mut best: Option<(usize, usize, &str)> = None;
+    for start in 0..words.len() {
+        for end in start + 1..=words.len().min(start + kb::MAX_COLLABORATION_WORDS) {
+            if best.is_some_and(|(s, e, _)| end - start <= e - s) {
+                continue;
+            }
+            if let Some(name) = collaborations.get(&words[start..end].join(" ")) {
+                best = Some((start, end, name));
+            }
+        }
+    }
+    let (mut start, mut end, name) = best?;
+    let name = name.to_string();
+    let bracket = |w: &str| w.chars().all(|c| "()[],;".contains(c));
+    if start > 0 && bracket(words[start - 1]) {
+        start -= 1;
+    }
+    if start > 0 && words[start - 1].eq_ignore_ascii_case("the") {
+        start -= 1;
+    }
+    if end < words.len() && bracket(words[end]) {
+        end += 1;
+    }
+    words.drain(start..end);
+    Some(name)
+}
 
+fn numbered_author_words(tokens: &[Token]) -> Vec<&str> {
     let mut author_words = Vec::new();
     for token in tokens {
         if is_author_terminator(token) {
@@ -288,11 +769,31 @@ fn extract_authors(tokens: &[Token], result: &mut ParsedReference) {
         }
         author_words.push(token.text.as_str());
     }
-    let author_text = author_words.join(" ");
-    let author_text = author_text.trim().trim_end_matches(',').trim();
-    if !author_text.is_empty() && author_text.len() > 2 {
-        result.authors = Some(author_text.to_string());
-    }
+    author_words
+}
+
+/// Author-date entries ("Aaij, R., et al. (2015) Title. Phys. Rev. ...")
+/// have a well-defined author block: everything up to the year. Stray
+/// numbers or quotes inside the block don't end it, only the year, a
+/// journal name or an identifier does.
+fn author_date_words(tokens: &[Token]) -> Vec<&str> {
+    tokens
+        .iter()
+        .filter(|t| t.kind != TokenKind::LineMarker)
+        .take_while(|t| {
+            !matches!(
+                t.kind,
+                TokenKind::Year
+                    | TokenKind::JournalName
+                    | TokenKind::Doi
+                    | TokenKind::Hdl
+                    | TokenKind::ArxivId
+                    | TokenKind::Url
+                    | TokenKind::Editor
+            )
+        })
+        .map(|t| t.text.as_str())
+        .collect()
 }
 
 fn is_author_terminator(token: &Token) -> bool {
@@ -300,15 +801,58 @@ fn is_author_terminator(token: &Token) -> bool {
         token.kind,
         TokenKind::JournalName
             | TokenKind::Doi
+            | TokenKind::Hdl
             | TokenKind::ArxivId
             | TokenKind::ReportNumber
             | TokenKind::Year
             | TokenKind::Number
             | TokenKind::PageRange
             | TokenKind::Ibid
+            | TokenKind::Editor
     )
 }
 
+/// Collect editor names after an `Editor` marker ("eds. A. Jones and B. Lee,
+/// Springer (2001)"). Stops at the next identifier or numeration token, a
+/// publisher name, a lowercase word such as "in", or a page marker.
+fn extract_editors(tokens: &[Token], result: &mut ParsedReference) {
+    let Some(epos) = tokens.iter().position(|t| t.kind == TokenKind::Editor) else {
+        return;
+    };
+    let mut rest = &tokens[epos + 1..];
+    if rest.first().is_some_and(|t| t.text == "by") {
+        rest = &rest[1..];
+    }
+    let mut names = Vec::new();
+    for (i, token) in rest.iter().enumerate() {
+        if is_author_terminator(token) || ends_editor_list(token) {
+            break;
+        }
+        let following: Vec<&str> = rest[i..].iter().take(4).map(|t| t.text.as_str()).collect();
+        if kb::starts_with_publisher(&following.join(" ")) {
+            break;
+        }
+        names.push(token.text.as_str());
+    }
+    let editors = names.join(" ");
+    let editors = editors.trim().trim_end_matches([',', ';']).trim();
+    if !editors.is_empty() {
+        result.editors = Some(editors.to_string());
+    }
+}
+
+fn ends_editor_list(token: &Token) -> bool {
+    if token.kind != TokenKind::Word {
+        return false;
+    }
+    let text = token.text.trim_start_matches(['(', '[']);
+    let is_particle = matches!(text, "van" | "von" | "de" | "der" | "den" | "di" | "da" | "le");
+    let lowercase = text.chars().next().is_some_and(char::is_lowercase);
+    (lowercase && !is_particle)
+        || text.starts_with(['"', '\u{201c}', '\u{201d}'])
+        || matches!(text, "p." | "pp." | "In" | "In:" | "Proc." | "Proceedings")
+}
+
 fn extract_title_from_raw(raw: &str, result: &mut ParsedReference) {
     // Try various quote patterns (PDFs use inconsistent quoting)
     let title = extract_between_quotes(raw, '\u{201c}', '\u{201d}')
@@ -322,6 +866,83 @@ fn extract_title_from_raw(raw: &str, result: &mut ParsedReference) {
     }
 }
 
+/// The first italic run that follows some author text and starts before the
+/// journal name or first identifier, cut off at the journal in case that
+/// is set in italics too. Returns the title and its byte offset.
+fn italic_title(tokens: &[Token], italic_runs: &[String], raw: &str) -> Option<(usize, String)> {
+    let end = tokens
+        .iter()
+        .find(|t| {
+            matches!(
+                t.kind,
+                TokenKind::JournalName
+                    | TokenKind::Doi
+                    | TokenKind::Hdl
+                    | TokenKind::ArxivId
+                    | TokenKind::Url
+                    | TokenKind::ReportNumber
+            )
+        })
+        .map_or(raw.len(), |t| t.start);
+    let (start, run) = italic_runs
+        .iter()
+        .filter_map(|run| raw.find(run.as_str()).map(|start| (start, run)))
+        .filter(|&(start, _)| start > 0 && start < end)
+        .min_by_key(|&(start, _)| start)?;
+    let title = raw[start..end.min(start + run.len())]
+        .trim()
+        .trim_end_matches([',', '.', ';', ':'])
+        .trim();
+    (!title.is_empty()).then(|| (start, title.to_string()))
+}
+
+/// Decide what kind of work the reference cites, from the fields already
+/// extracted plus keyword cues in the raw text. A journal citation wins
+/// over book cues because publishers also appear in series names
+/// ("Springer Proc. Phys. 145"), and a full journal citation (title,
+/// volume and page) over a thesis cue.
+fn classify_ref_type(tokens: &[Token], result: &mut ParsedReference) {
+    let raw = result.raw_ref.as_str();
+    let publisher = kb::match_publisher(raw);
+    let full_journal =
+        result.journal_title.is_some() && result.journal_volume.is_some() && result.journal_page.is_some();
+    result.ref_type = if !full_journal && has_thesis_marker(raw) {
+        RefType::Thesis
+    } else if result.journal_title.is_some() && result.journal_volume.is_some() {
+        RefType::Journal
+    } else if has_in_prefix(raw) || tokens.iter().any(|t| t.kind == TokenKind::Editor) {
+        RefType::Proceedings
+    } else if publisher.is_some() {
+        RefType::Book
+    } else if result.arxiv_id.is_some() || result.report_number.is_some() {
+        RefType::Preprint
+    } else {
+        RefType::Unknown
+    };
+    if matches!(result.ref_type, RefType::Book | RefType::Proceedings) {
+        result.publisher = publisher;
+    }
+}
+
+/// "Ph.D. thesis", "PhD dissertation", on whole words so "hypothesis" and
+/// "nucleosynthesis" don't count.
+fn has_thesis_marker(raw: &str) -> bool {
+    let lower = raw.to_lowercase();
+    lower.contains("ph.d.")
+        || lower.contains("ph. d.")
+        || lower
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|w| matches!(w, "thesis" | "theses" | "dissertation" | "phd"))
+}
+
+/// "In: Proceedings of ...", "in Proc. of ...".
+fn has_in_prefix(raw: &str) -> bool {
+    raw.contains("In:")
+        || raw.contains(" in: ")
+        || raw.contains(", In ")
+        || raw.contains(" in Proc")
+}
+
 fn extract_between_quotes(text: &str, open: char, close: char) -> Option<String> {
     let start = text.find(open)? + open.len_utf8();
     let end = text[start..].find(close)? + start;
@@ -352,8 +973,7 @@ fn extract_sub_references(
     );
 
     // Mark the primary's arXiv position as used
-    let primary_seg_end = journal_positions.get(1).copied().unwrap_or(tokens.len());
-    if let Some(pos) = arxiv_position_in_range(tokens, 0, primary_seg_end) {
+    if let Some(pos) = arxiv_position_in_range(tokens, 0, primary_segment_end(tokens)) {
         used_arxiv_positions.push(pos);
     }
 
@@ -362,6 +982,9 @@ fn extract_sub_references(
     sub_refs.extend(extract_arxiv_only_sub_refs(
         raw, tokens, primary, &used_arxiv_positions,
     ));
+    for sub in &mut sub_refs {
+        sub.from_marker = raw.linemarker.clone();
+    }
     sub_refs
 }
 
@@ -389,11 +1012,11 @@ fn extract_journal_sub_refs(
         }
 
         let mut sub = make_sub_ref(raw, primary, &tokens[jpos]);
+        sub.note = correction_note(tokens, jpos);
         sub.arxiv_id = find_token_in_range(tokens, jpos, next_journal, TokenKind::ArxivId);
-        sub.doi = find_token_in_range(tokens, jpos, next_journal, TokenKind::Doi);
+        set_doi(&mut sub, find_token_in_range(tokens, jpos, next_journal, TokenKind::Doi));
 
-        let window_end = next_journal.min(jpos + 9);
-        assign_numeration(&tokens[jpos + 1..window_end], &mut sub);
+        assign_numeration(&tokens[jpos + 1..next_journal], &mut sub);
 
         if sub.journal_volume.is_some() {
             sub_refs.push(sub);
@@ -427,20 +1050,48 @@ fn extract_ibid_sub_refs(
         let mut sub = ParsedReference {
             raw_ref: raw.text.clone(),
             linemarker: raw.linemarker.clone(),
+            from_marker: None,
             authors: primary.authors.clone(),
+            authors_list: primary.authors_list.clone(),
+            et_al: primary.et_al,
+            editors: None,
             title: None,
             journal_title: Some(journal.clone()),
             journal_volume: None,
+            journal_issue: None,
             journal_year: None,
             journal_page: None,
+            numeration_raw: None,
+            status: None,
+            note: None,
             doi: None,
+            short_doi: None,
+            doi_prefix: None,
+            handle: None,
             arxiv_id: None,
+            texkey: None,
             isbn: None,
             report_number: None,
+            report_numbers: Vec::new(),
             url: None,
             collaboration: primary.collaboration.clone(),
+            ref_type: RefType::Journal,
+            publisher: None,
+            confidence: 0.0,
             source: raw.source,
+            found_on_page: raw.page_num,
+            cited_on_pages: Vec::new(),
+            section: raw.section,
+            warnings: Vec::new(),
         };
+        // The ibid segment runs to the next journal or ibid; a DOI inside
+        // it belongs to the erratum, not the primary.
+        let segment_end = tokens[i + 1..]
+            .iter()
+            .position(|t| matches!(t.kind, TokenKind::JournalName | TokenKind::Ibid))
+            .map_or(tokens.len(), |p| i + 1 + p);
+        set_doi(&mut sub, find_token_in_range(tokens, i, segment_end, TokenKind::Doi));
+        sub.note = correction_note(tokens, i);
         let window_end = (i + 9).min(tokens.len());
         assign_numeration(&tokens[i + 1..window_end], &mut sub);
         if sub.journal_volume.is_some() {
@@ -466,6 +1117,9 @@ fn extract_arxiv_only_sub_refs(
             sub.journal_title = None;
             sub.arxiv_id = Some(t.text.clone());
             sub.authors = None;
+            sub.authors_list = None;
+            sub.et_al = false;
+            sub.ref_type = RefType::Preprint;
             sub
         })
         .collect()
@@ -479,22 +1133,42 @@ fn make_sub_ref(
     ParsedReference {
         raw_ref: raw.text.clone(),
         linemarker: raw.linemarker.clone(),
+        from_marker: None,
         authors: primary.authors.clone(),
+        authors_list: primary.authors_list.clone(),
+        et_al: primary.et_al,
+        editors: None,
         title: None,
         journal_title: journal_token
             .normalized
             .clone()
             .or_else(|| Some(journal_token.text.clone())),
         journal_volume: None,
+        journal_issue: None,
         journal_year: None,
         journal_page: None,
+        numeration_raw: None,
+        status: None,
+        note: None,
         doi: None,
+        short_doi: None,
+        doi_prefix: None,
+        handle: None,
         arxiv_id: None,
+        texkey: None,
         isbn: None,
         report_number: None,
+        report_numbers: Vec::new(),
         url: None,
         collaboration: primary.collaboration.clone(),
+        ref_type: RefType::Journal,
+        publisher: None,
+        confidence: 0.0,
         source: raw.source,
+        found_on_page: raw.page_num,
+        cited_on_pages: Vec::new(),
+        section: raw.section,
+        warnings: Vec::new(),
     }
 }
 
@@ -521,3 +1195,588 @@ fn arxiv_position_in_range(
         .find(|(_, t)| t.kind == TokenKind::ArxivId)
         .map(|(i, _)| start + i)
 }
+
+/// Split an author string into individual names.
+/// Handles "A. Author, B. Other and C. Third" as well as the inverted
+/// "Author, A., Other, B." form, where a piece made only of initials is
+/// joined back onto the preceding surname. "et al." is dropped.
+pub fn split_authors(authors: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let pieces = authors
+        .split(',')
+        .flat_map(|p| p.split(" and "))
+        .flat_map(|p| p.split(" & "))
+        .map(str::trim)
+        .filter(|p| !p.is_empty());
+    for piece in pieces {
+        let piece = piece.trim_start_matches("and ").trim();
+        if piece.is_empty() || is_et_al(piece) {
+            continue;
+        }
+        if is_initials(piece) && let Some(last) = names.last_mut() && !last.contains(',') {
+            last.push_str(", ");
+            last.push_str(piece);
+            continue;
+        }
+        names.push(piece.to_string());
+    }
+    names
+}
+
+/// Surname of the first author: "Author" for "A. Author", "Author, A."
+/// and "Author et al.". None if the first name is only initials.
+pub fn first_author_surname(authors: &str) -> Option<String> {
+    let names = split_et_al(authors).map_or(authors, |(names, _)| names);
+    let first = split_authors(names).into_iter().next()?;
+    let surname = match first.split_once(',') {
+        Some((surname, _)) => surname.trim().to_string(),
+        None => first
+            .split_whitespace()
+            .skip_while(|w| is_initials(w) || is_joined_initials(w))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    (!surname.is_empty()).then_some(surname)
+}
+
+/// Split an author string at "et al." or "and collaborators", returning
+/// the names before it and whatever follows it.
+fn split_et_al(text: &str) -> Option<(&str, &str)> {
+    let lower = text.to_ascii_lowercase();
+    for phrase in ["et al.", "et. al.", "et al", "and collaborators"] {
+        let found = lower.match_indices(phrase).find(|&(pos, _)| {
+            let before_ok = lower[..pos].chars().next_back().is_none_or(|c| c == ' ' || c == ',');
+            let after_ok = !lower[pos + phrase.len()..].starts_with(|c: char| c.is_alphanumeric());
+            before_ok && after_ok
+        });
+        if let Some((pos, _)) = found {
+            let names = text[..pos].trim_end().trim_end_matches(',').trim_end();
+            return Some((names, text[pos + phrase.len()..].trim_start()));
+        }
+    }
+    None
+}
+
+fn is_et_al(text: &str) -> bool {
+    matches!(text.to_ascii_lowercase().as_str(), "et al." | "et al" | "et. al.")
+}
+
+/// "R.", "J. D.", "J.-P." — only initials, no surname.
+fn is_initials(text: &str) -> bool {
+    text.split([' ', '-'])
+        .filter(|p| !p.is_empty())
+        .all(|p| {
+            let letters = p.trim_end_matches('.');
+            letters.chars().count() == 1 && letters.chars().all(char::is_uppercase)
+        })
+}
+
+/// "A.B." or "J.-P." written without spaces.
+fn is_joined_initials(word: &str) -> bool {
+    word.ends_with('.')
+        && word
+            .split(['.', '-'])
+            .filter(|p| !p.is_empty())
+            .all(|p| p.chars().count() == 1 && p.chars().all(char::is_uppercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ReferenceSource;
+
+    fn parse_all(text: &str, page_num: usize) -> Vec<ParsedReference> {
+        let raw = RawReference {
+            text: text.to_string(),
+            linemarker: None,
+            from_marker: None,
+            source: ReferenceSource::Text,
+            page_num,
+            section: 0,
+            italic_runs: Vec::new(),
+        };
+        parse_references(&raw, &crate::tokenizer::tokenize(text))
+    }
+
+    fn parse(text: &str) -> ParsedReference {
+        parse_all(text, 0).remove(0)
+    }
+
+    #[test]
+    fn book_with_publisher() {
+        let r = parse("M. Peskin, \"An Introduction to QFT\", Addison-Wesley (1995)");
+        assert_eq!(r.ref_type, RefType::Book);
+        assert_eq!(r.publisher.as_deref(), Some("Addison-Wesley"));
+        assert_eq!(r.title.as_deref(), Some("An Introduction to QFT"));
+        assert_eq!(r.journal_year.as_deref(), Some("1995"));
+    }
+
+    #[test]
+    fn chapter_in_edited_volume() {
+        let r = parse("A. Author, In: Proceedings of the Workshop, ed. B. Editor, World Scientific, p. 45");
+        assert_eq!(r.ref_type, RefType::Proceedings);
+        assert_eq!(r.publisher.as_deref(), Some("World Scientific"));
+        assert_eq!(r.editors.as_deref(), Some("B. Editor"));
+    }
+
+    #[test]
+    fn numeration_raw_covers_volume_page_year() {
+        let r = parse("[1] A. Aaltonen et al., Phys. Rev. D 72, 052002 (2005).");
+        assert_eq!(r.journal_volume.as_deref(), Some("72"));
+        assert_eq!(r.numeration_raw.as_deref(), Some("72, 052002 (2005)"));
+    }
+
+    #[test]
+    fn bracketed_corrections_are_noted_sub_references() {
+        let refs = crate::parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005) [Erratum: Phys. Rev. D 85, 029901 (2012)].".to_string(),
+            "[2] B. Other, Phys. Rev. D 72, 052002 (2005) [Addendum: ibid. 85, 029901 (2012)]".to_string(),
+            "[3] C. Third, Phys. Rev. D 72, 052002 (2005) [Publisher's Note: Phys. Rev. D 73, 1 (2006)]".to_string(),
+            "[4] D. Fourth, Phys. Rev. Lett. 100, 1 (2008); Erratum-ibid. 101, 2 (2008)".to_string(),
+            "[5] E. Fifth, Phys. Rev. D 72, 052002 (2005); Nucl. Phys. B 417, 181 (1994)".to_string(),
+        ]);
+        let notes: Vec<(&str, Option<&str>)> =
+            refs.iter().map(|r| (r.linemarker.as_deref().unwrap(), r.note.as_deref())).collect();
+        assert_eq!(
+            notes,
+            [
+                ("1", None),
+                ("1", Some("erratum")),
+                ("2", None),
+                ("2", Some("addendum")),
+                ("3", None),
+                ("3", Some("publisher's note")),
+                ("4", None),
+                ("4", Some("erratum")),
+                ("5", None),
+                ("5", None),
+            ]
+        );
+        let erratum = &refs[1];
+        assert_eq!(erratum.from_marker.as_deref(), Some("1"));
+        assert_eq!(erratum.authors.as_deref(), Some("A. Author"));
+        assert_eq!(erratum.journal_title.as_deref(), Some("Phys. Rev. D"));
+        assert_eq!(erratum.journal_volume.as_deref(), Some("85"));
+        assert_eq!(erratum.journal_page.as_deref(), Some("029901"));
+        assert_eq!(erratum.journal_year.as_deref(), Some("2012"));
+        assert_eq!(refs[3].journal_volume.as_deref(), Some("85"));
+        assert_eq!(refs[7].journal_title.as_deref(), Some("Phys. Rev. Lett."));
+    }
+
+    #[test]
+    fn russian_original_and_translation_are_two_references() {
+        let refs = crate::parse_reference_text(&[
+            "[1] A. A. Abrikosov, Zh. Eksp. Teor. Fiz. 32, 1442 (1957) [Sov. Phys. JETP 5, 1174 (1957)].".to_string(),
+            "[2] V. N. Gribov, Pis’ma Zh. Éksp. Teor. Fiz. 5, 24 (1967) (JETP Lett. 5, 24 (1967))".to_string(),
+            "[3] L. Okun, Zh. Eksp. Teor. Fiz. Pis'ma Red. 5, 24 (1967) [Sov. Phys.–JETP 5, 1174 (1957)]".to_string(),
+        ]);
+        let journals: Vec<(&str, &str, &str, &str)> = refs
+            .iter()
+            .map(|r| {
+                (
+                    r.journal_title.as_deref().unwrap_or(""),
+                    r.journal_volume.as_deref().unwrap_or(""),
+                    r.journal_page.as_deref().unwrap_or(""),
+                    r.from_marker.as_deref().unwrap_or(""),
+                )
+            })
+            .collect();
+        assert_eq!(
+            journals,
+            [
+                ("Zh. Eksp. Teor. Fiz.", "32", "1442", ""),
+                ("Sov. Phys. - JETP", "5", "1174", "1"),
+                ("Pis'ma Zh. Eksp. Teor. Fiz.", "5", "24", ""),
+                ("JETP Lett.", "5", "24", "2"),
+                ("Zh. Eksp. Teor. Fiz. Pis'ma v Red.", "5", "24", ""),
+                ("Sov. Phys. - JETP", "5", "1174", "3"),
+            ]
+        );
+        assert_eq!(refs[1].authors.as_deref(), Some("A. A. Abrikosov"));
+        assert_eq!(refs[1].numeration_raw.as_deref(), Some("5, 1174 (1957)"));
+        assert_eq!(refs[3].journal_year.as_deref(), Some("1967"));
+        assert_eq!(refs[3].numeration_raw.as_deref(), Some("5, 24 (1967)"));
+    }
+
+    #[test]
+    fn page_ranges_use_a_single_hyphen() {
+        for page in ["1547-1553", "1547–1553", "1547—1553", "1547– 1553", "1547 - 1553", "1547--1553", "1547−1553"] {
+            assert_eq!(normalize_page_range(page), "1547-1553", "{page}");
+        }
+        assert_eq!(normalize_page_range("1547-53"), "1547-53");
+        assert_eq!(normalize_page_range("052002"), "052002");
+    }
+
+    #[test]
+    fn parsed_page_range_is_normalized() {
+        let r = parse("A. Author, Rev. Mod. Phys. 72, 1547– 1553 (2000)");
+        assert_eq!(r.journal_page.as_deref(), Some("1547-1553"));
+        let r = parse("A. Author, Ann. Rev. Astron. Astrophys. 44, 1547—1553 (2006)");
+        assert_eq!(r.journal_page.as_deref(), Some("1547-1553"));
+    }
+
+    #[test]
+    fn erratum_gets_its_own_doi() {
+        let refs = parse_all(
+            "A. Author, Phys. Rev. D 84, 024617 (2011) 10.1103/a [Erratum-ibid. 85, 029901 (2012) 10.1103/b]",
+            0,
+        );
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].journal_volume.as_deref(), Some("84"));
+        assert_eq!(refs[0].doi.as_deref(), Some("10.1103/a"));
+        assert_eq!(refs[1].journal_volume.as_deref(), Some("85"));
+        assert_eq!(refs[1].doi.as_deref(), Some("10.1103/b"));
+
+        // Without a DOI of its own, the primary doesn't take the erratum's
+        let refs = parse_all(
+            "A. Author, Phys. Rev. D 84, 024617 (2011) [Erratum-ibid. 85, 029901 (2012) 10.1103/b]",
+            0,
+        );
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].doi, None);
+        assert_eq!(refs[1].doi.as_deref(), Some("10.1103/b"));
+    }
+
+    #[test]
+    fn sub_references_keep_the_page() {
+        let refs = parse_all(
+            "A. Author, Phys. Rev. D 72, 052002 (2005); ibid. 73, 012001 (2006), Phys. Lett. B 600, 1 (2004)",
+            9,
+        );
+        assert!(refs.len() > 1);
+        assert!(refs.iter().all(|r| r.found_on_page == 9));
+    }
+
+    #[test]
+    fn proceedings_of_science() {
+        for text in [
+            "A. Author, PoS LATTICE2019, 123 (2020)",
+            "A. Author, PoS(LATTICE2019)123 (2020)",
+            "A. Author, PoS (LATTICE2019) 123 (2020)",
+        ] {
+            let r = parse(text);
+            assert_eq!(r.journal_title.as_deref(), Some("PoS"), "{text}");
+            assert_eq!(r.journal_volume.as_deref(), Some("LATTICE2019"), "{text}");
+            assert_eq!(r.journal_page.as_deref(), Some("123"), "{text}");
+            assert_eq!(r.journal_year.as_deref(), Some("2020"), "{text}");
+        }
+        let r = parse("A. Author, PoS(EPS-HEP2019)512");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("EPS-HEP2019"), Some("512")));
+    }
+
+    #[test]
+    fn and_joined_co_citations_split() {
+        let refs = parse_all("see Phys. Rev. D 72, 052002 (2005) and Phys. Rev. D 73, 011101 (2006)", 0);
+        let numeration: Vec<_> = refs
+            .iter()
+            .map(|r| (r.journal_volume.as_deref(), r.journal_page.as_deref(), r.journal_year.as_deref()))
+            .collect();
+        assert_eq!(
+            numeration,
+            [(Some("72"), Some("052002"), Some("2005")), (Some("73"), Some("011101"), Some("2006"))]
+        );
+        // The first citation never borrows numeration from the second
+        let refs = parse_all("A. Author, Phys. Rev. D 72, and Phys. Rev. D 73, 011101 (2006)", 0);
+        assert_eq!(refs.len(), 2);
+        assert_eq!((refs[0].journal_page.as_deref(), refs[0].journal_year.as_deref()), (None, None));
+        assert_eq!(refs[1].journal_year.as_deref(), Some("2006"));
+    }
+
+    #[test]
+    fn comma_in_numeration() {
+        let r = parse("A. Author, Science 340, 1234 (2013)");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("340"), Some("1234")));
+        // Thousands separator in page position
+        let r = parse("A. Author, Phys. Rev. B 88, 1,025 (2013)");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("88"), Some("1025")));
+        // Missing space after the volume comma
+        let r = parse("A. Author, Phys. Rev. B 88,1025 (2013)");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("88"), Some("1025")));
+        // A thousands separator in volume position makes a page, year or not
+        for text in ["A. Author, Science 1,234 (1999)", "A. Author, Science 1,234"] {
+            let r = parse(text);
+            assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (None, Some("1234")), "{text}");
+        }
+        let r = parse("A. Author, Phys. Rev. B 88,1025");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("88"), Some("1025")));
+        assert_eq!(extract_grouped_page("1,234,567"), None);
+    }
+
+    #[test]
+    fn forthcoming_journal_keeps_title() {
+        let r = parse("A. Author, Title of paper, submitted to Phys. Rev. Lett.");
+        assert_eq!(r.journal_title.as_deref(), Some("Phys. Rev. Lett."));
+        assert_eq!(r.status.as_deref(), Some("in press"));
+        let r = parse("A. Author, submitted to Phys. Rev. Lett.");
+        assert_eq!(r.authors.as_deref(), Some("A. Author"));
+        assert_eq!(r.status.as_deref(), Some("in press"));
+        let r = parse("A. Author, Title of paper, to appear in JHEP");
+        assert_eq!(r.journal_title.as_deref(), Some("J. High Energy Phys."));
+        assert_eq!(r.status.as_deref(), Some("in press"));
+        let r = parse("A. Author, Title of paper, accepted by Nucl. Phys. B (2019)");
+        assert_eq!(r.journal_title.as_deref(), Some("Nucl. Phys. B"));
+        assert_eq!(r.status.as_deref(), Some("in press"));
+    }
+
+    #[test]
+    fn bare_journal_word_without_status_is_dropped() {
+        let r = parse("A. Author, Title of paper, Phys. Rev. Lett.");
+        assert_eq!(r.journal_title, None);
+        assert_eq!(r.status, None);
+    }
+
+    #[test]
+    fn confidence_scores() {
+        let journal = parse("J. D. Bekenstein, \u{201c}Black holes and entropy,\u{201d} Phys. Rev. D 7, 2333 (1973).");
+        assert_eq!(journal.confidence, 0.8);
+        let preprint = parse("A. Author, Some preprint, arXiv:1001.0785");
+        assert_eq!(preprint.confidence, 0.5);
+        let full = parse("A. Author, Phys. Rev. Lett. 115, 072001 (2015), arXiv:1507.03414");
+        assert_eq!(full.confidence, 1.0);
+        let stray = parse("Introduction");
+        assert_eq!(stray.confidence, 0.0);
+        let prose = parse("we thank the referee for comments (2019)");
+        assert_eq!(prose.confidence, 0.2);
+    }
+
+    #[test]
+    fn split_inverted_author_names() {
+        assert_eq!(
+            split_authors("Aaij, R., Adeva, B., et al."),
+            vec!["Aaij, R.", "Adeva, B."]
+        );
+        assert_eq!(
+            split_authors("A. Author, B. Other and C. Third"),
+            vec!["A. Author", "B. Other", "C. Third"]
+        );
+    }
+
+    #[test]
+    fn author_date_authors() {
+        let r = parse("Aaij, R., Adeva, B., et al. (2015) Phys. Rev. Lett. 115, 072001");
+        assert_eq!(r.authors.as_deref(), Some("Aaij, R., Adeva, B."));
+        assert!(r.et_al);
+        assert_eq!(r.authors_list, Some(vec!["Aaij, R.".to_string(), "Adeva, B.".to_string()]));
+    }
+
+    #[test]
+    fn et_al_is_flagged_and_stripped() {
+        let r = parse("Aad, G. et al. (ATLAS Collaboration), Phys. Lett. B 716, 1 (2012)");
+        assert_eq!(r.authors.as_deref(), Some("Aad, G."));
+        assert_eq!(r.authors_list, Some(vec!["Aad, G.".to_string()]));
+        assert!(r.et_al);
+        assert_eq!(r.collaboration.as_deref(), Some("ATLAS Collaboration"));
+        for text in [
+            "[1] G. Aad et al. [ATLAS Collaboration], Phys. Lett. B 716, 1 (2012)",
+            "[1] G. Aad et al. [ ATLAS Collaboration ], Phys. Lett. B 716, 1 (2012)",
+            "[1] ATLAS Collaboration, G. Aad et al., Phys. Lett. B 716, 1 (2012)",
+            "[1] The ATLAS Collaboration, G. Aad et al., Phys. Lett. B 716, 1 (2012)",
+        ] {
+            let r = parse(text);
+            assert_eq!(r.collaboration.as_deref(), Some("ATLAS Collaboration"), "{text}");
+            assert_eq!(r.authors.as_deref(), Some("G. Aad"), "{text}");
+            assert!(r.et_al, "{text}");
+        }
+
+        let r = parse("[3] A. Author and collaborators, Phys. Lett. B 716, 1 (2012)");
+        assert_eq!(r.authors.as_deref(), Some("A. Author"));
+        assert!(r.et_al);
+        let r = parse("[4] A. Author, B. Other, et al, Phys. Lett. B 716, 1 (2012)");
+        assert_eq!(r.authors.as_deref(), Some("A. Author, B. Other"));
+        assert!(r.et_al);
+        let r = parse("[5] A. Author and B. Other, Phys. Lett. B 716, 1 (2012)");
+        assert!(!r.et_al);
+    }
+
+    #[test]
+    fn author_date_block_ends_at_year_not_quote() {
+        let r = parse("O\u{201d}Connell, D., Smith, J. 2010, \"Title\", ApJ, 700, 1");
+        assert_eq!(r.authors.as_deref(), Some("O\u{201d}Connell, D., Smith, J."));
+    }
+
+    #[test]
+    fn editors_stop_at_publisher() {
+        let r = parse("eds. A. Jones and B. Lee, Springer (2001)");
+        assert_eq!(r.editors.as_deref(), Some("A. Jones and B. Lee"));
+        assert_eq!(r.authors, None);
+        assert_eq!(r.publisher.as_deref(), Some("Springer"));
+        assert_eq!(r.journal_year.as_deref(), Some("2001"));
+    }
+
+    #[test]
+    fn editors_are_not_authors() {
+        let r = parse("C. Writer, New Results, edited by D. Chief, Dover (1990)");
+        assert!(!r.authors.unwrap().contains("Chief"));
+        assert_eq!(r.editors.as_deref(), Some("D. Chief"));
+    }
+
+    #[test]
+    fn edition_is_not_an_editor() {
+        let r = parse("S. Weinberg, The Quantum Theory of Fields, 2nd ed., Cambridge Univ. Press (2005)");
+        assert_eq!(r.ref_type, RefType::Book);
+        assert_eq!(r.publisher.as_deref(), Some("Cambridge University Press"));
+    }
+
+    #[test]
+    fn thesis_and_journal_and_preprint() {
+        assert_eq!(parse("J. Smith, Ph.D. thesis, MIT (2010)").ref_type, RefType::Thesis);
+        assert_eq!(parse("S. W. Hawking, Commun. Math. Phys. 43, 199 (1975)").ref_type, RefType::Journal);
+        assert_eq!(parse("A. Author, arXiv:1001.0785").ref_type, RefType::Preprint);
+        // "thesis" inside a longer word is no thesis cue
+        let r = parse("A. Author, Big-bang nucleosynthesis, arXiv:1001.0785");
+        assert_eq!(r.ref_type, RefType::Preprint);
+        // A full journal citation stays a journal article
+        let r = parse("A. Author, Test of the thesis, Phys. Rev. D 72, 052002 (2005)");
+        assert_eq!(r.ref_type, RefType::Journal);
+    }
+
+    #[test]
+    fn journal_issue() {
+        let r = parse("A. Author, Phys. Rev. Lett. 82(25), 4971 (1999)");
+        assert_eq!(r.journal_volume.as_deref(), Some("82"));
+        assert_eq!(r.journal_issue.as_deref(), Some("25"));
+        assert_eq!(r.journal_page.as_deref(), Some("4971"));
+
+        let r = parse("A. Author, IEEE Trans. Nucl. Sci. vol. 53, no. 3, pp. 1234-1240 (2006)");
+        assert_eq!(r.journal_volume.as_deref(), Some("53"));
+        assert_eq!(r.journal_issue.as_deref(), Some("3"));
+        assert_eq!(r.journal_page.as_deref(), Some("1234-1240"));
+
+        let r = parse("A. Author, Phys. Rev. D 72, 052002 (2005)");
+        assert_eq!(r.journal_issue, None);
+    }
+
+    #[test]
+    fn journal_clearing_can_be_disabled() {
+        let text = "A. Author, Lectures given at Nucl. Phys. B Summer School (1994)";
+        assert_eq!(parse(text).journal_title, None);
+        let raw = RawReference {
+            text: text.to_string(),
+            linemarker: None,
+            from_marker: None,
+            source: ReferenceSource::Text,
+            page_num: 0,
+            section: 0,
+            italic_runs: Vec::new(),
+        };
+        let opts = ParseOptions { keep_journal_without_volume: true, ..Default::default() };
+        let r = parse_references_with(&raw, &crate::tokenizer::tokenize(text), &opts).remove(0);
+        assert_eq!(r.journal_title.as_deref(), Some("Nucl. Phys. B"));
+        assert_eq!(r.journal_volume, None);
+    }
+
+    #[test]
+    fn month_qualified_years() {
+        for text in [
+            "A. Author, Phys. Rev. D 72, 052002 (March 2005)",
+            "A. Author, Phys. Rev. D 72, 052002, Jan. 2005",
+        ] {
+            let r = parse(text);
+            assert_eq!(r.journal_year.as_deref(), Some("2005"), "{text}");
+            assert_eq!(r.journal_page.as_deref(), Some("052002"), "{text}");
+        }
+    }
+
+    #[test]
+    fn keyword_prefixed_numeration() {
+        let r = parse("A. Author, Phys. Rev. D, vol. 72, pp. 1-10 (2005)");
+        assert_eq!(r.journal_volume.as_deref(), Some("72"));
+        assert_eq!(r.journal_page.as_deref(), Some("1-10"));
+        assert_eq!(r.journal_year.as_deref(), Some("2005"));
+
+        let r = parse("A. Author, Nucl. Phys. B, vol. 417, p. 181 (1994)");
+        assert_eq!(r.journal_volume.as_deref(), Some("417"));
+        assert_eq!(r.journal_page.as_deref(), Some("181"));
+
+        // Positionally the leading range would be the volume
+        let r = parse("A. Author, JHEP, pp. 10-20, vol. 5 (2006)");
+        assert_eq!(r.journal_volume.as_deref(), Some("5"));
+        assert_eq!(r.journal_page.as_deref(), Some("10-20"));
+    }
+
+    #[test]
+    fn page_before_volume() {
+        let r = parse("A. Author, Nucl. Phys. B, p. 835, vol. 140 (2005)");
+        assert_eq!((r.journal_volume.as_deref(), r.journal_page.as_deref()), (Some("140"), Some("835")));
+
+        for (text, volume, page) in [
+            ("A. Author, Phys. Rev. D 052002 (2005) 72", "72", "052002"),
+            ("A. Author, Phys. Rev. D 052002, 72 (2005)", "72", "052002"),
+            // The common orders are left alone
+            ("A. Author, Proc. SPIE 10699 (2018) 12", "10699", "12"),
+            ("A. Author, Phys. Rev. D 72, 052002 (2005)", "72", "052002"),
+            ("A. Author, JHEP 1106 (2011) 077", "1106", "077"),
+            ("A. Author, Lect. Notes Math. 1234, 56 (1990)", "1234", "56"),
+            ("A. Author, Proc. SPIE 10699, 12 (2018)", "10699", "12"),
+        ] {
+            let r = parse(text);
+            assert_eq!(r.journal_volume.as_deref(), Some(volume), "{text}");
+            assert_eq!(r.journal_page.as_deref(), Some(page), "{text}");
+        }
+    }
+
+    #[test]
+    fn implausible_volumes_are_flagged() {
+        let r = parse("A. Author, Phys. Rev. D 052002 (2005)");
+        assert_eq!(r.journal_volume.as_deref(), Some("052002"));
+        assert_eq!(r.warnings, ["volume looks like article number"]);
+        let r = parse("A. Author, Phys. Rev. D 72, 052002 (2005)");
+        assert!(r.warnings.is_empty());
+        let r = parse("A. Author, Phys. Lett. B 71600, 1 (2012)");
+        assert_eq!(r.warnings, ["volume is implausibly large"]);
+        // No journal, nothing to doubt
+        let r = ParsedReference { journal_title: None, journal_volume: Some("052002".into()), ..r };
+        assert!(validation_warnings(&r).is_empty());
+    }
+
+    #[test]
+    fn journal_after_numeration() {
+        let r = parse("A. Author, 72, 052002 (2005), Phys. Rev. D");
+        assert_eq!(r.journal_title.as_deref(), Some("Phys. Rev. D"));
+        assert_eq!(r.journal_volume.as_deref(), Some("72"));
+        assert_eq!(r.journal_page.as_deref(), Some("052002"));
+        assert_eq!(r.journal_year.as_deref(), Some("2005"));
+        assert_eq!(r.numeration_raw.as_deref(), Some("72, 052002 (2005)"));
+        assert_eq!(r.authors.as_deref(), Some("A. Author"));
+
+        // Numeration on both sides: the usual order wins
+        let r = parse("A. Author, 12, Phys. Rev. D 72, 052002 (2005)");
+        assert_eq!(r.journal_volume.as_deref(), Some("72"));
+        assert_eq!(r.journal_page.as_deref(), Some("052002"));
+
+        // A year alone before the name doesn't make it a journal citation
+        let r = parse("A. Author (2005), Phys. Rev. D");
+        assert_eq!(r.journal_volume, None);
+        assert_eq!(r.journal_title, None);
+    }
+
+    #[test]
+    fn physics_reports_page_ranges() {
+        for (text, volume, year, page) in [
+            ("H. E. Haber and G. L. Kane, Phys. Rept. 117, 75-263 (1985)", Some("117"), "1985", "75-263"),
+            ("S. P. Martin, Phys. Rep. 267 (1996) 195 - 373", Some("267"), "1996", "195-373"),
+            ("M. Drees, Phys. Rept. 267 (1996) 195--373", Some("267"), "1996", "195-373"),
+            ("G. Bertone, D. Hooper and J. Silk, Phys. Rept. 405 (2005) 279-390", Some("405"), "2005", "279-390"),
+            ("G. Jungman, Phys. Rept. (1996) 267 195-373", Some("267"), "1996", "195-373"),
+            ("R. Author, Phys. Rept. (2005) 279-390", None, "2005", "279-390"),
+        ] {
+            let r = parse(text);
+            assert_eq!(r.journal_volume.as_deref(), volume, "{text}");
+            assert_eq!(r.journal_year.as_deref(), Some(year), "{text}");
+            assert_eq!(r.journal_page.as_deref(), Some(page), "{text}");
+        }
+    }
+
+    #[test]
+    fn first_author_surnames() {
+        assert_eq!(first_author_surname("A. Author").as_deref(), Some("Author"));
+        assert_eq!(first_author_surname("Author, A.").as_deref(), Some("Author"));
+        assert_eq!(first_author_surname("Author et al.").as_deref(), Some("Author"));
+        assert_eq!(first_author_surname("J. D. Bekenstein and B. Other").as_deref(), Some("Bekenstein"));
+        assert_eq!(first_author_surname("Aaij, R., Adeva, B., et al.").as_deref(), Some("Aaij"));
+        assert_eq!(first_author_surname("J.-P. van der Berg").as_deref(), Some("van der Berg"));
+        assert_eq!(first_author_surname("A.B. Author & C. Other").as_deref(), Some("Author"));
+        assert_eq!(first_author_surname("J. D."), None);
+        assert_eq!(first_author_surname(""), None);
+    }
+}
@@ -1,36 +1,225 @@
+use std::ops::Range;
 use std::path::Path;
+use std::str::FromStr;
 
-use anyhow::{Context, Result};
 use pdfium_render::prelude::*;
 
-use crate::types::{PageChars, PdfChar};
+use crate::error::{PdfLoadReason, RefextractError};
+use crate::types::{FootnoteRule, PageChars, PdfChar, RULE_MARGIN_TOLERANCE};
 
-/// Load a PDF and extract characters with positions from every page.
+type Result<T, E = RefextractError> = std::result::Result<T, E>;
+
+/// Settings for character extraction.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// OCR pages whose text layer is (nearly) empty.
+    pub ocr_fallback: bool,
+    /// Tesseract settings used by the OCR fallback.
+    pub ocr: crate::ocr::OcrOptions,
+    /// Only extract these pages (all pages when `None`).
+    pub pages: Option<PageRange>,
+    /// Note per-page corrections (rotated pages) on stderr.
+    pub debug: bool,
+    /// User password for encrypted PDFs.
+    pub password: Option<String>,
+}
+
+/// A `--pages` selection: "180-210", "180-", "7", or "-20" for the last 20.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageRange {
+    /// Inclusive 1-based span; `end: None` runs to the last page.
+    Span { start: usize, end: Option<usize> },
+    /// The last N pages.
+    Last(usize),
+}
+
+impl FromStr for PageRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| format!("invalid page number '{n}' in range '{s}'"))
+        };
+        if let Some(last) = s.strip_prefix('-') {
+            return Ok(PageRange::Last(parse(last)?));
+        }
+        match s.split_once('-') {
+            Some((start, "")) => Ok(PageRange::Span { start: parse(start)?, end: None }),
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if end < start {
+                    return Err(format!("page range '{s}' ends before it starts"));
+                }
+                Ok(PageRange::Span { start, end: Some(end) })
+            }
+            None => {
+                let page = parse(s)?;
+                Ok(PageRange::Span { start: page, end: Some(page) })
+            }
+        }
+    }
+}
+
+impl PageRange {
+    /// Resolve to 0-based page indices for a document with `page_count` pages.
+    pub fn resolve(&self, page_count: usize) -> Result<Range<usize>> {
+        match *self {
+            PageRange::Last(n) => Ok(page_count.saturating_sub(n)..page_count),
+            PageRange::Span { start, end } => {
+                let end = end.unwrap_or(page_count);
+                if start > page_count || end > page_count {
+                    return Err(RefextractError::PageRange { start, end, page_count });
+                }
+                Ok(start - 1..end)
+            }
+        }
+    }
+}
+
+const DEFAULT_PDFIUM_PATHS: &[&str] = &[
+    "/usr/local/lib/libpdfium.so",
+    "/usr/lib/libpdfium.so",
+    "/usr/local/lib/libpdfium.dylib",
+    "/usr/lib/libpdfium.dylib",
+];
+
+/// Load the pdfium library from `path`, or else from the system library
+/// path and the usual install locations.
+pub fn bind_pdfium(path: Option<&str>) -> Result<Pdfium> {
+    let bindings = match path {
+        Some(path) => Pdfium::bind_to_library(path).map_err(|e| {
+            RefextractError::PdfiumBinding(format!("Failed to load pdfium from: {path}: {e}"))
+        })?,
+        None => Pdfium::bind_to_system_library().or_else(|_| try_default_pdfium_paths())?,
+    };
+    Ok(Pdfium::new(bindings))
+}
+
+fn try_default_pdfium_paths() -> Result<Box<dyn PdfiumLibraryBindings>> {
+    DEFAULT_PDFIUM_PATHS
+        .iter()
+        .find_map(|path| Pdfium::bind_to_library(path).ok())
+        .ok_or_else(|| {
+            RefextractError::PdfiumBinding(format!(
+                "Failed to find pdfium. Searched system library path and {DEFAULT_PDFIUM_PATHS:?}. \
+                 Use --pdfium-path or set PDFIUM_LIB_PATH."
+            ))
+        })
+}
+
+/// Load a PDF and extract characters with positions from every selected page.
 pub fn extract_chars(
     pdfium: &Pdfium,
     path: &Path,
-    ocr_fallback: bool,
+    opts: &ExtractOptions,
 ) -> Result<Vec<PageChars>> {
-    let document = pdfium
-        .load_pdf_from_file(path, None)
-        .with_context(|| format!("Failed to load PDF: {}", path.display()))?;
+    let label = path.display().to_string();
+    check_exists(path, &label)?;
+    let document = load_document(|password| pdfium.load_pdf_from_file(path, password), &label, opts)?;
+    extract_document_chars(&document, &label, opts)
+}
+
+/// pdfium reports a missing file as a generic file error; name it instead.
+fn check_exists(path: &Path, label: &str) -> Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    Err(RefextractError::PdfLoad { label: label.to_string(), reason: PdfLoadReason::NotFound })
+}
+
+/// Load a PDF from an in-memory buffer (e.g. stdin) and extract characters.
+/// `label` names the source in error messages.
+pub fn extract_chars_from_bytes(
+    pdfium: &Pdfium,
+    bytes: Vec<u8>,
+    label: &str,
+    opts: &ExtractOptions,
+) -> Result<Vec<PageChars>> {
+    let document = load_document(|password| pdfium.load_pdf_from_byte_slice(&bytes, password), label, opts)?;
+    extract_document_chars(&document, label, opts)
+}
+
+/// Open a document with `opts.password`. Without one, a PDF that asks for
+/// a password is retried with the empty password, which opens files that
+/// only have an owner (permissions) password.
+fn load_document<'a>(
+    load: impl Fn(Option<&'a str>) -> Result<PdfDocument<'a>, PdfiumError>,
+    label: &str,
+    opts: &'a ExtractOptions,
+) -> Result<PdfDocument<'a>> {
+    let password = opts.password.as_deref();
+    let result = match load(password) {
+        Err(e) if password.is_none() && is_password_error(&e) => load(Some("")),
+        result => result,
+    };
+    result.map_err(|e| RefextractError::PdfLoad {
+        label: label.to_string(),
+        reason: load_error_reason(&e, password.is_some()),
+    })
+}
+
+fn is_password_error(e: &PdfiumError) -> bool {
+    matches!(e, PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError))
+}
 
-    document
-        .pages()
+/// Say why a PDF couldn't be opened, telling a missing or wrong password
+/// apart from a damaged file.
+fn load_error_reason(e: &PdfiumError, password_given: bool) -> PdfLoadReason {
+    match e {
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) if password_given => {
+            PdfLoadReason::WrongPassword
+        }
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+            PdfLoadReason::NeedsPassword
+        }
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::SecurityError) => {
+            PdfLoadReason::UnsupportedSecurity
+        }
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::FormatError) => PdfLoadReason::Corrupt,
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::FileError) => PdfLoadReason::Unreadable,
+        e => PdfLoadReason::Other(e.to_string()),
+    }
+}
+
+fn extract_document_chars(document: &PdfDocument, label: &str, opts: &ExtractOptions) -> Result<Vec<PageChars>> {
+    let pages = document.pages();
+    let page_count = pages.len() as usize;
+    let selected = match &opts.pages {
+        Some(range) => range.resolve(page_count)?,
+        None => 0..page_count,
+    };
+    // Enumerate before skipping so page_num keeps the document numbering.
+    pages
         .iter()
         .enumerate()
-        .map(|(idx, page)| extract_page_chars(idx, &page, ocr_fallback))
-        .collect()
+        .skip(selected.start)
+        .take(selected.len())
+        .map(|(idx, page)| extract_page_chars(idx, &page, opts))
+        .collect::<Result<Vec<_>>>()
+        .and_then(|pages| check_text_layer(pages, label))
+}
+
+/// Fail with `NoText` when none of the pages has a visible character.
+fn check_text_layer(pages: Vec<PageChars>, label: &str) -> Result<Vec<PageChars>> {
+    if !pages.is_empty() && pages.iter().all(|p| p.chars.iter().all(|c| c.ch.is_whitespace())) {
+        return Err(RefextractError::NoText { label: label.to_string() });
+    }
+    Ok(pages)
 }
 
 fn extract_page_chars(
     page_idx: usize,
     page: &PdfPage,
-    ocr_fallback: bool,
+    opts: &ExtractOptions,
 ) -> Result<PageChars> {
     let text_page = page
         .text()
-        .with_context(|| format!("Failed to load text for page {}", page_idx + 1))?;
+        .map_err(|source| RefextractError::TextExtraction { page: page_idx + 1, source })?;
 
     let mut chars: Vec<PdfChar> = text_page
         .chars()
@@ -38,9 +227,27 @@ fn extract_page_chars(
         .filter_map(|ch| convert_text_char(&ch))
         .collect();
 
+    // Char bounds are in unrotated page space, while width and height are
+    // already those of the displayed page.
+    let (width, height) = (page.width().value, page.height().value);
+    let quarter_turns = match page.rotation() {
+        Ok(PdfPageRenderRotation::Degrees90) => 1,
+        Ok(PdfPageRenderRotation::Degrees180) => 2,
+        Ok(PdfPageRenderRotation::Degrees270) => 3,
+        _ => 0,
+    };
+    if quarter_turns != 0 {
+        let (unrotated_width, unrotated_height) =
+            if quarter_turns == 2 { (width, height) } else { (height, width) };
+        rotate_upright(&mut chars, unrotated_width, unrotated_height, quarter_turns);
+        if opts.debug {
+            eprintln!("p{}: rotated {}°, coordinates turned upright", page_idx + 1, quarter_turns * 90);
+        }
+    }
+
     let meaningful_chars = chars.iter().filter(|c| !c.ch.is_whitespace()).count();
-    if meaningful_chars < 10 && ocr_fallback {
-        match crate::ocr::ocr_page(page, page_idx) {
+    if meaningful_chars < 10 && opts.ocr_fallback {
+        match crate::ocr::ocr_page(page, page_idx, &opts.ocr) {
             Ok(ocr_chars) if ocr_chars.len() > chars.len() => {
                 eprintln!("OCR fallback: page {} ({} chars)", page_idx + 1, ocr_chars.len());
                 chars = ocr_chars;
@@ -52,14 +259,95 @@ fn extract_page_chars(
         }
     }
 
+    // Rules are only looked for on upright pages; their bounds would need
+    // the same turn as the chars otherwise.
+    let footnote_separator =
+        if quarter_turns == 0 { footnote_separator(&horizontal_rules(page), &chars, width, height) } else { None };
+
     Ok(PageChars {
         page_num: page_idx + 1,
-        width: page.width().value,
-        height: page.height().value,
+        width,
+        height,
         chars,
+        footnote_separator,
     })
 }
 
+/// A thin horizontal path object: `(left, width, y)` in page coordinates.
+type Rule = (f32, f32, f32);
+
+/// Rules thinner than this (in points) are lines, not filled boxes.
+const MAX_RULE_THICKNESS: f32 = 1.5;
+
+/// Horizontal lines drawn on the page, from its top-level path objects.
+fn horizontal_rules(page: &PdfPage) -> Vec<Rule> {
+    page.objects()
+        .iter()
+        .filter(|object| object.object_type() == PdfPageObjectType::Path)
+        .filter_map(|object| object.bounds().ok())
+        .filter(|bounds| bounds.height().value <= MAX_RULE_THICKNESS && bounds.width().value > 0.0)
+        .map(|bounds| (bounds.left().value, bounds.width().value, bounds.bottom().value))
+        .collect()
+}
+
+/// The footnote separator among a page's rules: a short line (5–50% of the
+/// page width, starting in the left half) in the bottom 40% of the page,
+/// starting at the left margin of the text in its column, with smaller
+/// text below it than above. Full-width rules belong to tables and
+/// figures, indented ones to equations and tables. With several
+/// candidates, the highest wins, so every footnote lies below it.
+pub(crate) fn footnote_separator(
+    rules: &[Rule],
+    chars: &[PdfChar],
+    page_width: f32,
+    page_height: f32,
+) -> Option<FootnoteRule> {
+    rules
+        .iter()
+        .map(|&(x, width, y)| FootnoteRule { x, width, y })
+        .filter(|rule| {
+            (0.05..=0.5).contains(&(rule.width / page_width))
+                && rule.x < page_width / 2.0
+                && rule.y < page_height * 0.4
+                && separates_footnotes(rule, chars)
+        })
+        .reduce(|a, b| if b.y > a.y { b } else { a })
+}
+
+/// The text in the rule's column starts where the rule does, and all of
+/// it below the rule is set smaller than the text above it on average.
+fn separates_footnotes(rule: &FootnoteRule, chars: &[PdfChar]) -> bool {
+    let column: Vec<&PdfChar> = chars
+        .iter()
+        .filter(|c| !c.ch.is_whitespace())
+        .filter(|c| c.x < rule.x + rule.width && c.x + c.width > rule.x - 2.0 * RULE_MARGIN_TOLERANCE)
+        .collect();
+    let Some(margin) = column.iter().map(|c| c.x).reduce(f32::min) else {
+        return false;
+    };
+    let above: Vec<f32> = column.iter().filter(|c| c.y > rule.y).map(|c| c.font_size).collect();
+    let largest_below = column.iter().filter(|c| c.y < rule.y).map(|c| c.font_size).reduce(f32::max);
+    let mean_above = (!above.is_empty()).then(|| above.iter().sum::<f32>() / above.len() as f32);
+    (margin - rule.x).abs() <= RULE_MARGIN_TOLERANCE
+        && matches!((largest_below, mean_above), (Some(below), Some(above)) if below < above * 0.95)
+}
+
+/// Map chars from unrotated page space (`width` x `height`, origin bottom
+/// left) to the page as displayed after turning it `quarter_turns` times
+/// clockwise (the page's `/Rotate` divided by 90), so layout sees
+/// upright lines.
+fn rotate_upright(chars: &mut [PdfChar], width: f32, height: f32, quarter_turns: u32) {
+    for c in chars {
+        let (x, y, w, h) = (c.x, c.y, c.width, c.height);
+        (c.x, c.y, c.width, c.height) = match quarter_turns % 4 {
+            1 => (y, width - x - w, h, w),
+            2 => (width - x - w, height - y - h, w, h),
+            3 => (height - y - h, x, h, w),
+            _ => (x, y, w, h),
+        };
+    }
+}
+
 fn convert_text_char(ch: &PdfPageTextChar) -> Option<PdfChar> {
     let unicode = ch.unicode_char()?;
     if unicode.is_control() && unicode != ' ' {
@@ -94,3 +382,136 @@ fn char_bounds(ch: &PdfPageTextChar) -> Option<(f32, f32, f32, f32)> {
         (rect.top().value - rect.bottom().value).abs(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_page_ranges() {
+        assert_eq!("180-210".parse(), Ok(PageRange::Span { start: 180, end: Some(210) }));
+        assert_eq!("180-".parse(), Ok(PageRange::Span { start: 180, end: None }));
+        assert_eq!("7".parse(), Ok(PageRange::Span { start: 7, end: Some(7) }));
+        assert_eq!("-20".parse(), Ok(PageRange::Last(20)));
+        assert!("0-5".parse::<PageRange>().is_err());
+        assert!("20-10".parse::<PageRange>().is_err());
+        assert!("abc".parse::<PageRange>().is_err());
+    }
+
+    #[test]
+    fn rotated_page_is_turned_upright() {
+        // A landscape page (792 wide, 612 tall as displayed) stored as a
+        // portrait page with /Rotate 90: lines run bottom to top, and later
+        // lines sit further left in unrotated space.
+        let (width, height) = (612.0, 792.0);
+        let lines = ["[1] A. Author, Phys. Rev. D 72, 052002 (2005)", "[2] B. Other, Nucl. Phys. B 417, 181 (1994)"];
+        let mut chars = Vec::new();
+        for (n, line) in lines.iter().enumerate() {
+            for (i, ch) in line.chars().enumerate() {
+                let (row_y, col_x) = (500.0 - n as f32 * 12.0, 50.0 + i as f32 * 5.0);
+                chars.push(PdfChar {
+                    ch,
+                    x: width - row_y - 10.0,
+                    y: col_x,
+                    width: 10.0,
+                    height: 5.0,
+                    font_size: 10.0,
+                    font_name: "Times-Roman".into(),
+                });
+            }
+        }
+        rotate_upright(&mut chars, width, height, 1);
+        assert_eq!((chars[0].x, chars[0].y, chars[0].width, chars[0].height), (50.0, 500.0, 5.0, 10.0));
+
+        let page = PageChars { page_num: 1, width: height, height: width, chars, footnote_separator: None };
+        let blocks = crate::layout::group_page(&page, &Default::default());
+        let text: Vec<String> = blocks.iter().flat_map(|b| &b.lines).map(|l| l.text()).collect();
+        assert_eq!(text, lines);
+
+        // Turning back the other way restores the stored coordinates
+        let mut c = vec![page.chars[0].clone()];
+        rotate_upright(&mut c, height, width, 3);
+        assert_eq!((c[0].x, c[0].y, c[0].width, c[0].height), (width - 500.0 - 10.0, 50.0, 10.0, 5.0));
+        rotate_upright(&mut c, width, height, 2);
+        assert_eq!((c[0].x, c[0].y), (500.0, 792.0 - 50.0 - 5.0));
+    }
+
+    #[test]
+    fn short_low_rule_is_the_footnote_separator() {
+        let (width, height) = (612.0, 792.0);
+        // A line of text at x = 72 in the given font size
+        let text = |y: f32, font_size: f32| -> Vec<PdfChar> {
+            (0..60)
+                .map(|i| PdfChar {
+                    ch: 'a',
+                    x: 72.0 + 5.0 * i as f32,
+                    y,
+                    width: 5.0,
+                    height: font_size,
+                    font_size,
+                    font_name: "Times-Roman".into(),
+                })
+                .collect()
+        };
+        let chars = [text(400.0, 10.0), text(200.0, 10.0), text(100.0, 8.0)].concat();
+        // A table rule across the text block, a separator, and a rule in the header
+        let rules = [(72.0, 468.0, 150.0), (72.0, 144.0, 120.0), (72.0, 144.0, 700.0)];
+        let separator = footnote_separator(&rules, &chars, width, height);
+        assert_eq!(separator, Some(FootnoteRule { x: 72.0, width: 144.0, y: 120.0 }));
+        assert_eq!(footnote_separator(&rules[..1], &chars, width, height), None);
+        assert_eq!(footnote_separator(&[(400.0, 144.0, 120.0)], &chars, width, height), None);
+        // An indented rule, or one above body-size text, separates nothing
+        assert_eq!(footnote_separator(&[(100.0, 144.0, 120.0)], &chars, width, height), None);
+        assert_eq!(footnote_separator(&[(72.0, 144.0, 300.0)], &chars, width, height), None);
+    }
+
+    #[test]
+    fn load_errors_name_the_reason() {
+        let internal = PdfiumError::PdfiumLibraryInternalError;
+        let message = |e, password_given| {
+            let reason = load_error_reason(&internal(e), password_given);
+            RefextractError::PdfLoad { label: "a.pdf".into(), reason }.to_string()
+        };
+        assert!(message(PdfiumInternalError::PasswordError, false).ends_with("needs a password (--password)"));
+        assert!(message(PdfiumInternalError::PasswordError, true).ends_with("a.pdf: wrong password"));
+        assert!(message(PdfiumInternalError::FormatError, false).ends_with("a.pdf is corrupt or not a PDF"));
+        assert!(message(PdfiumInternalError::FileError, true).ends_with("could not be read"));
+        assert!(is_password_error(&internal(PdfiumInternalError::PasswordError)));
+        assert!(!is_password_error(&internal(PdfiumInternalError::SecurityError)));
+    }
+
+    #[test]
+    fn missing_file_and_textless_pdf_are_told_apart() {
+        let missing = check_exists(Path::new("/nonexistent/paper.pdf"), "paper.pdf");
+        assert!(matches!(
+            missing,
+            Err(RefextractError::PdfLoad { reason: PdfLoadReason::NotFound, .. })
+        ));
+
+        let blank = |page_num, chars| PageChars { page_num, width: 612.0, height: 792.0, chars, footnote_separator: None };
+        let space = PdfChar {
+            ch: ' ',
+            x: 0.0,
+            y: 0.0,
+            width: 5.0,
+            height: 10.0,
+            font_size: 10.0,
+            font_name: "OCR".into(),
+        };
+        let scan = check_text_layer(vec![blank(1, Vec::new()), blank(2, vec![space.clone()])], "scan.pdf");
+        assert!(matches!(&scan, Err(RefextractError::NoText { label }) if label == "scan.pdf"));
+
+        let text = PdfChar { ch: 'A', ..space };
+        assert!(check_text_layer(vec![blank(1, Vec::new()), blank(2, vec![text])], "paper.pdf").is_ok());
+    }
+
+    #[test]
+    fn resolve_page_ranges() {
+        let span = PageRange::Span { start: 180, end: Some(210) };
+        assert_eq!(span.resolve(250).unwrap(), 179..210);
+        assert!(span.resolve(200).is_err(), "range past the last page must fail");
+        assert_eq!(PageRange::Span { start: 5, end: None }.resolve(8).unwrap(), 4..8);
+        assert_eq!(PageRange::Last(20).resolve(250).unwrap(), 230..250);
+        assert_eq!(PageRange::Last(20).resolve(5).unwrap(), 0..5);
+    }
+}
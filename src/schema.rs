@@ -0,0 +1,262 @@
+//! JSON Schema for the JSON output (`--print-schema`).
+//!
+//! Written by hand to mirror the serde layout of `ParsedReference`; the
+//! tests below fail when a field is added there but not here.
+
+use serde_json::{Map, Value, json};
+
+/// Optional string fields of `ParsedReference`, with descriptions.
+const OPTIONAL_STRINGS: &[(&str, &str)] = &[
+    ("linemarker", "Reference marker without brackets, e.g. \"12\""),
+    ("from_marker", "Marker of the entry a sub-reference was split from"),
+    ("authors", "Author list as written"),
+    ("editors", "Editor list as written"),
+    ("title", "Title of the cited work"),
+    ("journal_title", "Standardized journal name"),
+    ("journal_volume", "Volume, including a section letter (\"D72\")"),
+    ("journal_issue", "Issue number within the volume"),
+    ("journal_year", "Publication year"),
+    ("journal_page", "First page, article number or page range"),
+    ("numeration_raw", "Source text the volume/year/page were read from"),
+    ("status", "Publication status for forthcoming articles (\"in press\")"),
+    ("note", "Correction a sub-reference cites: \"erratum\", \"addendum\" or \"publisher's note\""),
+    ("doi", "DOI without resolver prefix"),
+    ("short_doi", "shortDOI (\"10/abcde\") as written; `doi` is filled once it is resolved"),
+    ("doi_prefix", "Registrant part of the DOI, e.g. \"10.1103\""),
+    ("handle", "Handle without resolver prefix, e.g. \"2078.1/123456\""),
+    ("arxiv_id", "arXiv identifier, without version"),
+    ("texkey", "INSPIRE texkey, e.g. \"Weinberg:1967tq\""),
+    ("isbn", "ISBN"),
+    ("report_number", "Standardized report number"),
+    ("url", "URL"),
+    ("collaboration", "Standardized collaboration name"),
+    ("publisher", "Publisher of a book or proceedings"),
+];
+
+const REF_TYPES: &[&str] = &["Journal", "Book", "Proceedings", "Thesis", "Preprint", "Unknown"];
+const SOURCES: &[&str] = &["ReferenceSection", "Footnote", "Text"];
+
+/// Schema for the array printed in single-file JSON mode.
+pub fn reference_list_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "refextract output",
+        "type": "array",
+        "items": { "$ref": "#/$defs/ParsedReference" },
+        "$defs": { "ParsedReference": parsed_reference_schema() },
+    })
+}
+
+fn parsed_reference_schema() -> Value {
+    let mut properties = Map::new();
+    properties.insert(
+        "raw_ref".into(),
+        json!({ "type": "string", "description": "Reference text as extracted; omitted with --no-raw" }),
+    );
+    for (name, description) in OPTIONAL_STRINGS {
+        properties.insert(
+            (*name).into(),
+            json!({ "type": "string", "description": description }),
+        );
+    }
+    properties.insert(
+        "authors_list".into(),
+        json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "`authors` split into individual names",
+        }),
+    );
+    properties.insert(
+        "report_numbers".into(),
+        json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Every report number; `report_number` is the first",
+        }),
+    );
+    properties.insert(
+        "et_al".into(),
+        json!({
+            "type": "boolean",
+            "description": "Author list truncated with \"et al.\"; omitted when false",
+        }),
+    );
+    properties.insert(
+        "ref_type".into(),
+        json!({ "enum": REF_TYPES, "description": "Kind of work cited" }),
+    );
+    properties.insert(
+        "confidence".into(),
+        json!({
+            "type": "number",
+            "minimum": 0.0,
+            "maximum": 1.0,
+            "description": "Estimate that this is a real citation",
+        }),
+    );
+    properties.insert(
+        "source".into(),
+        json!({ "enum": SOURCES, "description": "Where the reference was found" }),
+    );
+    properties.insert(
+        "found_on_page".into(),
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "description": "1-based PDF page the reference came from; 0 for text input",
+        }),
+    );
+    properties.insert(
+        "cited_on_pages".into(),
+        json!({
+            "type": "array",
+            "items": { "type": "integer", "minimum": 1 },
+            "description": "Body pages citing the entry by its marker (--cited-on-pages); omitted when none",
+        }),
+    );
+    properties.insert(
+        "section".into(),
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "description": "Reference section of a document with several, counted from 0; omitted when 0",
+        }),
+    );
+    properties.insert(
+        "warnings".into(),
+        json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": "Signs of a mis-parse, e.g. \"volume looks like article number\"; omitted when empty",
+        }),
+    );
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": ["ref_type", "confidence", "source", "found_on_page"],
+        "additionalProperties": false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ParsedReference, RefType, ReferenceSource};
+
+    fn some(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    /// Every field populated, so every serialized key gets checked.
+    fn full_reference() -> ParsedReference {
+        ParsedReference {
+            raw_ref: "S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967)".into(),
+            linemarker: some("1"),
+            from_marker: some("1"),
+            authors: some("S. Weinberg"),
+            authors_list: Some(vec!["S. Weinberg".into()]),
+            et_al: true,
+            editors: some("A. Editor"),
+            title: some("A Model of Leptons"),
+            journal_title: some("Phys. Rev. Lett."),
+            journal_volume: some("19"),
+            journal_issue: some("19"),
+            journal_year: some("1967"),
+            journal_page: some("1264"),
+            numeration_raw: some("19, 1264 (1967)"),
+            status: some("in press"),
+            note: some("erratum"),
+            doi: some("10.1103/PhysRevLett.19.1264"),
+            short_doi: some("10/bxfr"),
+            doi_prefix: some("10.1103"),
+            handle: some("2078.1/123456"),
+            arxiv_id: some("1001.0785"),
+            texkey: some("Weinberg:1967tq"),
+            isbn: some("978-0-201-50397-5"),
+            report_number: some("CERN-TH-2019-001"),
+            report_numbers: vec!["CERN-TH-2019-001".into(), "SLAC-PUB-8587".into()],
+            url: some("https://example.org"),
+            collaboration: some("ATLAS Collaboration"),
+            ref_type: RefType::Journal,
+            publisher: some("World Scientific"),
+            confidence: 0.9,
+            source: ReferenceSource::ReferenceSection,
+            found_on_page: 12,
+            cited_on_pages: vec![2, 5],
+            section: 1,
+            warnings: vec!["volume looks like article number".into()],
+        }
+    }
+
+    fn check(schema: &Value, value: &Value) -> Result<(), String> {
+        if let Some(allowed) = schema["enum"].as_array() {
+            return if allowed.contains(value) { Ok(()) } else { Err(format!("{value} not in enum")) };
+        }
+        match schema["type"].as_str() {
+            Some("string") if value.is_string() => Ok(()),
+            Some("number") if value.is_number() => Ok(()),
+            Some("integer") if value.is_u64() => Ok(()),
+            Some("boolean") if value.is_boolean() => Ok(()),
+            Some("array") => value
+                .as_array()
+                .ok_or_else(|| format!("{value} is not an array"))?
+                .iter()
+                .try_for_each(|item| check(&schema["items"], item)),
+            Some("object") => {
+                let object = value.as_object().ok_or_else(|| format!("{value} is not an object"))?;
+                for key in schema["required"].as_array().unwrap() {
+                    if !object.contains_key(key.as_str().unwrap()) {
+                        return Err(format!("missing required {key}"));
+                    }
+                }
+                object.iter().try_for_each(|(key, v)| {
+                    let property = schema["properties"]
+                        .get(key)
+                        .ok_or_else(|| format!("{key} not in schema"))?;
+                    check(property, v).map_err(|e| format!("{key}: {e}"))
+                })
+            }
+            other => Err(format!("{value} does not match type {other:?}")),
+        }
+    }
+
+    #[test]
+    fn full_output_matches_schema() {
+        let schema = parsed_reference_schema();
+        let value = serde_json::to_value(full_reference()).unwrap();
+        check(&schema, &value).unwrap();
+        // And the other way: no schema property that serde never emits.
+        let emitted = value.as_object().unwrap().len();
+        assert_eq!(schema["properties"].as_object().unwrap().len(), emitted);
+    }
+
+    #[test]
+    fn parsed_output_matches_schema() {
+        let refs = crate::parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), arXiv:1001.0785".to_string(),
+            "Introduction".to_string(),
+        ]);
+        let schema = reference_list_schema();
+        let value = serde_json::to_value(&refs).unwrap();
+        check(&schema["$defs"]["ParsedReference"], &value[0]).unwrap();
+        check(&schema["$defs"]["ParsedReference"], &value[1]).unwrap();
+    }
+
+    #[test]
+    fn enum_values_match_serde() {
+        let types = [
+            RefType::Journal,
+            RefType::Book,
+            RefType::Proceedings,
+            RefType::Thesis,
+            RefType::Preprint,
+            RefType::Unknown,
+        ];
+        let names: Vec<Value> = types.iter().map(|t| serde_json::to_value(t).unwrap()).collect();
+        assert_eq!(names, REF_TYPES.iter().map(|s| json!(s)).collect::<Vec<_>>());
+        let sources = [ReferenceSource::ReferenceSection, ReferenceSource::Footnote, ReferenceSource::Text];
+        let names: Vec<Value> = sources.iter().map(|s| serde_json::to_value(s).unwrap()).collect();
+        assert_eq!(names, SOURCES.iter().map(|s| json!(s)).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,125 @@
+//! Per-run summary counts for `--stats`.
+
+use std::fmt;
+
+use crate::types::{ParsedReference, ReferenceSource};
+use crate::SequenceIssue;
+
+/// Reference counts for one document, or summed over a batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub documents: usize,
+    pub references: usize,
+    /// References with a DOI straight from the text, before DOI resolution.
+    pub doi_before_lookup: usize,
+    pub doi: usize,
+    pub arxiv: usize,
+    /// References with a recognized journal title.
+    pub journal: usize,
+    pub reference_section: usize,
+    pub footnote: usize,
+    pub text: usize,
+    /// Duplicate or missing reference markers (`check_marker_sequence`).
+    pub marker_issues: Vec<SequenceIssue>,
+}
+
+/// Count one document's references. `doi_before_lookup` equals `doi`;
+/// callers that run DOI enrichment afterwards update `doi`, and those
+/// that check the numbering set `marker_issues`.
+pub fn summarize(refs: &[ParsedReference]) -> Stats {
+    let count = |pred: fn(&ParsedReference) -> bool| refs.iter().filter(|r| pred(r)).count();
+    let doi = count(|r| r.doi.is_some());
+    Stats {
+        documents: 1,
+        references: refs.len(),
+        doi_before_lookup: doi,
+        doi,
+        arxiv: count(|r| r.arxiv_id.is_some()),
+        journal: count(|r| r.journal_title.is_some()),
+        reference_section: count(|r| r.source == ReferenceSource::ReferenceSection),
+        footnote: count(|r| r.source == ReferenceSource::Footnote),
+        text: count(|r| r.source == ReferenceSource::Text),
+        marker_issues: Vec::new(),
+    }
+}
+
+impl Stats {
+    /// Add another document's counts to this one.
+    pub fn add(&mut self, other: &Stats) {
+        self.documents += other.documents;
+        self.references += other.references;
+        self.doi_before_lookup += other.doi_before_lookup;
+        self.doi += other.doi;
+        self.arxiv += other.arxiv;
+        self.journal += other.journal;
+        self.reference_section += other.reference_section;
+        self.footnote += other.footnote;
+        self.text += other.text;
+        self.marker_issues.extend(other.marker_issues.iter().cloned());
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.documents == 1 { "" } else { "s" };
+        writeln!(f, "{} references in {} document{plural}", self.references, self.documents)?;
+        writeln!(f, "  DOI:     {} ({} before lookup)", self.doi, self.doi_before_lookup)?;
+        writeln!(f, "  arXiv:   {}", self.arxiv)?;
+        writeln!(f, "  journal: {}", self.journal)?;
+        write!(
+            f,
+            "  source:  {} reference section, {} footnote",
+            self.reference_section, self.footnote
+        )?;
+        if self.text > 0 {
+            write!(f, ", {} text", self.text)?;
+        }
+        if !self.marker_issues.is_empty() {
+            write!(f, "\n  numbering issues: {}", self.marker_issues.len())?;
+            for issue in &self.marker_issues {
+                write!(f, "\n    {issue}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_counts_identifiers_and_sources() {
+        let mut refs = crate::parse_reference_text(&[
+            "[1] A. Author, Phys. Rev. D 72, 052002 (2005), arXiv:1001.0785".to_string(),
+            "[2] B. Other, doi:10.1103/PhysRevD.72.052002".to_string(),
+            "[3] C. Third, Nucl. Phys. B 417, 181 (1994)".to_string(),
+        ]);
+        refs[2].source = ReferenceSource::Footnote;
+        let stats = summarize(&refs);
+        assert_eq!(
+            stats,
+            Stats {
+                documents: 1,
+                references: 3,
+                doi_before_lookup: 1,
+                doi: 1,
+                arxiv: 1,
+                journal: 2,
+                reference_section: 0,
+                footnote: 1,
+                text: 2,
+                marker_issues: Vec::new(),
+            }
+        );
+
+        let mut total = Stats::default();
+        total.add(&stats);
+        total.add(&stats);
+        assert_eq!(total.references, 6);
+        assert!(total.to_string().starts_with("6 references in 2 documents\n"));
+
+        total.marker_issues.push(SequenceIssue::Missing { section: 0, from: 4, to: 4 });
+        assert!(total.to_string().ends_with("\n  numbering issues: 1\n    marker [4] is missing"));
+    }
+}
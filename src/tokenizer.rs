@@ -1,17 +1,42 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::kb;
+use crate::{kb, markers};
 use crate::types::{Token, TokenKind};
 
 static DOI_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"10\.\d{4,}/[^\s,;]+").unwrap());
 
+/// Resolver prefix directly before a DOI: "https://doi.org/", "http://dx.doi.org/".
+static DOI_URL_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"https?://(?:dx\.)?doi\.org/$").unwrap());
+
+/// shortDOI, "10/abcde". Only read after "doi:" or a doi.org resolver
+/// (`SHORT_DOI_PREFIX_RE`), since "10/5" alone is a fraction.
+static SHORT_DOI_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)\b10/[a-z0-9]+\b").unwrap());
+
+static SHORT_DOI_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)(?:\bdoi:\s?|https?://(?:dx\.)?doi\.org/)$").unwrap());
+
+/// Handle, as "hdl:2078.1/123456" or a hdl.handle.net URL; group 1 is the
+/// handle ("prefix/suffix", the prefix made of digits and dots).
+static HDL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:\bhdl:\s?|https?://hdl\.handle\.net/)(\d[\d.]*/[^\s,;]+)").unwrap()
+});
+
 static ARXIV_NEW_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\d{4}\.\d{4,5}(?:v\d+)?").unwrap());
 
+/// Archives of old-style arXiv IDs, as a regex alternation.
+pub(crate) const ARXIV_OLD_ARCHIVES: &str = "hep|astro|cond|gr|math|nucl|physics|quant|cs|nlin|q-bio|q-fin|q-alg|alg-geom|solv-int|chao-dyn|adap-org|comp-gas|patt-sol|funct-an|dg-ga|mtrl-th|supr-con|acc-phys|ao-sci|bayes-an|chem-ph|plasm-ph|atom-ph|stat";
+
+/// Old-style arXiv ID: "hep-ph/0202058", "hep ph 0202058", and the
+/// category.subcategory form "math.AG/0202058".
 static ARXIV_OLD_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?:hep|astro|cond|gr|math|nucl|physics|quant|cs|nlin|q-bio|q-fin|q-alg|alg-geom|solv-int|chao-dyn|adap-org|comp-gas|patt-sol|funct-an|dg-ga|mtrl-th|supr-con|acc-phys|ao-sci|bayes-an|chem-ph|plasm-ph|atom-ph|stat)(?:[\s.\-][a-z]{2,4})?[\s/]+\d{7}(?:v\d+)?").unwrap()
+    Regex::new(&format!(
+        r"(?:{ARXIV_OLD_ARCHIVES})(?:[\s.\-][a-z]{{2,4}}|\.[A-Z]{{2}})?[\s/]+\d{{7}}(?:v\d+)?"
+    ))
+    .unwrap()
 });
 
 /// Matches bare arXiv format: "arXiv:0510213 [hep-ph]" — 7-digit number with bracketed category
@@ -19,6 +44,11 @@ static ARXIV_BARE_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)arXiv:(\d{7})\s*\[([a-z-]+(?:\.[a-zA-Z-]+)?)\]").unwrap()
 });
 
+/// Scheme written before an arXiv ID: "arXiv:", "arXiv: ", "e-Print:",
+/// "e-Print: arXiv:".
+static ARXIV_SCHEME_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(?:e-?print\s*:?\s*)?(?:arxiv\s*:?\s*)?$").unwrap());
+
 static URL_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"https?://[^\s,;]+").unwrap());
 
@@ -30,11 +60,23 @@ static ARXIV_URL_RE: Lazy<Regex> = Lazy::new(|| {
 static ISBN_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?:978|979)[-\s]?\d[-\s]?\d{2,5}[-\s]?\d{2,5}[-\s]?\d").unwrap());
 
+/// INSPIRE/SPIRES texkey ("Weinberg:1967tq"). The 2-3 letter suffix is
+/// what keeps ordinary "Section:1" or "Author:2001" fragments out. Name
+/// particles may come first in lowercase ("tHooft:1971rn", "deBoer:..."),
+/// but the name needs an uppercase letter.
+static TEXKEY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b[a-z]*[A-Z][A-Za-z]*:\d{4}[a-z]{2,3}\b").unwrap());
+
+/// Latest year accepted as a publication year. Next year is allowed so
+/// references dated ahead of the calendar still count as years.
+static MAX_YEAR: Lazy<u32> = Lazy::new(|| current_year() + 1);
+
+/// Up to two closing parens: "(1957))" ends a parenthesized translation.
 static YEAR_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^\(?((?:19|20)\d{2})[a-z]?\)?$").unwrap());
+    Lazy::new(|| Regex::new(r"^\(?((?:19|20)\d{2})[a-z]?\){0,2}$").unwrap());
 
 static PAGE_RANGE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\d+\s*[-–—]\s*\d+").unwrap());
+    Lazy::new(|| Regex::new(r"\d+\s*(?:--|[-–—])\s*\d+").unwrap());
 
 static NUMBER_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\d+").unwrap());
@@ -51,7 +93,7 @@ static VOLUME_COLON_PAGE_RE: Lazy<Regex> =
 
 /// Volume(issue):page: "72(2):1346–1349" or "23(21):1704–1706"
 static VOLUME_ISSUE_COLON_PAGE_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"^(\d+)\(\d+(?:[-–—]\d+)?\):([A-Za-z]?\d+(?:\s*[-–—]\s*[A-Za-z]?\d+)?)$").unwrap()
+    Regex::new(r"^(\d+)\((\d+(?:[-–—]\d+)?)\):([A-Za-z]?\d+(?:\s*[-–—]\s*[A-Za-z]?\d+)?)$").unwrap()
 });
 
 /// Compact volume(year) without page: "301(1993)"
@@ -62,9 +104,9 @@ static VOLUME_YEAR_RE: Lazy<Regex> =
 static YEAR_ISSUE_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^((?:19|20)\d{2})\((\d{1,2})\)$").unwrap());
 
-/// Volume with issue number: "82(25)" or "82(2-3)" — extract volume, discard issue
+/// Volume with issue number: "82(25)" or "82(2-3)" — extract volume and issue
 static VOLUME_ISSUE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(\d+)\(\d+(?:[-–—]\d+)?\)$").unwrap());
+    Lazy::new(|| Regex::new(r"^(\d+)\((\d+(?:[-–—]\d+)?)\)$").unwrap());
 
 /// Article number with letter suffix: "111301(R)", "040404/1" — extract digits
 static ARTICLE_NUMBER_RE: Lazy<Regex> =
@@ -73,12 +115,46 @@ static ARTICLE_NUMBER_RE: Lazy<Regex> =
 static LINE_MARKER_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^\s*(?:\[(\d+)\]|\((\d+)\)|(\d+)[.\)])\s*").unwrap());
 
+/// Hyphen look-alikes PDFs emit where an ASCII "-" is meant: hyphen,
+/// non-breaking hyphen, figure dash, minus sign, small and fullwidth
+/// hyphen-minus.
+const HYPHEN_VARIANTS: &[char] = &['\u{2010}', '\u{2011}', '\u{2012}', '\u{2212}', '\u{fe63}', '\u{ff0d}'];
+
+/// Replace hyphen look-alikes with ASCII "-" so identifier patterns match
+/// ("hep\u{2011}ph/0202058"). An en dash becomes "-" only between two
+/// letters ("hep–ph"); between numbers it marks a page range and stays.
+/// Run on reference text before tokenizing, since token spans are byte
+/// offsets into it.
+pub fn normalize_dashes(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let letter = |i: Option<usize>| i.and_then(|i| chars.get(i)).is_some_and(|c| c.is_ascii_alphabetic());
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| match c {
+            _ if HYPHEN_VARIANTS.contains(&c) => '-',
+            '–' if letter(i.checked_sub(1)) && letter(Some(i + 1)) => '-',
+            c => c,
+        })
+        .collect()
+}
+
 /// Tokenize a reference string into a sequence of typed tokens.
+/// Every token records the byte span of `text` it came from.
+///
+/// ```
+/// use refextract::types::TokenKind;
+///
+/// let tokens = refextract::tokenizer::tokenize("Phys. Rev. D 72, 052002 (2005)");
+/// let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+/// assert_eq!(kinds, [TokenKind::JournalName, TokenKind::Number, TokenKind::Number, TokenKind::Year]);
+/// assert_eq!(tokens[0].normalized.as_deref(), Some("Phys. Rev. D"));
+/// ```
 pub fn tokenize(text: &str) -> Vec<Token> {
     let mut tokens = Vec::new();
     let work = strip_line_marker(text, &mut tokens);
     let spans = find_identifier_spans(work);
-    fill_tokens(work, &spans, &mut tokens);
+    fill_tokens(work, text.len() - work.len(), &spans, &mut tokens);
     tokens
 }
 
@@ -90,12 +166,14 @@ fn strip_line_marker<'a>(text: &'a str, tokens: &mut Vec<Token>) -> &'a str {
             .or_else(|| caps.get(3))
             .map(|m| m.as_str().to_string())
             .unwrap_or_default();
+        let end = caps.get(0).unwrap().end();
         tokens.push(Token {
             kind: TokenKind::LineMarker,
             text: marker,
             normalized: None,
+            start: 0,
+            end,
         });
-        let end = caps.get(0).unwrap().end();
         return &text[end..];
     }
     text
@@ -111,14 +189,18 @@ struct Span {
 
 fn find_identifier_spans(text: &str) -> Vec<Span> {
     let mut spans = Vec::new();
+    add_handle_spans(&mut spans, text);
     add_doi_spans(&mut spans, text);
-    add_regex_spans(&mut spans, text, &URL_RE, TokenKind::Url);
+    add_url_spans(&mut spans, text);
     convert_arxiv_url_spans(&mut spans);
     add_arxiv_old_spans(&mut spans, text);
     add_arxiv_bare_spans(&mut spans, text);
     add_regex_spans(&mut spans, text, &ARXIV_NEW_RE, TokenKind::ArxivId);
+    extend_arxiv_over_scheme(&mut spans, text);
     add_regex_spans(&mut spans, text, &ISBN_RE, TokenKind::Isbn);
     add_report_number_spans(&mut spans, text);
+    add_regex_spans(&mut spans, text, &TEXKEY_RE, TokenKind::Texkey);
+    add_collaboration_spans(&mut spans, text);
     add_journal_name_spans(&mut spans, text);
     spans.sort_by_key(|s| s.start);
     remove_overlapping_spans(&mut spans);
@@ -153,13 +235,65 @@ fn convert_arxiv_url_spans(spans: &mut [Span]) {
     }
 }
 
+/// Grow arXiv spans back over a preceding "arXiv:" / "e-Print:" so the
+/// scheme isn't left behind as a stray word. The token text stays the bare ID.
+fn extend_arxiv_over_scheme(spans: &mut [Span], text: &str) {
+    for span in spans.iter_mut().filter(|s| s.kind == TokenKind::ArxivId) {
+        if let Some(scheme) = ARXIV_SCHEME_RE.find(&text[..span.start])
+            && !scheme.is_empty()
+        {
+            span.start = scheme.start();
+        }
+    }
+}
+
+/// Add handle spans. Like doi.org URLs, a hdl.handle.net URL is one Hdl
+/// span rather than a Url, with the bare handle as its text.
+fn add_handle_spans(spans: &mut Vec<Span>, text: &str) {
+    for caps in HDL_RE.captures_iter(text) {
+        let (m, handle) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+        let handle = handle.as_str().trim_end_matches(|c: char| ".)]}>".contains(c));
+        let end = caps.get(1).unwrap().start() + handle.len();
+        if !overlaps_existing(spans, m.start(), end) {
+            spans.push(Span {
+                start: m.start(),
+                end,
+                kind: TokenKind::Hdl,
+                text: handle.to_string(),
+                normalized: None,
+            });
+        }
+    }
+}
+
+/// Add DOI spans. A doi.org URL becomes a single Doi span covering the
+/// whole URL, with the bare DOI as its text. shortDOIs are lowercased,
+/// as shortdoi.org issues them.
 fn add_doi_spans(spans: &mut Vec<Span>, text: &str) {
+    for m in SHORT_DOI_RE.find_iter(text) {
+        let Some(prefix) = SHORT_DOI_PREFIX_RE.find(&text[..m.start()]) else {
+            continue;
+        };
+        let start = if prefix.as_str().contains("://") { prefix.start() } else { m.start() };
+        if !overlaps_existing(spans, start, m.end()) {
+            spans.push(Span {
+                start,
+                end: m.end(),
+                kind: TokenKind::Doi,
+                text: m.as_str().to_lowercase(),
+                normalized: None,
+            });
+        }
+    }
     for m in DOI_RE.find_iter(text) {
         let matched = m.as_str().trim_end_matches(|c: char| ".)]}>".contains(c));
+        let start = DOI_URL_PREFIX_RE
+            .find(&text[..m.start()])
+            .map_or(m.start(), |prefix| prefix.start());
         let end = m.start() + matched.len();
-        if !overlaps_existing(spans, m.start(), end) {
+        if !overlaps_existing(spans, start, end) {
             spans.push(Span {
-                start: m.start(),
+                start,
                 end,
                 kind: TokenKind::Doi,
                 text: matched.to_string(),
@@ -208,16 +342,20 @@ fn add_arxiv_bare_spans(spans: &mut Vec<Span>, text: &str) {
 }
 
 /// Normalize old-style arXiv ID: "hep ph/0202058" → "hep-ph/0202058"
-/// Also handles dot separators: "math.dg/0412256" → "math-dg/0412256"
+/// Also handles dot separators: "math.dg/0412256" → "math-dg/0412256",
+/// keeps an uppercase subcategory ("math.AG/0202058") and restores the
+/// slash when a space stands in for it: "hep-ph 0202058" → "hep-ph/0202058".
 fn normalize_arxiv_old(raw: &str) -> String {
     let mut result = String::new();
     let mut chars = raw.chars().peekable();
     while let Some(c) = chars.next() {
-        if c == ' ' || c == '\t' || c == '.' {
-            if chars.peek().is_some_and(|&next| next.is_ascii_alphabetic()) {
-                result.push('-');
+        if c == ' ' || c == '\t' || c == '.' || c == '/' {
+            match chars.peek() {
+                Some(&next) if c == '.' && next.is_ascii_uppercase() => result.push('.'),
+                Some(&next) if c != '/' && next.is_ascii_alphabetic() => result.push('-'),
+                Some(&next) if next.is_ascii_digit() && !result.ends_with('/') => result.push('/'),
+                _ => {} // Space/dot before slash — skip
             }
-            // Space/dot before slash or digits — skip
         } else {
             result.push(c);
         }
@@ -225,6 +363,47 @@ fn normalize_arxiv_old(raw: &str) -> String {
     result
 }
 
+/// Add URL spans without the punctuation of the sentence around them:
+/// "(see https://a.org/x)." keeps "https://a.org/x". A closing bracket
+/// stays when the URL opened it ("https://en.wikipedia.org/wiki/W_(boson)").
+fn add_url_spans(spans: &mut Vec<Span>, text: &str) {
+    for m in URL_RE.find_iter(text) {
+        let url = trim_url(m.as_str());
+        let end = m.start() + url.len();
+        if !overlaps_existing(spans, m.start(), end) {
+            spans.push(Span {
+                start: m.start(),
+                end,
+                kind: TokenKind::Url,
+                text: url.to_string(),
+                normalized: None,
+            });
+        }
+    }
+}
+
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    while let Some(last) = url.chars().last() {
+        let open = match last {
+            ')' => '(',
+            ']' => '[',
+            '}' => '{',
+            '>' => '<',
+            '.' | ':' | '!' | '?' | '\'' | '"' => {
+                url = &url[..url.len() - 1];
+                continue;
+            }
+            _ => break,
+        };
+        if url.matches(open).count() >= url.matches(last).count() {
+            break;
+        }
+        url = &url[..url.len() - 1];
+    }
+    url
+}
+
 fn add_regex_spans(
     spans: &mut Vec<Span>,
     text: &str,
@@ -245,17 +424,37 @@ fn add_regex_spans(
 }
 
 fn add_report_number_spans(spans: &mut Vec<Span>, text: &str) {
-    if let Some((matched, standardized)) = kb::match_report_number(text)
-        && let Some(pos) = text.find(&matched)
-            && !overlaps_existing(spans, pos, pos + matched.len()) {
-                spans.push(Span {
-                    start: pos,
-                    end: pos + matched.len(),
-                    kind: TokenKind::ReportNumber,
-                    text: matched,
-                    normalized: Some(standardized),
-                });
-            }
+    for m in kb::match_report_numbers(text) {
+        let end = m.start + m.matched.len();
+        if !overlaps_existing(spans, m.start, end) {
+            spans.push(Span {
+                start: m.start,
+                end,
+                kind: TokenKind::ReportNumber,
+                text: m.matched,
+                normalized: Some(m.number),
+            });
+        }
+    }
+}
+
+/// Add collaboration spans from the baked-in collaboration KB, ahead of
+/// journal names so "LSST Science Collaboration" isn't read as Science.
+fn add_collaboration_spans(spans: &mut Vec<Span>, text: &str) {
+    let mut pos = 0;
+    while let Some((start, end, name)) = kb::COLLABORATIONS.find(&text[pos..]) {
+        let (start, end) = (pos + start, pos + end);
+        if !overlaps_existing(spans, start, end) {
+            spans.push(Span {
+                start,
+                end,
+                kind: TokenKind::Collaboration,
+                text: text[start..end].to_string(),
+                normalized: Some(name.to_string()),
+            });
+        }
+        pos = end;
+    }
 }
 
 fn add_journal_name_spans(spans: &mut Vec<Span>, text: &str) {
@@ -328,6 +527,8 @@ fn embedded_in_compound_name(text: &str, pos: usize, len: usize) -> bool {
 /// Extend a journal match to include a section letter if present.
 /// "Phys. Rev." + " D31" → "Phys. Rev. D" (volume "31" becomes a separate token).
 /// "Nucl. Phys." + " B253" → "Nucl. Phys. B"
+/// "Eur. Phys. J." + " C 74" → "Eur. Phys. J. C" (digits stay for the volume).
+/// A letter followed by a period ("A. Author") is an initial, not a section.
 fn extend_section_letter(
     text: &str,
     pos: usize,
@@ -355,6 +556,14 @@ fn extend_section_letter(
         let new_abbrev = format!("{} {}", abbrev, letter);
         return (new_len, new_abbrev);
     }
+    // Single uppercase letter, spaces, then a digit
+    if i < remaining.len() && remaining[i].is_ascii_uppercase() {
+        let digits_at = i + 1 + remaining[i + 1..].iter().take_while(|&&b| b == b' ').count();
+        if digits_at > i + 1 && remaining.get(digits_at).is_some_and(u8::is_ascii_digit) {
+            let letter = remaining[i] as char;
+            return (len + i + 1, format!("{abbrev} {letter}"));
+        }
+    }
     (len, abbrev)
 }
 
@@ -415,45 +624,116 @@ fn remove_overlapping_spans(spans: &mut Vec<Span>) {
 }
 
 /// Fill tokens between identifier spans with classified remaining text.
-fn fill_tokens(text: &str, spans: &[Span], tokens: &mut Vec<Token>) {
+/// `base` is the offset of `text` within the string being tokenized.
+fn fill_tokens(text: &str, base: usize, spans: &[Span], tokens: &mut Vec<Token>) {
     let mut pos = 0;
     for span in spans {
         if pos < span.start {
-            classify_gap(&text[pos..span.start], tokens);
+            classify_gap(&text[pos..span.start], base + pos, tokens);
         }
         tokens.push(Token {
             kind: span.kind.clone(),
             text: span.text.clone(),
             normalized: span.normalized.clone(),
+            start: base + span.start,
+            end: base + span.end,
         });
         pos = span.end;
     }
     if pos < text.len() {
-        classify_gap(&text[pos..], tokens);
+        classify_gap(&text[pos..], base + pos, tokens);
     }
 }
 
 /// Classify remaining text fragments into words, years, numbers, etc.
-fn classify_gap(text: &str, tokens: &mut Vec<Token>) {
-    let words: Vec<&str> = text.split_whitespace().collect();
+/// Tokens split out of one word ("417(1994)181") all get that word's span.
+fn classify_gap(text: &str, base: usize, tokens: &mut Vec<Token>) {
+    let words: Vec<(usize, &str)> = text
+        .split_whitespace()
+        .map(|w| (w.as_ptr() as usize - text.as_ptr() as usize, w))
+        .collect();
     let mut i = 0;
     while i < words.len() {
+        let first = tokens.len();
+        let (start, word) = words[i];
+        // Spaced page range: "195" + "-" + "373" → "195-373"
+        let spaced_dash = i + 2 < words.len()
+            && word.bytes().all(|b| b.is_ascii_digit())
+            && matches!(words[i + 1].1, "-" | "--" | "–" | "—")
+            && words[i + 2].1.as_bytes().first().is_some_and(|b| b.is_ascii_digit());
         // Re-join broken page ranges: "1547–" + "1553" → "1547–1553"
         // Common in two-column PDFs where "179:1547– 1553" spans a line break
-        if i + 1 < words.len()
-            && ends_with_dash(words[i])
-            && words[i + 1].as_bytes().first().is_some_and(|b| b.is_ascii_digit())
+        let end = if spaced_dash {
+            let dash = words[i + 1].1;
+            let (next_start, next) = words[i + 2];
+            classify_word(&format!("{word}{dash}{next}"), tokens);
+            i += 3;
+            next_start + next.len()
+        } else if i + 1 < words.len()
+            && is_month(word)
+            && is_year_word(words[i + 1].1)
+            && is_date(i.checked_sub(1).map(|p| words[p].1), word, words[i + 1].1)
         {
-            let joined = format!("{}{}", words[i], words[i + 1]);
+            // "(March 2005)", "Jan. 2005" → one year token, month dropped
+            let (next_start, next) = words[i + 1];
+            classify_word(next, tokens);
+            tokens.last_mut().unwrap().text = text[start..next_start + next.len()].to_string();
+            i += 2;
+            next_start + next.len()
+        } else if i + 1 < words.len()
+            && ends_with_dash(word)
+            && words[i + 1].1.as_bytes().first().is_some_and(|b| b.is_ascii_digit())
+        {
+            let (next_start, next) = words[i + 1];
+            let joined = format!("{word}{next}");
             classify_word(&joined, tokens);
             i += 2;
+            next_start + next.len()
         } else {
-            classify_word(words[i], tokens);
+            classify_word(word, tokens);
             i += 1;
+            start + word.len()
+        };
+        for token in &mut tokens[first..] {
+            token.start = base + start;
+            token.end = base + end;
         }
     }
 }
 
+/// English month name, full or abbreviated: "March", "(Mar.", "Sept.".
+fn is_month(word: &str) -> bool {
+    const MONTHS: &[&str] = &[
+        "january", "february", "march", "april", "may", "june", "july", "august", "september",
+        "october", "november", "december",
+    ];
+    let name = word.trim_start_matches('(');
+    let name = name.strip_suffix('.').unwrap_or(name);
+    let lower = name.to_lowercase();
+    name.starts_with(|c: char| c.is_uppercase())
+        && (MONTHS.contains(&lower.as_str())
+            || lower == "sept"
+            || (lower.len() == 3 && MONTHS.iter().any(|m| m.starts_with(&lower))))
+}
+
+/// Whether `month` and `year` after `prev` are a date rather than a surname
+/// and a year: "R. May 2005" is an author, "Phys. Rev. D 72, 1 (March
+/// 2005)" a date. After an initial or an author-list "and" the month is a
+/// name, unless brackets mark the pair as a date.
+fn is_date(prev: Option<&str>, month: &str, year: &str) -> bool {
+    let in_author_list = prev.is_some_and(|p| {
+        markers::is_initial_token(p.trim_end_matches(',')) || matches!(p, "and" | "&")
+    });
+    !in_author_list || month.starts_with('(') || year.trim_end_matches([',', '.', ';']).ends_with(')')
+}
+
+fn is_year_word(word: &str) -> bool {
+    let clean = word.trim_matches(|c: char| matches!(c, ',' | '.' | ';' | ':'));
+    YEAR_RE
+        .captures(clean)
+        .is_some_and(|caps| is_plausible_year(caps[1].parse().unwrap_or(0)))
+}
+
 fn ends_with_dash(word: &str) -> bool {
     let trimmed = word.trim_end_matches([',', '.', ';', ':']);
     trimmed.ends_with('-') || trimmed.ends_with('–') || trimmed.ends_with('—')
@@ -477,6 +757,8 @@ fn try_compound_numeration(clean: &str, tokens: &mut Vec<Token>) -> bool {
                 kind: TokenKind::Word,
                 text: letter.as_str().to_string(),
                 normalized: None,
+                start: 0,
+                end: 0,
             });
         }
         push_page_or_number(tokens, &caps[3]);
@@ -488,10 +770,11 @@ fn try_compound_numeration(clean: &str, tokens: &mut Vec<Token>) -> bool {
         push_year(tokens, &caps[2]);
         return true;
     }
-    // Volume(issue):page: "72(2):1346–1349" → volume + page
+    // Volume(issue):page: "72(2):1346–1349" → volume + issue + page
     if let Some(caps) = VOLUME_ISSUE_COLON_PAGE_RE.captures(clean) {
         push_number(tokens, &caps[1]);
-        push_page_or_number(tokens, &caps[2]);
+        push_issue(tokens, &caps[2]);
+        push_page_or_number(tokens, &caps[3]);
         return true;
     }
     // Year with issue: "2007(12)" → emit year + issue number (JCAP/JHEP format)
@@ -500,9 +783,10 @@ fn try_compound_numeration(clean: &str, tokens: &mut Vec<Token>) -> bool {
         push_number(tokens, &caps[2]);
         return true;
     }
-    // Volume with issue number: "82(25)" → emit volume only
+    // Volume with issue number: "82(25)" → volume + issue
     if let Some(caps) = VOLUME_ISSUE_RE.captures(clean) {
         push_number(tokens, &caps[1]);
+        push_issue(tokens, &caps[2]);
         return true;
     }
     // Article number with suffix: "111301(R)", "040404/1" → emit digits
@@ -529,33 +813,58 @@ fn classify_word(word: &str, tokens: &mut Vec<Token>) {
         || clean_lower.ends_with(":ibid")
         || clean_lower.ends_with(":ibid.")
     {
-        tokens.push(Token { kind: TokenKind::Ibid, text: word.to_string(), normalized: None });
+        tokens.push(Token { kind: TokenKind::Ibid, text: word.to_string(), normalized: None, start: 0, end: 0 });
+        return;
+    }
+    if is_editor_marker(word, tokens.last()) {
+        tokens.push(Token { kind: TokenKind::Editor, text: word.to_string(), normalized: None, start: 0, end: 0 });
         return;
     }
     if is_punctuation(word) {
-        tokens.push(Token { kind: TokenKind::Punctuation, text: word.to_string(), normalized: None });
+        tokens.push(Token { kind: TokenKind::Punctuation, text: word.to_string(), normalized: None, start: 0, end: 0 });
         return;
     }
     if let Some(caps) = YEAR_RE.captures(clean) {
         let year: u32 = caps[1].parse().unwrap_or(0);
-        if (1900..=2030).contains(&year) {
-            tokens.push(Token { kind: TokenKind::Year, text: word.to_string(), normalized: Some(caps[1].to_string()) });
+        if is_plausible_year(year) {
+            tokens.push(Token { kind: TokenKind::Year, text: word.to_string(), normalized: Some(caps[1].to_string()), start: 0, end: 0 });
             return;
         }
     }
     if PAGE_RANGE_RE.is_match(clean) {
-        tokens.push(Token { kind: TokenKind::PageRange, text: word.to_string(), normalized: None });
+        tokens.push(Token { kind: TokenKind::PageRange, text: word.to_string(), normalized: None, start: 0, end: 0 });
         return;
     }
     if NUMBER_RE.is_match(clean) && clean.chars().all(|c| c.is_ascii_digit()) {
-        tokens.push(Token { kind: TokenKind::Number, text: word.to_string(), normalized: None });
+        tokens.push(Token { kind: TokenKind::Number, text: word.to_string(), normalized: None, start: 0, end: 0 });
         return;
     }
-    if let Some(collab) = kb::match_collaboration(clean) {
-        tokens.push(Token { kind: TokenKind::Collaboration, text: word.to_string(), normalized: Some(collab) });
-        return;
-    }
-    tokens.push(Token { kind: TokenKind::Word, text: word.to_string(), normalized: None });
+    tokens.push(Token { kind: TokenKind::Word, text: word.to_string(), normalized: None, start: 0, end: 0 });
+}
+
+/// True for numbers between 1900 and next year.
+pub(crate) fn is_plausible_year(year: u32) -> bool {
+    (1900..=*MAX_YEAR).contains(&year)
+}
+
+/// Current UTC calendar year, from the system clock.
+fn current_year() -> u32 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    year_from_days((secs / 86_400) as i64)
+}
+
+/// Calendar year of a day count since 1970-01-01 (Hinnant's civil_from_days).
+fn year_from_days(days: i64) -> u32 {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let year = yoe + era * 400 + i64::from(mp >= 10);
+    year as u32
 }
 
 fn push_number(tokens: &mut Vec<Token>, num: &str) {
@@ -563,6 +872,18 @@ fn push_number(tokens: &mut Vec<Token>, num: &str) {
         kind: TokenKind::Number,
         text: num.to_string(),
         normalized: None,
+        start: 0,
+        end: 0,
+    });
+}
+
+fn push_issue(tokens: &mut Vec<Token>, issue: &str) {
+    tokens.push(Token {
+        kind: TokenKind::Issue,
+        text: issue.to_string(),
+        normalized: None,
+        start: 0,
+        end: 0,
     });
 }
 
@@ -571,6 +892,8 @@ fn push_year(tokens: &mut Vec<Token>, year: &str) {
         kind: TokenKind::Year,
         text: format!("({year})"),
         normalized: Some(year.to_string()),
+        start: 0,
+        end: 0,
     });
 }
 
@@ -584,10 +907,280 @@ fn push_page_or_number(tokens: &mut Vec<Token>, page: &str) {
         kind,
         text: page.to_string(),
         normalized: None,
+        start: 0,
+        end: 0,
     });
 }
 
+/// "ed.", "eds.", "(Eds.)" or "edited" (as in "edited by"). The abbreviated
+/// forms need their dot so the given name "Ed" isn't caught, and "2nd ed."
+/// is an edition, not an editor.
+fn is_editor_marker(word: &str, prev: Option<&Token>) -> bool {
+    let clean = word.trim_matches(|c: char| matches!(c, ',' | ';' | ':' | '(' | ')' | '[' | ']'));
+    if clean.eq_ignore_ascii_case("edited") {
+        return true;
+    }
+    if !matches!(clean, "ed." | "eds." | "Ed." | "Eds.") {
+        return false;
+    }
+    let is_edition = prev.is_some_and(|t| {
+        let p = t.text.trim_end_matches(',').to_ascii_lowercase();
+        p.len() > 2 && ["st", "nd", "rd", "th"].iter().any(|s| p.ends_with(s))
+            && p[..p.len() - 2].chars().all(|c| c.is_ascii_digit())
+    });
+    !is_edition
+}
+
 fn is_punctuation(word: &str) -> bool {
     let trimmed = word.trim();
     matches!(trimmed, "," | "." | ";" | ":" | "and" | "et" | "al." | "al" | "&" | "-" | "–" | "—")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_spans_reproduce_source_text() {
+        let raw_ref = "[3] J. Smith and A. Jones, Phys. Rev. D 72, 052002 (2005), doi:10.1103/PhysRevD.72.052002";
+        for token in tokenize(raw_ref) {
+            let source = &raw_ref[token.start..token.end];
+            match token.kind {
+                TokenKind::LineMarker => assert_eq!(source, "[3] "),
+                // Normalized identifiers keep their raw spelling in the source
+                TokenKind::JournalName | TokenKind::Doi => assert!(source.contains(&token.text)),
+                _ => assert_eq!(source, token.text),
+            }
+        }
+    }
+
+    fn journal_then_volume(text: &str) -> (String, String) {
+        let tokens = tokenize(text);
+        let jpos = tokens.iter().position(|t| t.kind == TokenKind::JournalName).unwrap();
+        let journal = tokens[jpos].normalized.clone().unwrap_or(tokens[jpos].text.clone());
+        (journal, tokens[jpos + 1].text.clone())
+    }
+
+    #[test]
+    fn spaced_section_letter_joins_journal() {
+        assert_eq!(journal_then_volume("Phys. Rev. D 72, 052002"), ("Phys. Rev. D".into(), "72,".into()));
+        assert_eq!(journal_then_volume("Eur. Phys. J. C 74, 3076"), ("Eur. Phys. J. C".into(), "74,".into()));
+        assert_eq!(journal_then_volume("Nucl. Phys. B 417 (1994) 181"), ("Nucl. Phys. B".into(), "417".into()));
+    }
+
+    #[test]
+    fn author_initial_is_not_a_section_letter() {
+        let (journal, next) = journal_then_volume("Phys. Rev. Lett. A. Author, 12 (1990) 1");
+        assert_eq!(journal, "Phys. Rev. Lett.");
+        assert_eq!(next, "A.");
+    }
+
+    #[test]
+    fn split_words_share_the_word_span() {
+        let raw_ref = "Nucl. Phys. B 417(1994)181, 1547– 1553";
+        let tokens = tokenize(raw_ref);
+        let compound: Vec<&Token> = tokens.iter().filter(|t| t.text == "(1994)").collect();
+        assert_eq!(compound.len(), 1);
+        assert_eq!(&raw_ref[compound[0].start..compound[0].end], "417(1994)181,");
+        let range = tokens.iter().find(|t| t.kind == TokenKind::PageRange).unwrap();
+        assert_eq!(&raw_ref[range.start..range.end], "1547– 1553");
+    }
+
+    #[test]
+    fn inspire_texkey() {
+        let tokens = tokenize("S. Weinberg, Phys. Rev. Lett. 19, 1264 (1967) [Weinberg:1967tq]");
+        let texkey = tokens.iter().find(|t| t.kind == TokenKind::Texkey).unwrap();
+        assert_eq!(texkey.text, "Weinberg:1967tq");
+        for (text, key) in [
+            ("G. 't Hooft, Nucl. Phys. B 35, 167 (1971) [tHooft:1971rn]", "tHooft:1971rn"),
+            ("J. de Boer et al., arXiv:hep-th/9807226 [deBoer:1998kjm]", "deBoer:1998kjm"),
+        ] {
+            let tokens = tokenize(text);
+            assert_eq!(tokens.iter().find(|t| t.kind == TokenKind::Texkey).map(|t| t.text.as_str()), Some(key));
+        }
+        for text in ["see Section:1 and Author:2001", "Weinberg:1967", "see note:2001ab"] {
+            assert!(tokenize(text).iter().all(|t| t.kind != TokenKind::Texkey), "{text}");
+        }
+    }
+
+    #[test]
+    fn year_window_tracks_the_calendar() {
+        assert_eq!(year_from_days(0), 1970);
+        assert_eq!(year_from_days(20_453), 2025);
+        assert_eq!(year_from_days(20_454), 2026);
+        let next = current_year() + 1;
+        assert!(is_plausible_year(next));
+        assert!(!is_plausible_year(next + 1));
+        assert!(!is_plausible_year(1899));
+        let text = format!("A. Author, Title ({next})");
+        assert_eq!(tokenize(&text).last().unwrap().kind, TokenKind::Year);
+    }
+
+    #[test]
+    fn doi_org_urls_are_dois() {
+        for (text, doi) in [
+            ("A. Author, https://doi.org/10.1103/PhysRevD.72.052002.", "10.1103/PhysRevD.72.052002"),
+            ("A. Author, http://dx.doi.org/10.1000/xyz", "10.1000/xyz"),
+        ] {
+            let tokens = tokenize(text);
+            let token = tokens.iter().find(|t| t.kind == TokenKind::Doi).unwrap();
+            assert_eq!(token.text, doi, "{text}");
+            assert!(text[token.start..token.end].starts_with("http"));
+            assert!(tokens.iter().all(|t| t.kind != TokenKind::Url && !t.text.contains("doi.org")));
+        }
+    }
+
+    #[test]
+    fn short_dois_after_a_doi_prefix() {
+        for (text, doi) in [
+            ("A. Author, Title, doi:10/abcde.", "10/abcde"),
+            ("A. Author, DOI: 10/BXFR", "10/bxfr"),
+            ("A. Author, http://dx.doi.org/10/bxfr", "10/bxfr"),
+        ] {
+            let tokens = tokenize(text);
+            let token = tokens.iter().find(|t| t.kind == TokenKind::Doi).expect(text);
+            assert_eq!(token.text, doi, "{text}");
+            assert!(tokens.iter().all(|t| t.kind != TokenKind::Url), "{text}");
+        }
+        let r = crate::parse_reference_text(&["A. Author, Title, doi:10/abcde.".to_string()]);
+        assert_eq!((r[0].doi.as_deref(), r[0].short_doi.as_deref()), (None, Some("10/abcde")));
+        // A fraction in prose is no DOI
+        assert!(tokenize("in 10/5 of the events").iter().all(|t| t.kind != TokenKind::Doi));
+    }
+
+    #[test]
+    fn collaboration_tokens() {
+        let text = "G. Aad et al. [ATLAS Collaboration], Phys. Lett. B 716, 1 (2012)";
+        let tokens = tokenize(text);
+        let token = tokens.iter().find(|t| t.kind == TokenKind::Collaboration).unwrap();
+        assert_eq!(token.text, "ATLAS Collaboration");
+        assert_eq!(token.normalized.as_deref(), Some("ATLAS Collaboration"));
+        assert_eq!(&text[token.start..token.end], "ATLAS Collaboration");
+        let r = crate::parse_reference_text(&[text.to_string()]);
+        assert_eq!(r[0].collaboration.as_deref(), Some("ATLAS Collaboration"));
+        assert_eq!(r[0].authors.as_deref(), Some("G. Aad"));
+        // Whole words only
+        assert!(tokenize("ECMS Collaboration").iter().all(|t| t.kind != TokenKind::Collaboration));
+    }
+
+    #[test]
+    fn month_is_folded_into_year() {
+        for text in ["(March 2005)", "Jan. 2005", "Sept. 2005,"] {
+            let tokens = tokenize(text);
+            assert_eq!(tokens.len(), 1, "{text}");
+            assert_eq!(tokens[0].kind, TokenKind::Year);
+            assert_eq!(tokens[0].normalized.as_deref(), Some("2005"));
+        }
+        // A surname that happens to be a month keeps its comma and stays a word
+        assert_eq!(tokenize("R. May, 2005")[1].kind, TokenKind::Word);
+        // ...and right before a year too, after an initial
+        let kinds: Vec<TokenKind> = tokenize("R. May 2005, Phys.").into_iter().map(|t| t.kind).collect();
+        assert_eq!(kinds[..3], [TokenKind::Word, TokenKind::Word, TokenKind::Year]);
+        assert_eq!(tokenize("A. Author and R. May (May 2005)").last().unwrap().kind, TokenKind::Year);
+        let r = crate::parse_one("R. May 2005, Phys. Rev. D 72, 1");
+        assert_eq!(r[0].authors.as_deref(), Some("R. May"));
+    }
+
+    #[test]
+    fn numeration_keywords_are_separate_words() {
+        let kinds: Vec<TokenKind> = tokenize("Journal, vol. 72, pp. 1-10 (2005)").into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [TokenKind::Word, TokenKind::Word, TokenKind::Number, TokenKind::Word, TokenKind::PageRange, TokenKind::Year]
+        );
+    }
+
+    #[test]
+    fn compact_volume_keeps_issue() {
+        let kinds = |text: &str| -> Vec<(TokenKind, String)> {
+            tokenize(text).into_iter().map(|t| (t.kind, t.text)).collect()
+        };
+        assert_eq!(
+            kinds("82(25)"),
+            vec![(TokenKind::Number, "82".into()), (TokenKind::Issue, "25".into())]
+        );
+        assert_eq!(
+            kinds("72(2):1346"),
+            vec![
+                (TokenKind::Number, "72".into()),
+                (TokenKind::Issue, "2".into()),
+                (TokenKind::Number, "1346".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn arxiv_scheme_is_part_of_the_id() {
+        for (text, id, scheme) in [
+            ("A. Author, arXiv:2101.12345", "2101.12345", "arXiv:2101.12345"),
+            ("A. Author, e-Print: 2101.12345", "2101.12345", "e-Print: 2101.12345"),
+            ("A. Author, arXiv: hep-ph/0202058", "hep-ph/0202058", "arXiv: hep-ph/0202058"),
+            ("A. Author, e-Print: arXiv:2101.12345 [hep-ph]", "2101.12345", "e-Print: arXiv:2101.12345"),
+        ] {
+            let tokens = tokenize(text);
+            assert_eq!(tokens.len(), 3 + text.contains('[') as usize, "{text}: {tokens:?}");
+            let arxiv = &tokens[2];
+            assert_eq!((arxiv.kind.clone(), arxiv.text.as_str()), (TokenKind::ArxivId, id));
+            assert_eq!(&text[arxiv.start..arxiv.end], scheme);
+        }
+    }
+
+    #[test]
+    fn old_arxiv_ids_without_a_slash_or_with_a_subcategory() {
+        for (text, id) in [
+            ("A. Author, hep-ph 0202058", "hep-ph/0202058"),
+            ("A. Author, hep ph/0202058", "hep-ph/0202058"),
+            ("A. Author, hep-ph / 0202058", "hep-ph/0202058"),
+            ("A. Author, math.AG/0202058", "math.AG/0202058"),
+            ("A. Author, math.dg/0412256", "math-dg/0412256"),
+        ] {
+            let tokens = tokenize(text);
+            let arxiv = tokens.iter().find(|t| t.kind == TokenKind::ArxivId).expect(text);
+            assert_eq!(arxiv.text, id, "{text}");
+        }
+    }
+
+    #[test]
+    fn handles() {
+        for (text, handle, span) in [
+            ("CDS, hdl:2078.1/123456.", "2078.1/123456", "hdl:2078.1/123456"),
+            ("see http://hdl.handle.net/10068/497522, 2009", "10068/497522", "http://hdl.handle.net/10068/497522"),
+        ] {
+            let tokens = tokenize(text);
+            let token = tokens.iter().find(|t| t.kind == TokenKind::Hdl).expect(text);
+            assert_eq!(token.text, handle);
+            assert_eq!(&text[token.start..token.end], span);
+            assert!(tokens.iter().all(|t| t.kind != TokenKind::Url), "{text}");
+        }
+    }
+
+    #[test]
+    fn urls_leave_trailing_punctuation_behind() {
+        for (text, url) in [
+            ("(see https://a.org/x).", "https://a.org/x"),
+            ("https://a.org/x, 2020", "https://a.org/x"),
+            ("at <https://a.org/x?q=1>.", "https://a.org/x?q=1"),
+            ("https://en.wikipedia.org/wiki/W_(boson).", "https://en.wikipedia.org/wiki/W_(boson)"),
+        ] {
+            let tokens = tokenize(text);
+            let token = tokens.iter().find(|t| t.kind == TokenKind::Url).expect(text);
+            assert_eq!(token.text, url);
+        }
+        let r = &crate::parse_one("A. Author, Code manual (see https://a.org/x).")[0];
+        assert_eq!(r.url.as_deref(), Some("https://a.org/x"));
+    }
+
+    #[test]
+    fn hyphen_variants_in_identifiers() {
+        for dash in ['\u{2010}', '\u{2011}', '\u{2012}', '\u{2212}', '–'] {
+            let text = normalize_dashes(&format!("A. Author, hep{dash}ph/0202058 and arXiv:0510213 [hep{dash}ph]"));
+            let ids: Vec<String> =
+                tokenize(&text).into_iter().filter(|t| t.kind == TokenKind::ArxivId).map(|t| t.text).collect();
+            assert_eq!(ids, ["hep-ph/0202058", "hep-ph/0510213"], "{dash:?}");
+        }
+        // Page ranges keep their en dash
+        assert_eq!(normalize_dashes("pp. 1–10, hep–th"), "pp. 1–10, hep-th");
+        let r = &crate::parse_one("A. Author, Phys. Rev. D 72, 052002 (2005), hep\u{2011}ph/0202058")[0];
+        assert_eq!(r.arxiv_id.as_deref(), Some("hep-ph/0202058"));
+    }
+}
@@ -9,7 +9,6 @@ pub struct PdfChar {
     pub width: f32,
     pub height: f32,
     pub font_size: f32,
-    #[allow(dead_code)]
     pub font_name: String,
 }
 
@@ -20,8 +19,31 @@ pub struct PageChars {
     pub width: f32,
     pub height: f32,
     pub chars: Vec<PdfChar>,
+    /// The short rule drawn above the footnotes, if the page has one
+    /// (`pdf::footnote_separator`). Blocks below it in its column are
+    /// footnotes.
+    pub footnote_separator: Option<FootnoteRule>,
+}
+
+/// A footnote separator rule, in page coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FootnoteRule {
+    pub x: f32,
+    pub width: f32,
+    pub y: f32,
+}
+
+impl FootnoteRule {
+    /// Whether `block` starts in the rule's column: at or right of the
+    /// rule's left end, before its right end.
+    pub fn in_column(&self, block: &Block) -> bool {
+        block.x >= self.x - RULE_MARGIN_TOLERANCE && block.x < self.x + self.width
+    }
 }
 
+/// How far (in points) a rule may start from the text's left margin.
+pub(crate) const RULE_MARGIN_TOLERANCE: f32 = 4.0;
+
 /// A word: sequence of characters forming a unit.
 #[derive(Debug, Clone)]
 pub struct Word {
@@ -33,6 +55,10 @@ pub struct Word {
     pub height: f32,
     pub font_size: f32,
     pub is_superscript: bool,
+    /// Set in an italic or oblique font.
+    pub is_italic: bool,
+    /// Most common font among the word's chars, e.g. "Times-Italic".
+    pub font_name: String,
 }
 
 /// A line of text: sequence of words on the same baseline.
@@ -43,6 +69,8 @@ pub struct Line {
     pub x_start: f32,
     pub x_end: f32,
     pub font_size: f32,
+    /// Font of most of the line's text.
+    pub font_name: String,
 }
 
 impl Line {
@@ -74,6 +102,29 @@ impl Block {
             .collect::<Vec<_>>()
             .join("\n")
     }
+
+    /// Runs of two or more consecutive italic words, continuing across
+    /// line breaks. A leading "et al." (often italic too) is dropped.
+    pub fn italic_runs(&self) -> Vec<String> {
+        let mut runs = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        let words = self.lines.iter().flat_map(|l| &l.words);
+        for word in words.map(Some).chain([None]) {
+            if let Some(word) = word && word.is_italic {
+                current.push(&word.text);
+                continue;
+            }
+            let mut run = current.as_slice();
+            if let ["et", al, rest @ ..] = run && al.starts_with("al") {
+                run = rest;
+            }
+            if run.len() >= 2 {
+                runs.push(run.join(" "));
+            }
+            current.clear();
+        }
+        runs
+    }
 }
 
 /// Zone classification for a block.
@@ -102,6 +153,21 @@ pub struct ZonedBlock {
 pub enum ReferenceSource {
     ReferenceSection,
     Footnote,
+    /// Plain-text input (no PDF layout).
+    Text,
+}
+
+/// What kind of work a reference cites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RefType {
+    Journal,
+    Book,
+    /// Conference proceedings and chapters in edited volumes.
+    Proceedings,
+    Thesis,
+    /// arXiv or report-number-only references.
+    Preprint,
+    Unknown,
 }
 
 /// A raw reference string before parsing.
@@ -109,8 +175,17 @@ pub enum ReferenceSource {
 pub struct RawReference {
     pub text: String,
     pub linemarker: Option<String>,
+    /// Marker of the entry a semicolon-separated piece was split from by
+    /// `split_semicolon_subrefs`; None on the entry's first piece.
+    pub from_marker: Option<String>,
     pub source: ReferenceSource,
     pub page_num: usize,
+    /// Index of the reference section the entry came from, counting
+    /// "References" headings from 0. Markers restart in each section.
+    pub section: usize,
+    /// Italic phrases of `text`, exactly as they appear in it. Only filled
+    /// for PDFs with `--title-from-italics`.
+    pub italic_runs: Vec<String>,
 }
 
 /// Token kinds for reference tokenization.
@@ -126,10 +201,20 @@ pub enum TokenKind {
     Number,
     PageRange,
     JournalName,
+    /// Collaboration name: "ATLAS Collaboration"; normalized is the
+    /// standardized name from the collaboration KB.
     Collaboration,
     Word,
     Punctuation,
     Ibid,
+    /// INSPIRE/SPIRES texkey: "Weinberg:1967tq".
+    Texkey,
+    /// Handle System identifier: "hdl:2078.1/123456", "https://hdl.handle.net/...".
+    Hdl,
+    /// Editor marker: "ed.", "eds.", "(Eds.)", "edited by".
+    Editor,
+    /// Issue number split off a compact volume: the "25" of "82(25)".
+    Issue,
 }
 
 /// A token in a reference string.
@@ -139,37 +224,105 @@ pub struct Token {
     pub text: String,
     /// The normalized form (for journal names, report numbers).
     pub normalized: Option<String>,
+    /// Byte range of the source text in the tokenized string.
+    pub start: usize,
+    pub end: usize,
 }
 
 /// A parsed reference ready for JSON output.
 #[derive(Debug, Clone, Serialize)]
 pub struct ParsedReference {
+    /// Emptied, and then omitted, by `drop_raw_refs` (`--no-raw`).
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub raw_ref: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub linemarker: Option<String>,
+    /// Marker of the entry a sub-reference was split from; None on the
+    /// primary reference. Group on `linemarker` to reassemble an entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_marker: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authors: Option<String>,
+    /// `authors` split into individual names.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors_list: Option<Vec<String>>,
+    /// The author list was truncated ("et al.", "and collaborators").
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub et_al: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editors: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub journal_title: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub journal_volume: Option<String>,
+    /// Issue number within the volume: "25" from "82(25)" or "vol. 82, no. 25".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub journal_issue: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub journal_year: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub journal_page: Option<String>,
+    /// Source substring the volume/year/page were read from, for auditing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeration_raw: Option<String>,
+    /// Publication status for forthcoming articles ("in press").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Set on sub-references citing a correction: "erratum", "addendum"
+    /// or "publisher's note".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub doi: Option<String>,
+    /// shortDOI as written ("10/abcde"). `doi` stays unset until DOI lookup
+    /// resolves it to the full DOI.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_doi: Option<String>,
+    /// Registrant part of `doi`: "10.1103" for APS, "10.1016" for Elsevier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doi_prefix: Option<String>,
+    /// Handle without resolver prefix: "2078.1/123456".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arxiv_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub texkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub isbn: Option<String>,
+    /// The first of `report_numbers`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub report_number: Option<String>,
+    /// Every report number, for references issued by several labs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub report_numbers: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub collaboration: Option<String>,
+    pub ref_type: RefType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// 0.0-1.0 estimate that this is a real citation (see `parse::confidence`).
+    pub confidence: f32,
     pub source: ReferenceSource,
+    /// 1-based PDF page the reference was collected from; 0 for text input.
+    pub found_on_page: usize,
+    /// Body pages citing this entry by its marker, with `--cited-on-pages`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cited_on_pages: Vec<usize>,
+    /// Reference section of a document with several, counted from 0; with
+    /// `linemarker` it identifies the entry. Omitted when 0.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub section: usize,
+    /// Signs of a mis-parse found by `parse::validation_warnings`, for
+    /// triage; the reference is kept either way.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
@@ -1,16 +1,83 @@
-use crate::types::{Block, ZoneKind, ZonedBlock};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::layout::{self, LayoutConfig, is_bold_font};
+use crate::markers;
+use crate::types::{Block, FootnoteRule, Line, PageChars, ZoneKind, ZonedBlock};
+
+/// Group every page into blocks and classify them into zones.
+pub fn zone_pages(page_chars: &[PageChars], config: &LayoutConfig) -> Vec<Vec<ZonedBlock>> {
+    let all_blocks = page_chars.iter().map(|page| layout::group_page(page, config)).collect();
+    let pages = page_chars.iter().map(|pc| (pc.page_num, pc.height, pc.footnote_separator));
+    zone_grouped(all_blocks, pages, config)
+}
+
+/// Classify blocks from another layout engine into zones. `pages[i]` is
+/// page `i + 1`; every page is `page_height` points tall. Without
+/// separator rules, footnotes are told apart by font size alone.
+pub fn zone_blocks(pages: Vec<Vec<Block>>, page_height: f32, config: &LayoutConfig) -> Vec<Vec<ZonedBlock>> {
+    let info: Vec<_> = (1..=pages.len()).map(|page_num| (page_num, page_height, None)).collect();
+    zone_grouped(pages, info, config)
+}
+
+/// `pages` gives each page's number, height and footnote separator.
+fn zone_grouped(
+    mut all_blocks: Vec<Vec<Block>>,
+    pages: impl IntoIterator<Item = (usize, f32, Option<FootnoteRule>)>,
+    config: &LayoutConfig,
+) -> Vec<Vec<ZonedBlock>> {
+    let pages: Vec<_> = pages.into_iter().collect();
+    // Blank filler and cover pages keep their slot, so page numbers stay
+    // right. Covers only lead the document itself, not a `--pages` range,
+    // and a sparse page with references is never blank.
+    let from_first_page = pages.first().is_some_and(|&(page_num, _, _)| page_num == 1);
+    let filler = layout::filler_pages(&all_blocks, from_first_page);
+    for (blocks, filler) in all_blocks.iter_mut().zip(filler) {
+        if filler && !holds_references(blocks) {
+            blocks.clear();
+        }
+    }
+    let body_font_size = compute_body_font_size(&all_blocks);
+    pages
+        .into_iter()
+        .zip(all_blocks.iter())
+        .map(|((page_num, height, separator), blocks)| {
+            classify_page(blocks, page_num, height, separator, body_font_size, config)
+        })
+        .collect()
+}
+
+/// A reference heading or a numbered entry on the page.
+fn holds_references(blocks: &[Block]) -> bool {
+    blocks.iter().any(|block| {
+        markers::has_any_marker(block)
+            || block.lines.iter().any(|line| is_reference_heading_line(&line.text(), &[]))
+    })
+}
 
 /// Classify blocks on a page into zones based on position and font.
+/// With a `footnote_separator` (the rule drawn above the footnotes),
+/// every block in its column is a footnote below it and not above it,
+/// whatever its font. Blocks in other columns are classified by font.
 pub fn classify_page(
     blocks: &[Block],
     page_num: usize,
     page_height: f32,
+    footnote_separator: Option<FootnoteRule>,
     body_font_size: f32,
+    config: &LayoutConfig,
 ) -> Vec<ZonedBlock> {
     blocks
         .iter()
         .map(|block| {
-            let zone = classify_block(block, page_height, body_font_size);
+            let rule = footnote_separator.filter(|rule| rule.in_column(block));
+            let zone = match classify_block(block, page_height, body_font_size, config) {
+                ZoneKind::Body | ZoneKind::Footnote if rule.is_some_and(|rule| block.y < rule.y) => {
+                    ZoneKind::Footnote
+                }
+                ZoneKind::Footnote if rule.is_some() => ZoneKind::Body,
+                zone => zone,
+            };
             ZonedBlock {
                 block: block.clone(),
                 zone,
@@ -24,24 +91,25 @@ fn classify_block(
     block: &Block,
     page_height: f32,
     body_font_size: f32,
+    config: &LayoutConfig,
 ) -> ZoneKind {
     let relative_y = block.y / page_height;
     let block_bottom = (block.y - block.height) / page_height;
 
     // Header: top ~5%
-    if relative_y > 0.95 {
+    if relative_y > config.header_band {
         return ZoneKind::Header;
     }
 
     // Page number: bottom ~3%, only digits
-    if block_bottom < 0.03 && is_page_number(block) {
+    if block_bottom < config.page_number_band && is_page_number(block) {
         return ZoneKind::PageNumber;
     }
 
-    // Footnote: bottom ~25%, smaller font, starts with superscript marker
-    if block_bottom < 0.25
-        && block.font_size < body_font_size * 0.9
-        && has_superscript_start(block)
+    // Footnote: bottom ~25%, smaller font, starts with a footnote marker
+    if block_bottom < config.footnote_band
+        && block.font_size < body_font_size * config.footnote_font_ratio
+        && (has_superscript_start(block) || has_footnote_marker_start(block))
     {
         return ZoneKind::Footnote;
     }
@@ -63,17 +131,70 @@ fn has_superscript_start(block: &Block) -> bool {
         .is_some_and(|word| word.is_superscript)
 }
 
+/// Symbols marking footnotes set in the regular font: "*", "∗", "†", "‡", "§".
+pub(crate) const FOOTNOTE_SYMBOLS: &[char] = &['*', '∗', '†', '‡', '§'];
+
+/// A regular-font footnote marker: "[1]", "1." or a footnote symbol.
+fn has_footnote_marker_start(block: &Block) -> bool {
+    static MARKER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:\[\d{1,3}\]|\d{1,3}\.(?:\s|$))").unwrap());
+    let Some(word) = block.lines.first().and_then(|line| line.words.first()) else {
+        return false;
+    };
+    word.text.starts_with(FOOTNOTE_SYMBOLS) || MARKER_RE.is_match(&word.text)
+}
+
 /// Detect if a block is a "References" / "Bibliography" heading.
-pub fn is_reference_heading(block: &Block) -> bool {
+/// `extra` holds further accepted titles in `normalize_heading` form
+/// (`--ref-heading`).
+pub fn is_reference_heading(block: &Block, extra: &[String]) -> bool {
     let text = block.text().to_uppercase();
     let trimmed = text.trim();
-    is_heading_text(trimmed)
+    is_heading_text(trimmed, extra) || (block.lines.len() <= 2 && is_bold_heading(&block.lines, extra))
 }
 
 /// Check if a single line's text is a reference heading.
-pub fn is_reference_heading_line(line_text: &str) -> bool {
+pub fn is_reference_heading_line(line_text: &str, extra: &[String]) -> bool {
     let trimmed = line_text.trim().to_uppercase();
-    is_heading_text(&trimmed)
+    is_heading_text(&trimmed, extra)
+}
+
+/// A single line set in bold that reads as a reference heading.
+pub fn is_bold_heading_line(line: &Line, extra: &[String]) -> bool {
+    is_bold_heading(std::slice::from_ref(line), extra)
+}
+
+/// A user-supplied heading in the form headings are compared in:
+/// uppercased, single-spaced, without a trailing colon or period.
+/// "Works  cited:" → "WORKS CITED"
+pub fn normalize_heading(heading: &str) -> String {
+    let words: Vec<&str> = heading.split_whitespace().collect();
+    words.join(" ").to_uppercase().trim_end_matches([':', '.']).to_string()
+}
+
+/// Bold headings are accepted in forms the text rules reject: a Roman
+/// numeral or appendix letter before the title ("VII. References",
+/// "A References"), or small caps split into "R EFERENCES". The lines must
+/// be short and entirely bold, so a bold "References" in prose doesn't
+/// count (callers still check that citations follow).
+fn is_bold_heading(lines: &[Line], extra: &[String]) -> bool {
+    static SECTION_PREFIX_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?:\d{1,2}|[IVXL]{1,5}|[A-Z])[.)]?\s+").unwrap());
+    if lines.is_empty() || !lines.iter().all(|l| is_bold_font(&l.font_name)) {
+        return false;
+    }
+    let text = lines.iter().map(|l| l.text()).collect::<Vec<_>>().join(" ").to_uppercase();
+    if text.chars().count() > 40 || has_dot_leaders(&text) {
+        return false;
+    }
+    let text = text.trim().trim_end_matches([':', '.']);
+    // "R EFERENCES" also looks like an appendix letter, so try both readings.
+    let unnumbered = SECTION_PREFIX_RE.find(text).map(|m| &text[m.end()..]);
+    [Some(text), unnumbered].into_iter().flatten().any(|title| {
+        let collapsed: String = title.split_whitespace().collect();
+        is_heading_word(title, extra)
+            || is_heading_word(&collapsed, extra)
+            || matches!(title, "REFERENCES AND NOTES" | "LITERATURE CITED" | "REFERENCES CITED")
+    })
 }
 
 /// Strip trailing parenthesized number ranges: "(36)-(84)", "(1)-(35)"
@@ -157,7 +278,26 @@ fn has_dot_leaders(text: &str) -> bool {
     false
 }
 
-fn is_heading_text(text: &str) -> bool {
+/// Single-word reference section titles, uppercased. English plus the
+/// French, German, Spanish and Italian forms seen in non-English HEP papers;
+/// all languages are always accepted.
+const HEADING_WORDS: &[&str] = &[
+    "REFERENCES",
+    "BIBLIOGRAPHY",
+    "RÉFÉRENCES",
+    "BIBLIOGRAPHIE",
+    "LITERATUR",
+    "LITERATURVERZEICHNIS",
+    "REFERENCIAS",
+    "BIBLIOGRAFIA",
+    "BIBLIOGRAFÍA",
+];
+
+fn is_heading_word(text: &str, extra: &[String]) -> bool {
+    HEADING_WORDS.contains(&text) || extra.iter().any(|h| h == text)
+}
+
+fn is_heading_text(text: &str, extra: &[String]) -> bool {
     // Reject TOC entries: lines with dot leaders like "References . . . . ." or "References....."
     // Three or more consecutive dots (with optional spaces between) indicate a TOC page entry.
     if has_dot_leaders(text) {
@@ -168,16 +308,15 @@ fn is_heading_text(text: &str) -> bool {
     let text = text.trim_end_matches([':', '.']);
     let text = strip_trailing_paren_range(text);
     // Exact matches
-    if matches!(
-        text,
-        "REFERENCES"
-            | "BIBLIOGRAPHY"
-            | "REFERENCES AND NOTES"
-            | "LITERATURE CITED"
-    ) {
+    if is_heading_word(text, extra)
+        || matches!(
+            text,
+            "REFERENCES AND NOTES" | "LITERATURE CITED" | "RÉFÉRENCES BIBLIOGRAPHIQUES"
+        )
+    {
         return true;
     }
-    if text.len() >= 30 {
+    if text.chars().count() >= 30 {
         return false;
     }
     // Accept section-numbered headings: "IX. REFERENCES", "5. REFERENCES"
@@ -189,7 +328,7 @@ fn is_heading_text(text: &str) -> bool {
         .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == ' ')
         .collect::<String>();
     let stripped = &text[prefix.len()..];
-    if stripped == "REFERENCES" || stripped == "BIBLIOGRAPHY" {
+    if is_heading_word(stripped, extra) {
         // Prefix must end with space/dot before heading (line numbers always do)
         let has_separator = prefix.ends_with(' ') || prefix.ends_with('.');
         let digit_count = prefix.chars().filter(|c| c.is_ascii_digit()).count();
@@ -203,33 +342,270 @@ fn is_heading_text(text: &str) -> bool {
         .collect::<String>();
     let suffix_len = suffix.len();
     let stripped = text[..text.len() - suffix_len].trim_end();
-    if stripped == "REFERENCES" || stripped == "BIBLIOGRAPHY" {
+    if is_heading_word(stripped, extra) {
         let digit_count = suffix.chars().filter(|c| c.is_ascii_digit()).count();
         return digit_count <= 1;
     }
     false
 }
 
-/// Compute the dominant (most common) font size across all pages.
+/// Compute the body font size: the most common size across all pages,
+/// counted in characters and bucketed to 0.1pt. When the two largest
+/// buckets are within `BODY_FONT_TIE_TENTHS` of each other, the mode is
+/// unreliable (a caption size can edge out the body), so the
+/// character-weighted median is used instead.
 pub fn compute_body_font_size(all_blocks: &[Vec<Block>]) -> f32 {
     let mut size_counts: Vec<(i32, usize)> = Vec::new();
     for blocks in all_blocks {
         for block in blocks {
             for line in &block.lines {
-                let key = (line.font_size * 10.0) as i32;
-                if let Some(entry) =
-                    size_counts.iter_mut().find(|(k, _)| *k == key)
-                {
-                    entry.1 += line.words.len();
-                } else {
-                    size_counts.push((key, line.words.len()));
+                for word in &line.words {
+                    let key = (word.font_size * 10.0) as i32;
+                    let chars = word.text.chars().count();
+                    if let Some(entry) = size_counts.iter_mut().find(|(k, _)| *k == key) {
+                        entry.1 += chars;
+                    } else {
+                        size_counts.push((key, chars));
+                    }
                 }
             }
         }
     }
-    size_counts
-        .iter()
-        .max_by_key(|(_, count)| *count)
-        .map(|(key, _)| *key as f32 / 10.0)
-        .unwrap_or(10.0)
+    // Largest bucket first; ties go to the smaller size.
+    size_counts.sort_by_key(|&(key, count)| (std::cmp::Reverse(count), key));
+    let key = match size_counts[..] {
+        [] => return 10.0,
+        [(first, _), (second, _), ..] if (first - second).abs() <= BODY_FONT_TIE_TENTHS => {
+            median_size(&mut size_counts)
+        }
+        [(first, _), ..] => first,
+    };
+    key as f32 / 10.0
+}
+
+/// Two most common font sizes closer than this (in 0.1pt) count as a tie.
+const BODY_FONT_TIE_TENTHS: i32 = 2;
+
+/// Character-weighted median of `(size, chars)` buckets.
+fn median_size(size_counts: &mut [(i32, usize)]) -> i32 {
+    size_counts.sort_by_key(|&(key, _)| key);
+    let total: usize = size_counts.iter().map(|(_, count)| count).sum();
+    let mut seen = 0;
+    for &(key, count) in size_counts.iter() {
+        seen += count;
+        if seen * 2 >= total {
+            return key;
+        }
+    }
+    size_counts.last().map_or(100, |&(key, _)| key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headings_in_other_languages() {
+        for heading in [
+            "Références",
+            "Bibliographie",
+            "Literatur",
+            "Literaturverzeichnis",
+            "Referencias",
+            "Bibliografía",
+            "Bibliografia",
+        ] {
+            assert!(is_reference_heading_line(heading, &[]), "{heading}");
+        }
+    }
+
+    #[test]
+    fn numbered_headings_in_other_languages() {
+        assert!(is_reference_heading_line("5. LITERATUR", &[]));
+        assert!(is_reference_heading_line("3. Références", &[]));
+        assert!(is_reference_heading_line("7 Bibliografia", &[]));
+    }
+
+    #[test]
+    fn accented_toc_entries_and_running_headers_rejected() {
+        assert!(!is_reference_heading_line("Références . . . . . . . 42", &[]));
+        assert!(!is_reference_heading_line("Literaturverzeichnis....... 117", &[]));
+        assert!(!is_reference_heading_line("RÉFÉRENCES 835", &[]));
+    }
+
+    #[test]
+    fn extra_headings_are_accepted() {
+        let extra = [normalize_heading("Works  cited:"), normalize_heading("Sources")];
+        assert_eq!(extra[0], "WORKS CITED");
+        assert!(!is_reference_heading_line("Works Cited", &[]));
+        assert!(is_reference_heading_line("Works Cited", &extra));
+        assert!(is_reference_heading_line("6. SOURCES", &extra));
+        assert!(!is_reference_heading_line("Works Cited . . . . . 42", &extra));
+        assert!(!is_reference_heading_line("Sources of systematic uncertainty", &extra));
+    }
+
+    fn footnote_block(font_size: f32) -> Block {
+        let marker = crate::types::Word {
+            text: "1".into(),
+            x: 72.0,
+            y: 80.0,
+            width: 4.0,
+            height: 6.0,
+            font_size: 6.0,
+            is_superscript: true,
+            is_italic: false,
+            font_name: "Times-Roman".into(),
+        };
+        let text = crate::types::Word { text: "See A. Author, Phys. Rev. D 7, 2333 (1973)".into(), x: 78.0, ..marker.clone() };
+        let line = crate::types::Line { words: vec![marker, text], y: 80.0, x_start: 72.0, x_end: 300.0, font_size, font_name: "Times-Roman".into() };
+        Block { lines: vec![line], x: 72.0, y: 80.0, width: 228.0, height: 10.0, font_size }
+    }
+
+    #[test]
+    fn body_font_size_from_char_counts() {
+        // One block per (size, chars): a single word of that many chars
+        let page = |sizes: &[(f32, usize)]| -> Vec<Block> {
+            sizes
+                .iter()
+                .map(|&(size, chars)| {
+                    let word = crate::types::Word {
+                        text: "x".repeat(chars),
+                        x: 72.0,
+                        y: 500.0,
+                        width: chars as f32 * size / 2.0,
+                        height: size,
+                        font_size: size,
+                        is_superscript: false,
+                        is_italic: false,
+                        font_name: "Times-Roman".into(),
+                    };
+                    let line = Line { words: vec![word], y: 500.0, x_start: 72.0, x_end: 400.0, font_size: size, font_name: "Times-Roman".into() };
+                    Block { lines: vec![line], x: 72.0, y: 500.0, width: 328.0, height: size, font_size: size }
+                })
+                .collect()
+        };
+        // Short 7pt words outnumber the body's words but not its chars
+        let mut blocks = page(&[(10.0, 12), (10.0, 12), (10.0, 12)]);
+        blocks.extend(page(&[(7.0, 1); 20]));
+        assert_eq!(compute_body_font_size(&[blocks]), 10.0);
+
+        // Caption sizes 8.0/8.1 narrowly lead; the median sits in the body text
+        let mixed = page(&[(8.0, 30), (8.1, 29), (10.0, 28), (10.2, 27), (10.4, 26)]);
+        assert_eq!(compute_body_font_size(&[mixed]), 10.0);
+        // A clear mode is kept
+        let clear = page(&[(8.0, 30), (10.0, 80), (12.0, 40)]);
+        assert_eq!(compute_body_font_size(&[clear]), 10.0);
+        assert_eq!(compute_body_font_size(&[]), 10.0);
+    }
+
+    #[test]
+    fn bold_headings() {
+        let heading = |text: &str, font: &str| {
+            let words = text
+                .split(' ')
+                .map(|w| crate::types::Word {
+                    text: w.into(),
+                    x: 72.0,
+                    y: 700.0,
+                    width: 40.0,
+                    height: 12.0,
+                    font_size: 12.0,
+                    is_superscript: false,
+                    is_italic: false,
+                    font_name: font.into(),
+                })
+                .collect();
+            let line = Line { words, y: 700.0, x_start: 72.0, x_end: 200.0, font_size: 12.0, font_name: font.into() };
+            Block { lines: vec![line], x: 72.0, y: 700.0, width: 128.0, height: 12.0, font_size: 12.0 }
+        };
+        for text in ["VII. References", "A References", "R EFERENCES", "References Cited:"] {
+            assert!(is_reference_heading(&heading(text, "ABCDEF+Times-Bold"), &[]), "{text}");
+            assert!(!is_reference_heading(&heading(text, "Times-Roman"), &[]), "{text} needs bold");
+        }
+        assert!(is_bold_heading_line(&heading("VII. References", "CMBX12").lines[0], &[]));
+        assert!(!is_reference_heading(&heading("References to earlier work are listed below", "Times-Bold"), &[]));
+        assert!(!is_reference_heading(&heading("VII. References . . . . . 42", "Times-Bold"), &[]));
+    }
+
+    #[test]
+    fn large_footnotes_need_a_looser_font_ratio() {
+        // Footnotes at 0.95x the body font
+        let block = footnote_block(9.5);
+        assert_eq!(classify_block(&block, 792.0, 10.0, &LayoutConfig::default()), ZoneKind::Body);
+        let tuned = LayoutConfig { footnote_font_ratio: 0.97, ..LayoutConfig::default() };
+        assert_eq!(classify_block(&block, 792.0, 10.0, &tuned), ZoneKind::Footnote);
+    }
+
+    #[test]
+    fn footnote_band_limits_footnote_height() {
+        let block = Block { y: 300.0, ..footnote_block(8.0) };
+        assert_eq!(classify_block(&block, 792.0, 10.0, &LayoutConfig::default()), ZoneKind::Body);
+        let tuned = LayoutConfig { footnote_band: 0.4, ..LayoutConfig::default() };
+        assert_eq!(classify_block(&block, 792.0, 10.0, &tuned), ZoneKind::Footnote);
+    }
+
+    #[test]
+    fn regular_font_footnote_markers() {
+        for marker in ["*", "†", "[2]", "3."] {
+            let mut block = footnote_block(8.0);
+            let first = &mut block.lines[0].words[0];
+            first.text = marker.into();
+            first.is_superscript = false;
+            assert_eq!(classify_block(&block, 792.0, 10.0, &LayoutConfig::default()), ZoneKind::Footnote, "{marker}");
+            // Body-size text is never a footnote
+            let block = Block { font_size: 10.0, ..block };
+            assert_eq!(classify_block(&block, 792.0, 10.0, &LayoutConfig::default()), ZoneKind::Body, "{marker}");
+        }
+        let mut block = footnote_block(8.0);
+        block.lines[0].words[0].is_superscript = false;
+        assert_eq!(classify_block(&block, 792.0, 10.0, &LayoutConfig::default()), ZoneKind::Body);
+    }
+    #[test]
+    fn separator_rule_decides_footnotes() {
+        let config = LayoutConfig::default();
+        // Body-size text below the rule is a footnote
+        let below = footnote_block(10.0);
+        let rule = FootnoteRule { x: 72.0, width: 144.0, y: 100.0 };
+        let zones = classify_page(std::slice::from_ref(&below), 1, 792.0, Some(rule), 10.0, &config);
+        assert_eq!(zones[0].zone, ZoneKind::Footnote);
+        let zones = classify_page(&[below], 1, 792.0, None, 10.0, &config);
+        assert_eq!(zones[0].zone, ZoneKind::Body);
+        // Small text above the rule is not
+        let above = Block { y: 110.0, ..footnote_block(8.0) };
+        let zones = classify_page(std::slice::from_ref(&above), 1, 792.0, None, 10.0, &config);
+        assert_eq!(zones[0].zone, ZoneKind::Footnote);
+        let zones = classify_page(std::slice::from_ref(&above), 1, 792.0, Some(rule), 10.0, &config);
+        assert_eq!(zones[0].zone, ZoneKind::Body);
+        // A rule in the other column leaves the block to its font
+        let right = FootnoteRule { x: 316.0, ..rule };
+        let zones = classify_page(&[above], 1, 792.0, Some(right), 10.0, &config);
+        assert_eq!(zones[0].zone, ZoneKind::Footnote);
+    }
+    #[test]
+    fn sparse_pages_with_references_are_kept() {
+        let block = |text: &str, y: f32| {
+            let word = crate::types::Word {
+                text: text.into(),
+                x: 72.0,
+                y,
+                width: 5.0 * text.len() as f32,
+                height: 10.0,
+                font_size: 10.0,
+                is_superscript: false,
+                is_italic: false,
+                font_name: "Times-Roman".into(),
+            };
+            let line = Line { words: vec![word], y, x_start: 72.0, x_end: 500.0, font_size: 10.0, font_name: "Times-Roman".into() };
+            Block { lines: vec![line], x: 72.0, y, width: 428.0, height: 12.0, font_size: 10.0 }
+        };
+        let body = "The quick brown fox jumps over the lazy dog again and again.";
+        let pages = vec![
+            vec![block("A Thesis on Foxes", 700.0)],
+            vec![block("References", 700.0), block("[1] A. Author, Phys. Rev. D 7, 1 (1973).", 680.0)],
+            (0..12).map(|i| block(body, 700.0 - 14.0 * i as f32)).collect(),
+        ];
+        let zoned = zone_blocks(pages, 792.0, &LayoutConfig::default());
+        assert!(zoned[0].is_empty());
+        assert_eq!(zoned[1].len(), 2);
+    }
 }